@@ -0,0 +1,57 @@
+//! Serve the bundled chart and klines fixtures on a local port
+//!
+//! Run `cargo run --example mock_server --features mock -- 8080`
+//! then point finfo at it with
+//! `FINFO_URL=http://127.0.0.1:8080 finfo AAPL`
+//!
+//! With the `websocket` feature the recorded trades are also
+//! streamed on the next port, such as with
+//! `FINFO_BINANCE_STREAM_URL=ws://127.0.0.1:8081 finfo -S BTC-USD`
+
+use finfo::data::mock;
+
+fn main() {
+    let port = std::env::args().nth(1).unwrap_or(String::from("8080"));
+    let chart = mock::to_chart_handler(mock::get_fixtures_dir().join("yahoo"));
+    #[cfg(feature = "http")]
+    let handler = {
+        let klines = mock::to_klines_handler(mock::get_fixtures_dir());
+        move |path: &str| match path.starts_with("/api/") {
+            true => klines(path),
+            false => chart(path),
+        }
+    };
+    #[cfg(not(feature = "http"))]
+    let handler = chart;
+    match mock::MockServer::bind(&format!("127.0.0.1:{}", port), handler) {
+        Ok(server) => {
+            println!("Serving fixtures at {}", server.url);
+            #[cfg(feature = "websocket")]
+            let _socket = {
+                let port = port.parse::<u16>().unwrap_or(8080) + 1;
+                let trades = std::fs::read_to_string(
+                    mock::get_fixtures_dir().join("binance/BTCUSDT-trades.jsonl"),
+                )
+                .unwrap_or_default();
+                let messages = trades.lines().map(str::to_string).collect();
+                match mock::MockSocket::bind(&format!("127.0.0.1:{}", port), messages) {
+                    Ok(socket) => {
+                        println!("Streaming trades at {}", socket.url);
+                        Some(socket)
+                    }
+                    Err(err) => {
+                        eprintln!("mock_server: {}", err);
+                        None
+                    }
+                }
+            };
+            loop {
+                std::thread::park();
+            }
+        }
+        Err(err) => {
+            eprintln!("mock_server: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
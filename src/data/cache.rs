@@ -0,0 +1,390 @@
+//! Bars of a remote provider cached in local files
+//!
+//! Local data files always win. Otherwise bars are read from
+//! the cache while it is fresh, then fetched again and cached
+//! as `TICKER.csv`. Splits and dividends are cached the same
+//! way as `actions/TICKER.csv`, and the exchange code of a
+//! ticker as `exchanges/TICKER.txt` which never goes stale.
+//! Offline only the files are read, and without a cache
+//! nothing is written and old cache files are deleted.
+
+use super::csv;
+use super::file::FileProvider;
+use super::Provider;
+use crate::error::{Error, Result};
+use crate::price::action::Action;
+use crate::price::OHLC;
+use crate::utils::stream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Seconds a cache file stays fresh when none are given
+pub const DEFAULT_MAX_AGE: u64 = 15 * 60;
+
+/// Provider of bars of a remote provider cached in files
+pub struct CacheProvider<P: Provider> {
+    pub remote: P,
+    /// Files of the user which are never written
+    pub data: FileProvider,
+    pub cache: FileProvider,
+    /// Never ask the remote provider
+    pub offline: bool,
+    /// Never read or write the cache
+    pub nocache: bool,
+    /// Time a cache file stays fresh
+    pub max_age: Duration,
+}
+
+impl<P: Provider> CacheProvider<P> {
+    pub fn new(remote: P, data_dir: PathBuf, cache_dir: PathBuf) -> Self {
+        Self {
+            remote,
+            data: FileProvider::new(data_dir),
+            cache: FileProvider::new(cache_dir),
+            offline: false,
+            nocache: false,
+            max_age: Duration::from_secs(DEFAULT_MAX_AGE),
+        }
+    }
+
+    /// Returns `true` if the cache file of a ticker was written
+    /// less than `max_age` ago
+    fn is_fresh(&self, path: &std::path::Path) -> bool {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age <= self.max_age)
+    }
+
+    /// Write the bars of a ticker to its cache file
+    fn set_cache(&self, ticker: &str, bars: &[OHLC]) -> Result<()> {
+        let path = self
+            .cache
+            .dir
+            .join(format!("{}.csv", ticker.to_uppercase()));
+        let to_error = |err: std::io::Error| Error::Io {
+            path: path.clone(),
+            message: err.to_string(),
+        };
+        std::fs::create_dir_all(&self.cache.dir).map_err(to_error)?;
+        std::fs::write(&path, csv::to_csv(bars)).map_err(to_error)?;
+        stream::debug(&format!(
+            "Cached {} bars of {} at {}",
+            bars.len(),
+            ticker,
+            path.display()
+        ));
+        Ok(())
+    }
+
+    /// Write the actions of a ticker to its cache file
+    fn set_actions(&self, ticker: &str, actions: &[Action]) -> Result<()> {
+        let path = self.cache.get_actions_path(ticker);
+        let to_error = |err: std::io::Error| Error::Io {
+            path: path.clone(),
+            message: err.to_string(),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(to_error)?;
+        }
+        std::fs::write(&path, csv::to_actions(actions)).map_err(to_error)?;
+        stream::debug(&format!(
+            "Cached {} actions of {} at {}",
+            actions.len(),
+            ticker,
+            path.display()
+        ));
+        Ok(())
+    }
+
+    /// Get the path of the cached exchange code of a ticker
+    fn get_exchange_path(&self, ticker: &str) -> PathBuf {
+        self.cache
+            .dir
+            .join("exchanges")
+            .join(format!("{}.txt", ticker.to_uppercase()))
+    }
+
+    /// Write the exchange code of a ticker to its cache file
+    fn set_exchange(&self, ticker: &str, exchange: &str) -> Result<()> {
+        let path = self.get_exchange_path(ticker);
+        let to_error = |err: std::io::Error| Error::Io {
+            path: path.clone(),
+            message: err.to_string(),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(to_error)?;
+        }
+        std::fs::write(&path, format!("{}\n", exchange)).map_err(to_error)
+    }
+
+    /// Delete a cache file when there is no cache
+    fn remove_cache(&self, path: &std::path::Path) {
+        if self.nocache && path.exists() {
+            if let Err(err) = std::fs::remove_file(path) {
+                stream::warn(&format!("{}: {}", path.display(), err));
+            }
+            stream::debug(&format!("Deleted cache {}", path.display()));
+        }
+    }
+}
+
+impl<P: Provider> Provider for CacheProvider<P> {
+    /// Get every ticker with a data or cache file sorted by name
+    fn get_tickers(&self) -> Vec<String> {
+        let mut tickers = self.data.get_tickers();
+        if !self.nocache {
+            tickers.extend(self.cache.get_tickers());
+        }
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        if self.data.get_path(ticker).exists() {
+            return self.data.get_bars(ticker);
+        }
+
+        let path = self.cache.get_path(ticker);
+        self.remove_cache(&path);
+        let is_cached = !self.nocache && path.exists();
+        if is_cached && (self.offline || self.is_fresh(&path)) {
+            stream::trace(&format!("Cache hit for {} at {}", ticker, path.display()));
+            return self.cache.get_bars(ticker);
+        }
+        if self.offline {
+            return Err(Error::NotFound(ticker.to_uppercase()));
+        }
+
+        match self.remote.get_bars(ticker) {
+            Ok(bars) => {
+                // Nothing is cached so the next request asks again
+                if !self.nocache && !bars.is_empty() {
+                    if let Err(err) = self.set_cache(ticker, &bars) {
+                        stream::warn(&err.to_string());
+                    }
+                }
+                Ok(bars)
+            }
+            // A stale cache beats no bars at all
+            Err(err) if is_cached && !matches!(err, Error::NotFound(_)) => {
+                stream::warn(&format!("{}: {}; using the cache", ticker, err));
+                self.cache.get_bars(ticker)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the actions of a ticker like its bars
+    ///
+    /// Tickers without any actions are cached too
+    fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+        if self.data.get_path(ticker).exists() {
+            return self.data.get_actions(ticker);
+        }
+
+        let path = self.cache.get_actions_path(ticker);
+        self.remove_cache(&path);
+        let is_cached = !self.nocache && path.exists();
+        if is_cached && (self.offline || self.is_fresh(&path)) {
+            return self.cache.get_actions(ticker);
+        }
+        if self.offline {
+            return Ok(vec![]);
+        }
+
+        match self.remote.get_actions(ticker) {
+            Ok(actions) => {
+                if !self.nocache {
+                    if let Err(err) = self.set_actions(ticker, &actions) {
+                        stream::warn(&err.to_string());
+                    }
+                }
+                Ok(actions)
+            }
+            Err(err) if is_cached && !matches!(err, Error::NotFound(_)) => {
+                stream::warn(&format!("{}: {}; using the cache", ticker, err));
+                self.cache.get_actions(ticker)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get the exchange code of a ticker from the cache once
+    /// fetched
+    ///
+    /// Tickers of data files have no known exchange
+    fn get_exchange(&self, ticker: &str) -> Result<Option<String>> {
+        if self.data.get_path(ticker).exists() {
+            return Ok(None);
+        }
+
+        let path = self.get_exchange_path(ticker);
+        self.remove_cache(&path);
+        if !self.nocache {
+            if let Ok(exchange) = std::fs::read_to_string(&path) {
+                return Ok(Some(exchange.trim().to_string()).filter(|code| !code.is_empty()));
+            }
+        }
+        if self.offline {
+            return Ok(None);
+        }
+
+        let exchange = self.remote.get_exchange(ticker)?;
+        if let (false, Some(exchange)) = (self.nocache, &exchange) {
+            if let Err(err) = self.set_exchange(ticker, exchange) {
+                stream::warn(&err.to_string());
+            }
+        }
+        Ok(exchange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Provider of one bar counting its requests
+    struct MockProvider {
+        requests: Cell<usize>,
+        online: bool,
+    }
+
+    impl Provider for MockProvider {
+        fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+            self.requests.set(self.requests.get() + 1);
+            match (self.online, ticker) {
+                (false, _) => Err(Error::Invalid(String::from("down"))),
+                (true, "AAPL") => Ok(vec![OHLC::new(1.0, 2.0, 0.5, 1.5).at(60)]),
+                _ => Err(Error::NotFound(ticker.to_string())),
+            }
+        }
+
+        fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+            self.requests.set(self.requests.get() + 1);
+            match (self.online, ticker) {
+                (false, _) => Err(Error::Invalid(String::from("down"))),
+                (true, "AAPL") => Ok(vec![Action::Dividend {
+                    time: 60,
+                    amount: 0.5,
+                }]),
+                _ => Ok(vec![]),
+            }
+        }
+
+        fn get_exchange(&self, ticker: &str) -> Result<Option<String>> {
+            self.requests.set(self.requests.get() + 1);
+            Ok((ticker == "AAPL").then(|| String::from("NMS")))
+        }
+    }
+
+    fn provider(name: &str, online: bool) -> CacheProvider<MockProvider> {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        let remote = MockProvider {
+            requests: Cell::new(0),
+            online,
+        };
+        CacheProvider::new(remote, dir.join("data"), dir.join("cache"))
+    }
+
+    #[test]
+    fn test_get_bars() {
+        let mut provider = provider("finfo-test-cache", true);
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 1);
+        assert!(provider.cache.get_path("AAPL").exists());
+        // Fresh cache is a hit
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 1);
+        assert_eq!(provider.remote.requests.get(), 1);
+        assert_eq!(provider.get_tickers(), vec!["AAPL"]);
+
+        // Stale cache is fetched again
+        provider.max_age = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(10));
+        provider.get_bars("AAPL").unwrap();
+        assert_eq!(provider.remote.requests.get(), 2);
+
+        // Data files win over the remote provider
+        std::fs::write(provider.data.dir.join("MSFT.csv"), "60,1,2,0.5,1.5\n").unwrap();
+        assert_eq!(provider.get_bars("MSFT").unwrap().len(), 1);
+        assert_eq!(provider.remote.requests.get(), 2);
+        assert_eq!(
+            provider.get_bars("NOPE"),
+            Err(Error::NotFound(String::from("NOPE")))
+        );
+
+        // No cache deletes the file and always fetches
+        provider.nocache = true;
+        provider.get_bars("AAPL").unwrap();
+        assert!(!provider.cache.get_path("AAPL").exists());
+        assert_eq!(provider.remote.requests.get(), 4);
+        std::fs::remove_dir_all(provider.cache.dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_get_bars_offline() {
+        let mut provider = provider("finfo-test-cache-offline", false);
+        provider.offline = true;
+        assert_eq!(
+            provider.get_bars("AAPL"),
+            Err(Error::NotFound(String::from("AAPL")))
+        );
+
+        // Stale cache is still read offline and when the
+        // remote provider fails
+        provider
+            .set_cache("AAPL", &[OHLC::new(1.0, 2.0, 0.5, 1.5)])
+            .unwrap();
+        provider.max_age = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 1);
+        assert_eq!(provider.remote.requests.get(), 0);
+        provider.offline = false;
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 1);
+        assert_eq!(provider.remote.requests.get(), 1);
+        std::fs::remove_dir_all(provider.cache.dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_get_actions() {
+        let mut provider = provider("finfo-test-cache-actions", true);
+        assert_eq!(provider.get_actions("AAPL").unwrap().len(), 1);
+        assert!(provider.cache.get_actions_path("AAPL").exists());
+        assert_eq!(provider.get_actions("AAPL").unwrap().len(), 1);
+        assert_eq!(provider.remote.requests.get(), 1);
+        // Tickers without actions are not asked again
+        assert_eq!(provider.get_actions("MSFT").unwrap(), vec![]);
+        assert_eq!(provider.get_actions("MSFT").unwrap(), vec![]);
+        assert_eq!(provider.remote.requests.get(), 2);
+        // The actions directory is not a ticker
+        assert_eq!(provider.get_tickers(), Vec::<String>::new());
+
+        // A stale cache beats a failing remote provider
+        provider.remote.online = false;
+        provider.max_age = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(provider.get_actions("AAPL").unwrap().len(), 1);
+        provider.offline = true;
+        assert_eq!(provider.get_actions("IBM").unwrap(), vec![]);
+        assert_eq!(provider.remote.requests.get(), 3);
+        std::fs::remove_dir_all(provider.cache.dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_get_exchange() {
+        let mut provider = provider("finfo-test-cache-exchange", true);
+        assert_eq!(provider.get_exchange("AAPL"), Ok(Some(String::from("NMS"))));
+        // Exchange codes never go stale
+        provider.max_age = Duration::ZERO;
+        assert_eq!(provider.get_exchange("AAPL"), Ok(Some(String::from("NMS"))));
+        assert_eq!(provider.remote.requests.get(), 1);
+        provider.offline = true;
+        assert_eq!(provider.get_exchange("IBM"), Ok(None));
+        assert_eq!(provider.remote.requests.get(), 1);
+        std::fs::remove_dir_all(provider.cache.dir.parent().unwrap()).unwrap();
+    }
+}
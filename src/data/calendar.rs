@@ -0,0 +1,586 @@
+//! Trading calendars of exchanges
+//!
+//! A calendar knows the days an exchange trades, its holidays
+//! and early closes, and the hours of its pre-market, regular
+//! and post-market sessions in its time zone. Calendars of
+//! NYSE and Nasdaq, the LSE and crypto exchanges trading
+//! around the clock are bundled from `calendars/*.txt`. Files
+//! of the same format in `$FINFO_CALENDARS` or
+//! `~/.finfo/calendars` add calendars or replace bundled ones
+//! of the same name. A ticker trades on the calendar of the
+//! exchange code of its quotes, otherwise the first calendar
+//! with a matching ticker pattern, otherwise on none:
+//!
+//! ```text
+//! name = NYSE
+//! exchanges = NYSE, NMS
+//! timezone = America/New_York
+//! days = mon, tue, wed, thu, fri
+//! regular = 09:30-16:00
+//! holiday = 2024-12-25 Christmas Day
+//! half = 2024-12-24 13:00
+//! ```
+
+use super::route;
+use crate::error::{Error, Result};
+use crate::price::resample::Timeframe;
+use crate::price::validate::Sessions;
+use crate::price::History;
+use crate::utils::stream;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Weekday};
+use chrono_tz::Tz;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Calendars shipped with finfo, checked in order
+pub const BUNDLED: &[&str] = &[
+    include_str!("../../calendars/crypto.txt"),
+    include_str!("../../calendars/lse.txt"),
+    include_str!("../../calendars/nyse.txt"),
+];
+
+/// Days a regular session is looked for ahead of a time
+const MAX_CLOSED_DAYS: usize = 14;
+
+/// Part of a trading day
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Session {
+    Pre,
+    Regular,
+    Post,
+}
+
+impl Session {
+    pub fn to_name(self) -> &'static str {
+        match self {
+            Session::Pre => "pre-market",
+            Session::Regular => "open",
+            Session::Post => "post-market",
+        }
+    }
+}
+
+/// Trading days and hours of an exchange
+#[derive(Clone, PartialEq, Debug)]
+pub struct Calendar {
+    pub name: String,
+    /// Codes of the exchange in quotes such as `NMS`
+    pub exchanges: Vec<String>,
+    /// Patterns of the tickers traded, see `route::is_match`
+    pub tickers: Vec<String>,
+    pub timezone: Tz,
+    pub days: Vec<Weekday>,
+    /// Local minutes after midnight every session starts and
+    /// ends, ordered by time
+    pub sessions: Vec<(Session, u32, u32)>,
+    pub holidays: Vec<NaiveDate>,
+    /// Local minutes after midnight the regular session
+    /// closes early
+    pub half_days: Vec<(NaiveDate, u32)>,
+}
+
+impl Sessions for Calendar {
+    /// Returns `true` if the exchange trades on a local date
+    fn is_session(&self, date: NaiveDate) -> bool {
+        self.days.contains(&date.weekday()) && self.holidays.binary_search(&date).is_err()
+    }
+}
+
+impl Calendar {
+    /// Returns `true` if the calendar is named or has the
+    /// exchange code `name` ignoring case
+    pub fn is_named(&self, name: &str) -> bool {
+        std::iter::once(&self.name)
+            .chain(&self.exchanges)
+            .any(|other| other.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns `true` if a ticker trades on the exchange
+    pub fn is_ticker(&self, ticker: &str) -> bool {
+        self.tickers
+            .iter()
+            .any(|pattern| route::is_match(pattern, ticker))
+    }
+
+    /// Returns the local date of a Unix timestamp
+    pub fn to_date(&self, time: i64) -> NaiveDate {
+        DateTime::from_timestamp(time, 0)
+            .unwrap_or_default()
+            .with_timezone(&self.timezone)
+            .date_naive()
+    }
+
+    /// Get the sessions of a local date as Unix timestamps
+    ///
+    /// On a half day the regular session closes early and the
+    /// post-market session starts at the early close
+    pub fn get_sessions(&self, date: NaiveDate) -> Vec<(Session, i64, i64)> {
+        if !self.is_session(date) {
+            return vec![];
+        }
+        let close = self
+            .half_days
+            .iter()
+            .find(|(day, _)| *day == date)
+            .map(|(_, close)| *close);
+        self.sessions
+            .iter()
+            .filter_map(|&(session, start, end)| {
+                let (start, end) = match (session, close) {
+                    (Session::Regular, Some(close)) => (start, end.min(close)),
+                    (Session::Post, Some(close)) if close < start => (close, close + end - start),
+                    _ => (start, end),
+                };
+                Some((
+                    session,
+                    self.to_time(date, start)?,
+                    self.to_time(date, end)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the session at a Unix timestamp with the Unix
+    /// timestamps of its start and end, `None` when the
+    /// exchange is closed
+    pub fn to_current(&self, time: i64) -> Option<(Session, i64, i64)> {
+        self.get_sessions(self.to_date(time))
+            .into_iter()
+            .find(|(_, start, end)| *start <= time && time < *end)
+    }
+
+    /// Returns the session at a Unix timestamp, `None` when
+    /// the exchange is closed
+    pub fn to_session(&self, time: i64) -> Option<Session> {
+        self.to_current(time).map(|(session, _, _)| session)
+    }
+
+    /// Returns `true` if the regular session is open
+    pub fn is_open(&self, time: i64) -> bool {
+        self.to_session(time) == Some(Session::Regular)
+    }
+
+    /// Returns the Unix timestamps of the regular session open
+    /// at or after `time` and of its close
+    pub fn to_next_regular(&self, time: i64) -> Option<(i64, i64)> {
+        self.to_date(time)
+            .iter_days()
+            .take(MAX_CLOSED_DAYS)
+            .flat_map(|date| self.get_sessions(date))
+            .find(|(session, _, end)| *session == Session::Regular && *end > time)
+            .map(|(_, start, end)| (start, end))
+    }
+
+    /// Returns the Unix timestamp of a local date and minutes
+    /// after its midnight
+    fn to_time(&self, date: NaiveDate, minutes: u32) -> Option<i64> {
+        let local = date.and_hms_opt(0, 0, 0)? + chrono::Duration::minutes(minutes as i64);
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|time| time.timestamp())
+    }
+
+    /// Returns a Unix timestamp as local text such as
+    /// `2024-03-14 09:30 EDT`
+    fn to_text(&self, time: i64, format: &str) -> String {
+        DateTime::from_timestamp(time, 0)
+            .unwrap_or_default()
+            .with_timezone(&self.timezone)
+            .format(format)
+            .to_string()
+    }
+}
+
+/// Parse a calendar of `key = value` lines
+///
+/// Blank lines and lines starting with `#` are skipped. Text
+/// after the date of a `holiday` or the time of a `half` day
+/// is a comment
+pub fn to_calendar(text: &str) -> Result<Calendar> {
+    let mut calendar = Calendar {
+        name: String::new(),
+        exchanges: vec![],
+        tickers: vec![],
+        timezone: Tz::UTC,
+        days: vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ],
+        sessions: vec![],
+        holidays: vec![],
+        half_days: vec![],
+    };
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let to_error = |message: String| Error::Parse {
+            path: None,
+            line: i + 1,
+            message,
+        };
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(to_error(String::from("expected 'key = value'")))?;
+        let value = value.trim();
+        let to_list = || -> Vec<String> {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let invalid = |what: &str| to_error(format!("invalid {} '{}'", what, value));
+        let mut words = value.split_whitespace();
+        let to_date = |word: Option<&str>| {
+            word.and_then(|word| NaiveDate::parse_from_str(word, "%Y-%m-%d").ok())
+                .ok_or(invalid("date"))
+        };
+        match key.trim() {
+            "name" => calendar.name = value.to_string(),
+            "exchanges" => calendar.exchanges = to_list(),
+            "tickers" => calendar.tickers = to_list(),
+            "timezone" => calendar.timezone = value.parse().map_err(|_| invalid("timezone"))?,
+            "days" => {
+                calendar.days = to_list()
+                    .iter()
+                    .map(|day| day.parse::<Weekday>().map_err(|_| invalid("days")))
+                    .collect::<Result<Vec<Weekday>>>()?
+            }
+            key @ ("pre" | "regular" | "post") => {
+                let session = match key {
+                    "pre" => Session::Pre,
+                    "post" => Session::Post,
+                    _ => Session::Regular,
+                };
+                let (start, end) = value
+                    .split_once('-')
+                    .and_then(|(start, end)| Some((to_minutes(start)?, to_minutes(end)?)))
+                    .filter(|(start, end)| start < end)
+                    .ok_or(invalid("hours"))?;
+                calendar.sessions.retain(|(other, _, _)| *other != session);
+                calendar.sessions.push((session, start, end));
+            }
+            "holiday" => calendar.holidays.push(to_date(words.next())?),
+            "half" => {
+                let date = to_date(words.next())?;
+                let close = words
+                    .next()
+                    .and_then(to_minutes)
+                    .ok_or(invalid("early close"))?;
+                calendar.half_days.push((date, close));
+            }
+            key => return Err(to_error(format!("unknown key '{}'", key))),
+        }
+    }
+    if calendar.name.is_empty() {
+        return Err(Error::Invalid(String::from("missing name")));
+    }
+    if !calendar
+        .sessions
+        .iter()
+        .any(|(session, _, _)| *session == Session::Regular)
+    {
+        return Err(Error::Invalid(String::from("missing regular hours")));
+    }
+    calendar.sessions.sort_by_key(|(_, start, _)| *start);
+    calendar.holidays.sort();
+    calendar.holidays.dedup();
+    Ok(calendar)
+}
+
+/// Returns the minutes after midnight of `HH:MM`, up to `24:00`
+fn to_minutes(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+    let total = hours * 60 + minutes;
+    (minutes < 60 && total <= 24 * 60).then_some(total)
+}
+
+/// Read the calendar of a file
+pub fn read_calendar(path: &Path) -> Result<Calendar> {
+    let text = std::fs::read_to_string(path).map_err(|err| Error::Io {
+        path: path.to_path_buf(),
+        message: err.to_string(),
+    })?;
+    to_calendar(&text).map_err(|err| err.in_file(path))
+}
+
+/// Get the directory of calendars of the user
+///
+/// Set by the `FINFO_CALENDARS` environment variable
+/// otherwise `~/.finfo/calendars`
+pub fn get_calendars_dir() -> PathBuf {
+    if let Some(path) = std::env::var_os("FINFO_CALENDARS") {
+        return PathBuf::from(path);
+    }
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".finfo").join("calendars")
+}
+
+/// Get every calendar in the order tickers are matched
+///
+/// Calendars of the user in `dir` come first sorted by file
+/// name and replace bundled calendars of the same name. Files
+/// failing to parse are skipped with a warning
+pub fn get_calendars(dir: &Path) -> Vec<Calendar> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect();
+    paths.sort();
+    let mut calendars: Vec<Calendar> = paths
+        .iter()
+        .filter_map(|path| match read_calendar(path) {
+            Ok(calendar) => Some(calendar),
+            Err(err) => {
+                stream::warn(&err.to_string());
+                None
+            }
+        })
+        .collect();
+    for text in BUNDLED {
+        let Ok(calendar) = to_calendar(text) else {
+            continue;
+        };
+        if !calendars.iter().any(|other| other.is_named(&calendar.name)) {
+            calendars.push(calendar);
+        }
+    }
+    calendars
+}
+
+/// Returns the calendar of a ticker
+///
+/// The `exchange` code of its quotes is looked up first, then
+/// the ticker patterns. Tickers of unknown exchanges have none
+pub fn find_calendar<'a>(
+    calendars: &'a [Calendar],
+    ticker: &str,
+    exchange: Option<&str>,
+) -> Option<&'a Calendar> {
+    match exchange {
+        Some(exchange) => calendars
+            .iter()
+            .find(|calendar| calendar.is_named(exchange)),
+        None => calendars.iter().find(|calendar| calendar.is_ticker(ticker)),
+    }
+}
+
+impl History {
+    /// Returns the timeframes whose latest bar is still
+    /// trading at `time`
+    pub fn to_live(&self, calendar: &Calendar, time: i64) -> Vec<Timeframe> {
+        if !calendar.is_open(time) {
+            return vec![];
+        }
+        Timeframe::ALL
+            .into_iter()
+            .filter(|timeframe| {
+                self.get_timeframe(*timeframe)
+                    .last()
+                    .is_some_and(|bar| timeframe.to_start(bar.time) == timeframe.to_start(time))
+            })
+            .collect()
+    }
+
+    /// Print whether the market is open at `time` with the
+    /// timeframes whose latest bar is still trading
+    pub fn show_market<T: Write>(&self, mut output: T, calendar: &Calendar, time: i64) {
+        let next_open = calendar.to_next_regular(time).map(|(open, _)| open);
+        let status = match (calendar.to_current(time), next_open) {
+            (Some((Session::Pre, _, _)), Some(open)) => format!(
+                "{}, opens {}",
+                Session::Pre.to_name(),
+                calendar.to_text(open, "%H:%M %Z")
+            ),
+            (Some((session, _, end)), _) => format!(
+                "{} until {}",
+                session.to_name(),
+                calendar.to_text(end, "%H:%M %Z")
+            ),
+            (None, Some(open)) => format!(
+                "closed, opens {}",
+                calendar.to_text(open, "%Y-%m-%d %H:%M %Z")
+            ),
+            (None, None) => String::from("closed"),
+        };
+        let _ = writeln!(output, "Market ({}): {}", calendar.name, status);
+        let live: Vec<&str> = self
+            .to_live(calendar, time)
+            .iter()
+            .map(Timeframe::to_name)
+            .collect();
+        if !live.is_empty() {
+            let _ = writeln!(output, "Trading bars: {}", live.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::OHLC;
+
+    fn calendar(name: &str) -> Calendar {
+        BUNDLED
+            .iter()
+            .map(|text| to_calendar(text).unwrap())
+            .find(|calendar| calendar.is_named(name))
+            .unwrap()
+    }
+
+    /// Returns the Unix timestamp of a UTC date and time
+    fn at(text: &str) -> i64 {
+        crate::data::csv::to_time(text).unwrap()
+    }
+
+    #[test]
+    fn test_to_calendar() {
+        let calendars: Vec<Calendar> = BUNDLED
+            .iter()
+            .map(|text| to_calendar(text).unwrap())
+            .collect();
+        assert_eq!(calendars.len(), 3);
+        let nyse = calendar("nms");
+        assert_eq!(nyse.name, "NYSE");
+        assert_eq!(nyse.timezone, Tz::America__New_York);
+        assert_eq!(nyse.sessions[1], (Session::Regular, 570, 960));
+        assert!(calendar("LSE").is_ticker("vod.l"));
+        assert!(calendar("crypto").is_ticker("BTC-USD"));
+        assert!(!calendar("crypto").is_ticker("AAPL"));
+
+        assert_eq!(
+            to_calendar("name = X\nregular = 09:30-25:00\n").map_err(String::from),
+            Err(String::from("line 2: invalid hours '09:30-25:00'"))
+        );
+        assert_eq!(
+            to_calendar("name = X\nopen = 09:30\n").map_err(String::from),
+            Err(String::from("line 2: unknown key 'open'"))
+        );
+        assert!(to_calendar("name = X\ntimezone = Mars/Olympus\n").is_err());
+        assert!(to_calendar("name = X\n").is_err());
+        assert!(to_calendar("regular = 09:30-16:00\n").is_err());
+    }
+
+    #[test]
+    fn test_to_session() {
+        let nyse = calendar("NYSE");
+        // 2024-03-14 is a Thursday in daylight saving time
+        assert_eq!(
+            nyse.to_session(at("2024-03-14 12:00:00")),
+            Some(Session::Pre)
+        );
+        assert_eq!(
+            nyse.to_session(at("2024-03-14 13:30:00")),
+            Some(Session::Regular)
+        );
+        assert_eq!(
+            nyse.to_session(at("2024-03-14 21:00:00")),
+            Some(Session::Post)
+        );
+        assert_eq!(nyse.to_session(at("2024-03-15 00:00:00")), None);
+        // Standard time opens an hour later in UTC
+        assert!(!nyse.is_open(at("2024-01-10 14:00:00")));
+        assert!(nyse.is_open(at("2024-01-10 14:30:00")));
+        // Holidays, weekends and half days
+        assert_eq!(nyse.to_session(at("2024-12-25 15:00:00")), None);
+        assert_eq!(nyse.to_session(at("2024-03-16 15:00:00")), None);
+        assert!(nyse.is_open(at("2024-11-29 17:59:00")));
+        assert_eq!(
+            nyse.to_session(at("2024-11-29 18:00:00")),
+            Some(Session::Post)
+        );
+        assert_eq!(nyse.to_session(at("2024-11-29 22:00:00")), None);
+
+        let lse = calendar("LSE");
+        assert!(lse.is_open(at("2024-07-01 07:00:00")));
+        assert!(!lse.is_open(at("2024-12-31 13:00:00")));
+        assert!(calendar("crypto").is_open(at("2024-12-25 03:00:00")));
+
+        // Good Friday and the weekend are skipped
+        let (open, close) = nyse.to_next_regular(at("2024-03-28 21:00:00")).unwrap();
+        assert_eq!(open, at("2024-04-01 13:30:00"));
+        assert_eq!(close, at("2024-04-01 20:00:00"));
+    }
+
+    #[test]
+    fn test_show_market() {
+        let nyse = calendar("NYSE");
+        let history = History::from_bars(vec![
+            OHLC::new(1.0, 2.0, 0.5, 1.5).at(at("2024-03-13")),
+            OHLC::new(1.0, 2.0, 0.5, 1.5).at(at("2024-03-14")),
+        ]);
+        let show = |time: &str| {
+            let mut output: Vec<u8> = vec![];
+            history.show_market(&mut output, &nyse, at(time));
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(
+            show("2024-03-14 15:00:00"),
+            "Market (NYSE): open until 16:00 EDT\nTrading bars: daily, weekly, monthly, yearly\n"
+        );
+        assert_eq!(
+            show("2024-03-14 12:00:00"),
+            "Market (NYSE): pre-market, opens 09:30 EDT\n"
+        );
+        assert_eq!(
+            show("2024-03-14 21:00:00"),
+            "Market (NYSE): post-market until 20:00 EDT\n"
+        );
+        assert_eq!(
+            show("2024-03-16 12:00:00"),
+            "Market (NYSE): closed, opens 2024-03-18 09:30 EDT\n"
+        );
+        // The weekly bar still trades the next day
+        assert_eq!(
+            history.to_live(&nyse, at("2024-03-15 15:00:00")),
+            vec![Timeframe::Weekly, Timeframe::Monthly, Timeframe::Yearly]
+        );
+    }
+
+    #[test]
+    fn test_get_calendars() {
+        let dir = std::env::temp_dir().join("finfo-test-calendars");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("nyse.txt"),
+            "name = NYSE\nexchanges = NMS\nregular = 10:00-11:00\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.txt"), "name = broken\n").unwrap();
+        let calendars = get_calendars(&dir);
+
+        let names: Vec<&str> = calendars
+            .iter()
+            .map(|calendar| calendar.name.as_str())
+            .collect();
+        assert_eq!(names, ["NYSE", "Crypto", "LSE"]);
+        assert_eq!(calendars[0].sessions, vec![(Session::Regular, 600, 660)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_calendar() {
+        let calendars = get_calendars(Path::new("missing"));
+        let name = |ticker: &str, exchange: Option<&str>| {
+            find_calendar(&calendars, ticker, exchange).map(|calendar| calendar.name.as_str())
+        };
+        assert_eq!(name("AAPL", Some("NMS")), Some("NYSE"));
+        assert_eq!(name("BTCUSDT", Some("BINANCE")), Some("Crypto"));
+        assert_eq!(name("BTC-USD", None), Some("Crypto"));
+        // Unknown exchanges have no calendar rather than NYSE's
+        assert_eq!(name("7203.T", Some("JPX")), None);
+        assert_eq!(name("AAPL", None), None);
+    }
+}
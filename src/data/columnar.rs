@@ -0,0 +1,357 @@
+//! Bars stored as columns in Parquet and Arrow IPC files
+//!
+//! Needs the `arrow` feature. A file holds every timeframe of
+//! a `History` with the columns:
+//!
+//! - `timeframe`: `utf8` name of the timeframe such as `daily`,
+//!   `timeframe` for the specified bars
+//! - `time`: `timestamp[s, UTC]` of when the bar opened
+//! - `open`, `high`, `low`, `close`, `volume`: `decimal128`
+//!   with `SCALE` digits after decimal
+//!
+//! Files written elsewhere such as by pandas or polars may
+//! leave out `timeframe` and `volume`, use any timestamp unit
+//! and store numbers as floats.
+
+use crate::error::{Error, Result};
+use crate::price::resample::Timeframe;
+use crate::price::{History, OHLC};
+use arrow::array::{
+    Array, ArrayRef, Decimal128Array, Float64Array, RecordBatch, StringArray, TimestampSecondArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Total digits of decimal columns
+pub const PRECISION: u8 = 18;
+/// Digits after decimal of decimal columns
+pub const SCALE: i8 = 6;
+
+/// Name of the specified bars in the `timeframe` column
+const SPECIFIED: &str = "timeframe";
+
+/// Kinds of columnar files
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Parquet,
+    /// Arrow IPC, also known as Feather
+    Ipc,
+}
+
+impl Format {
+    /// File extensions of the formats
+    pub const EXTENSIONS: [&'static str; 5] = ["parquet", "pq", "arrow", "feather", "ipc"];
+
+    /// Returns the format of a file from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "parquet" | "pq" => Some(Format::Parquet),
+            "arrow" | "feather" | "ipc" => Some(Format::Ipc),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the schema of files written by finfo
+pub fn to_schema() -> Schema {
+    let decimal = DataType::Decimal128(PRECISION, SCALE);
+    Schema::new(vec![
+        Field::new("timeframe", DataType::Utf8, false),
+        Field::new(
+            "time",
+            DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            false,
+        ),
+        Field::new("open", decimal.clone(), false),
+        Field::new("high", decimal.clone(), false),
+        Field::new("low", decimal.clone(), false),
+        Field::new("close", decimal.clone(), false),
+        Field::new("volume", decimal, false),
+    ])
+}
+
+/// Returns every timeframe of a history as one batch of rows
+pub fn to_batch(history: &History) -> Result<RecordBatch> {
+    let mut rows: Vec<(&str, &OHLC)> = history
+        .timeframe
+        .iter()
+        .map(|bar| (SPECIFIED, bar))
+        .collect();
+    for timeframe in Timeframe::ALL {
+        let name = timeframe.to_name();
+        rows.extend(
+            history
+                .get_timeframe(timeframe)
+                .iter()
+                .map(|bar| (name, bar)),
+        );
+    }
+
+    let to_decimal = |price: fn(&OHLC) -> f32| -> Result<ArrayRef> {
+        let factor = 10f64.powi(SCALE as i32);
+        let array = rows
+            .iter()
+            .map(|(_, bar)| Some((price(bar) as f64 * factor).round() as i128))
+            .collect::<Decimal128Array>()
+            .with_precision_and_scale(PRECISION, SCALE)
+            .map_err(|err| Error::Invalid(err.to_string()))?;
+        Ok(Arc::new(array))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|(name, _)| name),
+        )),
+        Arc::new(
+            TimestampSecondArray::from_iter_values(rows.iter().map(|(_, bar)| bar.time))
+                .with_timezone("UTC"),
+        ),
+        to_decimal(|bar| bar.open)?,
+        to_decimal(|bar| bar.high)?,
+        to_decimal(|bar| bar.low)?,
+        to_decimal(|bar| bar.close)?,
+        to_decimal(|bar| bar.volume)?,
+    ];
+    RecordBatch::try_new(Arc::new(to_schema()), columns)
+        .map_err(|err| Error::Invalid(err.to_string()))
+}
+
+/// Build a history from batches of rows
+///
+/// Rows without a `timeframe` column are the specified
+/// bars and get resampled into every timeframe
+pub fn from_batches(batches: &[RecordBatch]) -> Result<History> {
+    let mut history = History::default();
+    let mut has_timeframes = false;
+    for batch in batches {
+        let Some(column) = batch.column_by_name("timeframe") else {
+            history.timeframe.extend(to_bars(batch)?);
+            continue;
+        };
+        has_timeframes = true;
+        let names = cast(column, &DataType::Utf8).map_err(|err| Error::Invalid(err.to_string()))?;
+        let names = names
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or(Error::Invalid(String::from("invalid column 'timeframe'")))?;
+        for (i, bar) in to_bars(batch)?.into_iter().enumerate() {
+            let bars = match names.value(i) {
+                "hourly" => &mut history.hourly,
+                "daily" => &mut history.daily,
+                "weekly" => &mut history.weekly,
+                "monthly" => &mut history.monthly,
+                "yearly" => &mut history.yearly,
+                SPECIFIED => &mut history.timeframe,
+                name => return Err(Error::Invalid(format!("invalid timeframe '{}'", name))),
+            };
+            bars.push(bar);
+        }
+    }
+
+    if !has_timeframes {
+        return Ok(History::from_bars(history.timeframe));
+    }
+    // Only resampled bars were kept, the shortest stand in
+    if history.timeframe.is_empty() {
+        if let Some(timeframe) = Timeframe::ALL
+            .into_iter()
+            .find(|timeframe| !history.get_timeframe(*timeframe).is_empty())
+        {
+            history.timeframe = history.get_timeframe(timeframe).clone();
+        }
+    }
+    Ok(history)
+}
+
+/// Returns the bars in the rows of a batch
+fn to_bars(batch: &RecordBatch) -> Result<Vec<OHLC>> {
+    let to_column = |name: &str, data_type: &DataType| -> Result<Option<ArrayRef>> {
+        match batch.column_by_name(name) {
+            Some(column) => cast(column, data_type)
+                .map(Some)
+                .map_err(|err| Error::Invalid(format!("invalid column '{}': {}", name, err))),
+            None => Ok(None),
+        }
+    };
+    let to_floats = |name: &str| -> Result<Vec<f32>> {
+        let Some(column) = to_column(name, &DataType::Float64)? else {
+            return match name {
+                "volume" => Ok(vec![0.0; batch.num_rows()]),
+                _ => Err(Error::Invalid(format!("missing column '{}'", name))),
+            };
+        };
+        let column = column.as_any().downcast_ref::<Float64Array>().unwrap();
+        Ok((0..column.len())
+            .map(|i| match column.is_null(i) {
+                true => 0.0,
+                false => column.value(i) as f32,
+            })
+            .collect())
+    };
+
+    let times = match to_column("time", &DataType::Timestamp(TimeUnit::Second, None))? {
+        Some(column) => {
+            let column = column
+                .as_any()
+                .downcast_ref::<TimestampSecondArray>()
+                .unwrap();
+            (0..column.len())
+                .map(|i| match column.is_null(i) {
+                    true => 0,
+                    false => column.value(i),
+                })
+                .collect()
+        }
+        None => vec![0; batch.num_rows()],
+    };
+    let (open, high, low, close, volume) = (
+        to_floats("open")?,
+        to_floats("high")?,
+        to_floats("low")?,
+        to_floats("close")?,
+        to_floats("volume")?,
+    );
+    Ok((0..batch.num_rows())
+        .map(|i| {
+            OHLC::new(open[i], high[i], low[i], close[i])
+                .at(times[i])
+                .with_volume(volume[i])
+        })
+        .collect())
+}
+
+/// Write every timeframe of a history to a Parquet or
+/// Arrow IPC file picked by its extension
+pub fn write_history(path: &Path, history: &History) -> Result<()> {
+    let to_error = |message: String| Error::Io {
+        path: path.to_path_buf(),
+        message,
+    };
+    let format = Format::from_path(path).ok_or(to_error(String::from("unknown format")))?;
+    let batch = to_batch(history).map_err(|err| to_error(err.to_string()))?;
+    let file = File::create(path).map_err(|err| to_error(err.to_string()))?;
+    match format {
+        Format::Parquet => {
+            let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)
+                .map_err(|err| to_error(err.to_string()))?;
+            writer
+                .write(&batch)
+                .map_err(|err| to_error(err.to_string()))?;
+            writer.close().map_err(|err| to_error(err.to_string()))?;
+        }
+        Format::Ipc => {
+            let mut writer = arrow::ipc::writer::FileWriter::try_new(file, &batch.schema())
+                .map_err(|err| to_error(err.to_string()))?;
+            writer
+                .write(&batch)
+                .map_err(|err| to_error(err.to_string()))?;
+            writer.finish().map_err(|err| to_error(err.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a history from a Parquet or Arrow IPC file picked by
+/// its extension
+pub fn read_history(path: &Path) -> Result<History> {
+    let to_error = |message: String| Error::Io {
+        path: path.to_path_buf(),
+        message,
+    };
+    let format = Format::from_path(path).ok_or(to_error(String::from("unknown format")))?;
+    let file = File::open(path).map_err(|err| to_error(err.to_string()))?;
+    let batches: Vec<RecordBatch> = match format {
+        Format::Parquet => {
+            parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+                .and_then(|builder| builder.build())
+                .map_err(|err| to_error(err.to_string()))?
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|err| to_error(err.to_string()))?
+        }
+        Format::Ipc => arrow::ipc::reader::FileReader::try_new(file, None)
+            .map_err(|err| to_error(err.to_string()))?
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|err| to_error(err.to_string()))?,
+    };
+    from_batches(&batches).map_err(|err| to_error(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> History {
+        History::from_bars(vec![
+            OHLC::new(1.25, 2.0, 0.5, 1.5)
+                .at(1_710_374_400)
+                .with_volume(1_200.0),
+            OHLC::new(1.5, 3.0, 1.0, 2.5).at(1_710_460_800),
+        ])
+    }
+
+    #[test]
+    fn test_to_batch() {
+        let batch = to_batch(&history()).unwrap();
+        // Two specified, two daily, one weekly, monthly and yearly
+        assert_eq!(batch.num_rows(), 7);
+        assert_eq!(batch.schema().as_ref(), &to_schema());
+        assert_eq!(from_batches(&[batch]).unwrap(), history());
+    }
+
+    #[test]
+    fn test_from_batches() {
+        // Floats in milliseconds as written by pandas
+        let schema = Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new("open", DataType::Float64, true),
+            Field::new("high", DataType::Float64, true),
+            Field::new("low", DataType::Float64, true),
+            Field::new("close", DataType::Float32, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(arrow::array::TimestampMillisecondArray::from(vec![
+                    1_710_374_400_000,
+                ])),
+                Arc::new(Float64Array::from(vec![1.0])),
+                Arc::new(Float64Array::from(vec![2.0])),
+                Arc::new(Float64Array::from(vec![0.5])),
+                Arc::new(arrow::array::Float32Array::from(vec![1.5])),
+            ],
+        )
+        .unwrap();
+        let history = from_batches(std::slice::from_ref(&batch)).unwrap();
+        assert_eq!(
+            history.timeframe,
+            vec![OHLC::new(1.0, 2.0, 0.5, 1.5).at(1_710_374_400)]
+        );
+        assert_eq!(history.daily.len(), 1);
+
+        let batch = batch.project(&[0, 1, 2, 3]).unwrap();
+        assert_eq!(
+            from_batches(&[batch]),
+            Err(Error::Invalid(String::from("missing column 'close'")))
+        );
+    }
+
+    #[test]
+    fn test_write_history() {
+        let dir = std::env::temp_dir().join("finfo-test-columnar");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["AAPL.parquet", "AAPL.arrow"] {
+            let path = dir.join(name);
+            write_history(&path, &history()).unwrap();
+            assert_eq!(read_history(&path).unwrap(), history());
+        }
+        assert!(write_history(&dir.join("AAPL.xlsx"), &history()).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
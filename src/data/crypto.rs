@@ -0,0 +1,446 @@
+//! Bars fetched from crypto exchanges
+//!
+//! Needs the `http` feature. Binance answers klines as arrays
+//! of `[open time, open, high, low, close, volume, ...]` and
+//! Coinbase answers candles as objects of the same values.
+//! Long ranges are fetched one page of `limit` bars at a time
+//! and rate limited requests are retried after waiting.
+//!
+//! Tickers are pairs such as `BTC-USD`, `BTC/USD` or `BTCUSDT`.
+
+use super::http;
+use super::Provider;
+use crate::error::{Error, Result};
+use crate::price::resample::Timeframe;
+use crate::price::OHLC;
+use crate::utils::stream;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Quote currencies recognized at the end of a pair
+pub const QUOTES: [&str; 9] = [
+    "USDT", "USDC", "BUSD", "FDUSD", "USD", "EUR", "GBP", "BTC", "ETH",
+];
+
+/// Seconds to wait after a rate limit without `Retry-After`
+pub const DEFAULT_BACKOFF: u64 = 1;
+
+/// Exchanges with a klines API
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Exchange {
+    Binance,
+    Coinbase,
+}
+
+impl Exchange {
+    /// Returns the readable name of an exchange
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Exchange::Binance => "binance",
+            Exchange::Coinbase => "coinbase",
+        }
+    }
+
+    /// Returns the base URL of the public API of an exchange
+    pub fn to_url(&self) -> &'static str {
+        match self {
+            Exchange::Binance => "https://api.binance.com",
+            Exchange::Coinbase => "https://api.coinbase.com",
+        }
+    }
+
+    /// Get the base URL of the API of an exchange
+    ///
+    /// Set by the `FINFO_BINANCE_URL` or `FINFO_COINBASE_URL`
+    /// environment variable otherwise `to_url`
+    pub fn get_base_url(&self) -> String {
+        let name = format!("FINFO_{}_URL", self.to_name().to_uppercase());
+        std::env::var(name)
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or(self.to_url().to_string())
+    }
+
+    /// Returns the most bars an exchange sends at once
+    pub fn to_limit(&self) -> usize {
+        match self {
+            Exchange::Binance => 1000,
+            Exchange::Coinbase => 350,
+        }
+    }
+
+    /// Returns the symbol of a pair on an exchange
+    ///
+    /// Binance has no dollar pairs so `USD` is `USDT`
+    pub fn to_symbol(&self, base: &str, quote: &str) -> String {
+        match (self, quote) {
+            (Exchange::Binance, "USD") => format!("{}USDT", base),
+            (Exchange::Binance, _) => format!("{}{}", base, quote),
+            (Exchange::Coinbase, _) => format!("{}-{}", base, quote),
+        }
+    }
+
+    /// Returns the name of an interval on an exchange
+    fn to_interval(self, interval: Timeframe) -> Result<&'static str> {
+        match (self, interval) {
+            (Exchange::Binance, Timeframe::Hourly) => Ok("1h"),
+            (Exchange::Binance, Timeframe::Daily) => Ok("1d"),
+            (Exchange::Binance, Timeframe::Weekly) => Ok("1w"),
+            (Exchange::Coinbase, Timeframe::Hourly) => Ok("ONE_HOUR"),
+            (Exchange::Coinbase, Timeframe::Daily) => Ok("ONE_DAY"),
+            _ => Err(Error::Invalid(format!(
+                "{} has no {} bars",
+                self.to_name(),
+                interval.to_name()
+            ))),
+        }
+    }
+}
+
+/// Returns the base and quote currency of a pair such as
+/// `BTC-USD`, `btc/usd` or `BTCUSDT`
+pub fn to_pair(ticker: &str) -> Option<(String, String)> {
+    let ticker = ticker.trim().to_uppercase();
+    if let Some((base, quote)) = ticker.split_once(['-', '/']) {
+        return match base.is_empty() || quote.is_empty() {
+            true => None,
+            false => Some((base.to_string(), quote.to_string())),
+        };
+    }
+    QUOTES.iter().find_map(|quote| {
+        let base = ticker.strip_suffix(quote)?;
+        match base.is_empty() {
+            true => None,
+            false => Some((base.to_string(), quote.to_string())),
+        }
+    })
+}
+
+/// Provider of bars from a crypto exchange
+pub struct CryptoProvider {
+    pub exchange: Exchange,
+    pub base_url: String,
+    /// Length of a bar
+    pub interval: Timeframe,
+    /// Number of days of bars
+    pub days: i64,
+    /// Unix timestamp (seconds) bars end at, `None` is now
+    pub end: Option<i64>,
+    /// Most bars asked for in one request
+    pub limit: usize,
+    /// Attempts after a rate limit before giving up
+    pub max_retries: usize,
+    pub timeout: Duration,
+}
+
+impl CryptoProvider {
+    /// Create a provider of a year of daily bars
+    pub fn new(exchange: Exchange, base_url: &str) -> Self {
+        Self {
+            exchange,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            interval: Timeframe::Daily,
+            days: 365,
+            end: None,
+            limit: exchange.to_limit(),
+            max_retries: 3,
+            timeout: Duration::from_secs(http::DEFAULT_TIMEOUT),
+        }
+    }
+
+    /// Get the URL of a page of bars of a symbol opening from
+    /// `start` until before `end`
+    pub fn get_url(&self, symbol: &str, start: i64, end: i64) -> Result<String> {
+        let interval = self.exchange.to_interval(self.interval)?;
+        Ok(match self.exchange {
+            Exchange::Binance => format!(
+                "{}/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit={}",
+                self.base_url,
+                symbol,
+                interval,
+                start * 1000,
+                end * 1000 - 1,
+                self.limit
+            ),
+            Exchange::Coinbase => format!(
+                "{}/api/v3/brokerage/market/products/{}/candles?start={}&end={}&granularity={}&limit={}",
+                self.base_url,
+                symbol,
+                start,
+                end - 1,
+                interval,
+                self.limit
+            ),
+        })
+    }
+
+    /// Get the body of a request, waiting out rate limits
+    fn get_body(&self, url: &str, ticker: &str) -> Result<String> {
+        for attempt in 0..=self.max_retries {
+            let response = http::get(url, self.timeout)?;
+            match response.status {
+                200 => return Ok(response.body),
+                // Binance bans with `418` after ignored limits
+                429 | 418 if attempt < self.max_retries => {
+                    let wait = response
+                        .retry_after
+                        .unwrap_or(Duration::from_secs(DEFAULT_BACKOFF << attempt));
+                    stream::warn(&format!(
+                        "{}: rate limited, retrying in {}s",
+                        self.exchange.to_name(),
+                        wait.as_secs_f32()
+                    ));
+                    std::thread::sleep(wait);
+                }
+                429 | 418 => break,
+                // Binance answers unknown symbols with `400`
+                400 | 404 => return Err(Error::NotFound(ticker.to_uppercase())),
+                status => {
+                    return Err(Error::Network {
+                        url: url.to_string(),
+                        message: format!("unexpected status {}", status),
+                    })
+                }
+            }
+        }
+        Err(Error::Network {
+            url: url.to_string(),
+            message: format!("rate limited after {} retries", self.max_retries),
+        })
+    }
+}
+
+impl Provider for CryptoProvider {
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        let (base, quote) =
+            to_pair(ticker).ok_or(Error::Invalid(format!("invalid pair '{}'", ticker)))?;
+        let symbol = self.exchange.to_symbol(&base, &quote);
+        let step = self.interval.to_seconds();
+        let end = self
+            .end
+            .unwrap_or(chrono::Utc::now().timestamp())
+            .div_euclid(step)
+            * step
+            + step;
+        let start = end - self.days * 86_400;
+
+        let mut bars: Vec<OHLC> = vec![];
+        let mut page_start = start;
+        while page_start < end {
+            let page_end = end.min(page_start + step * self.limit.max(1) as i64);
+            let url = self.get_url(&symbol, page_start, page_end)?;
+            let body = self.get_body(&url, ticker)?;
+            let page = match self.exchange {
+                Exchange::Binance => from_klines(&body),
+                Exchange::Coinbase => from_candles(&body),
+            }
+            .map_err(|err| Error::Network {
+                url,
+                message: err.to_string(),
+            })?;
+            stream::trace(&format!(
+                "{} bars of {} from {}",
+                page.len(),
+                symbol,
+                page_start
+            ));
+            bars.extend(page);
+            page_start = page_end;
+        }
+        bars.sort_by_key(|bar| bar.time);
+        bars.dedup_by_key(|bar| bar.time);
+        Ok(bars)
+    }
+
+    /// Get the name of the exchange as its code such as `BINANCE`
+    fn get_exchange(&self, _ticker: &str) -> Result<Option<String>> {
+        Ok(Some(self.exchange.to_name().to_uppercase()))
+    }
+}
+
+/// Returns the number in a value sent as a number or a string
+fn to_number(value: &Value) -> Option<f32> {
+    match value {
+        Value::String(text) => text.parse().ok(),
+        _ => value.as_f64().map(|number| number as f32),
+    }
+}
+
+/// Returns the error of JSON that could not be parsed
+fn to_parse_error(err: serde_json::Error) -> Error {
+    Error::Parse {
+        path: None,
+        line: err.line(),
+        message: err.to_string(),
+    }
+}
+
+/// Parse Binance klines of JSON ordered from oldest to newest
+pub fn from_klines(text: &str) -> Result<Vec<OHLC>> {
+    let value: Value = serde_json::from_str(text).map_err(to_parse_error)?;
+    let klines = value
+        .as_array()
+        .ok_or(Error::Invalid(String::from("klines are not an array")))?;
+    klines
+        .iter()
+        .enumerate()
+        .map(|(i, kline)| {
+            let field = |index: usize| kline.get(index).and_then(to_number);
+            match (
+                kline.get(0).and_then(Value::as_i64),
+                field(1),
+                field(2),
+                field(3),
+                field(4),
+            ) {
+                (Some(time), Some(open), Some(high), Some(low), Some(close)) => {
+                    Ok(OHLC::new(open, high, low, close)
+                        .at(time / 1000)
+                        .with_volume(field(5).unwrap_or_default()))
+                }
+                _ => Err(Error::Invalid(format!("invalid kline {}", i))),
+            }
+        })
+        .collect()
+}
+
+/// Parse Coinbase candles of JSON
+///
+/// Candles are sent from newest to oldest and returned from
+/// oldest to newest
+pub fn from_candles(text: &str) -> Result<Vec<OHLC>> {
+    let value: Value = serde_json::from_str(text).map_err(to_parse_error)?;
+    let candles = value["candles"]
+        .as_array()
+        .ok_or(Error::Invalid(String::from("missing candles")))?;
+    let mut bars = candles
+        .iter()
+        .enumerate()
+        .map(|(i, candle)| {
+            let field = |name: &str| to_number(&candle[name]);
+            let time = match &candle["start"] {
+                Value::String(text) => text.parse().ok(),
+                start => start.as_i64(),
+            };
+            match (
+                time,
+                field("open"),
+                field("high"),
+                field("low"),
+                field("close"),
+            ) {
+                (Some(time), Some(open), Some(high), Some(low), Some(close)) => {
+                    Ok(OHLC::new(open, high, low, close)
+                        .at(time)
+                        .with_volume(field("volume").unwrap_or_default()))
+                }
+                _ => Err(Error::Invalid(format!("invalid candle {}", i))),
+            }
+        })
+        .collect::<Result<Vec<OHLC>>>()?;
+    bars.sort_by_key(|bar| bar.time);
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::mock::{self, MockServer, Reply};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Last daily bar of the fixtures
+    const END: i64 = 1_711_324_800;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(mock::get_fixtures_dir().join(name)).unwrap()
+    }
+
+    fn provider(exchange: Exchange, server: &MockServer) -> CryptoProvider {
+        let mut provider = CryptoProvider::new(exchange, &server.url);
+        provider.end = Some(END);
+        provider.days = 25;
+        provider.limit = 10;
+        provider
+    }
+
+    #[test]
+    fn test_to_pair() {
+        let pair = |base: &str, quote: &str| Some((base.to_string(), quote.to_string()));
+        assert_eq!(to_pair("BTC-USD"), pair("BTC", "USD"));
+        assert_eq!(to_pair("eth/btc"), pair("ETH", "BTC"));
+        assert_eq!(to_pair("BTCUSDT"), pair("BTC", "USDT"));
+        assert_eq!(to_pair("SOLUSD"), pair("SOL", "USD"));
+        assert_eq!(to_pair("AAPL"), None);
+        assert_eq!(to_pair("USDT"), None);
+        assert_eq!(Exchange::Binance.to_symbol("BTC", "USD"), "BTCUSDT");
+        assert_eq!(Exchange::Coinbase.to_symbol("BTC", "USDT"), "BTC-USDT");
+    }
+
+    #[test]
+    fn test_from_klines() {
+        let bars = from_klines(&fixture("binance/BTCUSDT.json")).unwrap();
+        assert_eq!(bars.len(), 25);
+        assert_eq!(bars[0].time, 1_709_251_200);
+        assert_eq!(bars[0].high, 61_610.0);
+        assert_eq!(bars[0].volume, 21_000.0);
+        assert_eq!(
+            from_klines("[[1709251200000, \"1\", \"x\"]]"),
+            Err(Error::Invalid(String::from("invalid kline 0")))
+        );
+    }
+
+    #[test]
+    fn test_from_candles() {
+        let bars = from_candles(&fixture("coinbase/BTC-USD.json")).unwrap();
+        assert_eq!(bars, from_klines(&fixture("binance/BTCUSDT.json")).unwrap());
+        assert!(from_candles(&fixture("coinbase/missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_get_bars() {
+        let server = MockServer::start(mock::to_klines_handler(mock::get_fixtures_dir())).unwrap();
+        for exchange in [Exchange::Binance, Exchange::Coinbase] {
+            let provider = provider(exchange, &server);
+            let bars = provider.get_bars("btc-usd").unwrap();
+            assert_eq!(bars.len(), 25);
+            assert_eq!(bars[24].time, END);
+            assert_eq!(
+                provider.get_bars("DOGE-USD"),
+                Err(Error::NotFound(String::from("DOGE-USD")))
+            );
+            assert_eq!(
+                provider.get_exchange("BTC-USD"),
+                Ok(Some(exchange.to_name().to_uppercase()))
+            );
+        }
+        // Pages of 10 bars over 25 days
+        let requests = server.get_requests();
+        assert_eq!(requests.len(), 8);
+        assert!(requests[0].starts_with(
+            "/api/v3/klines?symbol=BTCUSDT&interval=1d&startTime=1709251200000&endTime=1710115199999"
+        ));
+    }
+
+    #[test]
+    fn test_get_bars_rate_limit() {
+        let handler = mock::to_klines_handler(mock::get_fixtures_dir());
+        let count = AtomicUsize::new(0);
+        let server = MockServer::start(move |path| {
+            // Every other request is rate limited
+            match count.fetch_add(1, Ordering::Relaxed) % 2 {
+                0 => Reply::new(429, "{}").with_header("Retry-After", "0"),
+                _ => handler(path),
+            }
+        })
+        .unwrap();
+        let mut provider = provider(Exchange::Binance, &server);
+        assert_eq!(provider.get_bars("BTCUSDT").unwrap().len(), 25);
+        assert_eq!(server.get_requests().len(), 6);
+
+        provider.max_retries = 0;
+        assert!(matches!(
+            provider.get_bars("BTCUSDT"),
+            Err(Error::Network { .. })
+        ));
+    }
+}
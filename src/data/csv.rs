@@ -0,0 +1,304 @@
+//! Bars stored as comma separated values
+//!
+//! Every line is `time,open,high,low,close[,volume]` where
+//! `time` is a Unix timestamp (seconds) or a UTC date written
+//! as `YYYY-MM-DD`, `YYYY-MM-DD HH:MM:SS` or RFC 3339.
+//!
+//! Corporate actions are `time,dividend,amount` or
+//! `time,split,numerator:denominator` lines.
+
+use crate::error::{Error, Result};
+use crate::price::action::Action;
+use crate::price::candle::Tick;
+use crate::price::OHLC;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// Header line written at the top of a file
+pub const HEADER: &str = "time,open,high,low,close,volume";
+/// Header line written at the top of a file of actions
+pub const ACTIONS_HEADER: &str = "time,action,value";
+
+/// Returns the Unix timestamp of a time field
+pub fn to_time(field: &str) -> Result<i64> {
+    let field = field.trim();
+    if let Ok(time) = field.parse::<i64>() {
+        return Ok(time);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(field) {
+        return Ok(date.timestamp());
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(field, "%Y-%m-%d %H:%M:%S") {
+        return Ok(date.and_utc().timestamp());
+    }
+    NaiveDate::parse_from_str(field, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc().timestamp())
+        .ok_or(Error::Invalid(format!("invalid time '{}'", field)))
+}
+
+/// Parse bars from comma separated values
+///
+/// Blank lines and a header line are skipped, a missing
+/// volume is `0`
+pub fn from_csv(text: &str) -> Result<Vec<OHLC>> {
+    let mut bars = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let to_error = |message: String| Error::Parse {
+            path: None,
+            line: number + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.starts_with("time")) {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < 5 {
+            return Err(to_error(String::from("expected 5 fields")));
+        }
+        let mut prices = [0.0f32; 4];
+        for (price, field) in prices.iter_mut().zip(&fields[1..5]) {
+            *price = field
+                .trim()
+                .parse()
+                .map_err(|_| to_error(format!("invalid price '{}'", field.trim())))?;
+        }
+        let volume: f32 = match fields.get(5).map(|field| field.trim()) {
+            Some(field) if !field.is_empty() => field
+                .parse()
+                .map_err(|_| to_error(format!("invalid volume '{}'", field)))?,
+            _ => 0.0,
+        };
+        let time = to_time(fields[0]).map_err(|err| to_error(err.to_string()))?;
+        bars.push(
+            OHLC::new(prices[0], prices[1], prices[2], prices[3])
+                .at(time)
+                .with_volume(volume),
+        );
+    }
+    Ok(bars)
+}
+
+/// Parse trades from comma separated values
+///
+/// Every line is `time,price[,volume]` and trades may be in
+/// any order. Blank lines and a header line are skipped, a
+/// missing volume is `0`
+pub fn from_trades(text: &str) -> Result<Vec<Tick>> {
+    let mut ticks = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let to_error = |message: String| Error::Parse {
+            path: None,
+            line: number + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.starts_with("time")) {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 2 {
+            return Err(to_error(String::from("expected 2 fields")));
+        }
+        let price: f32 = fields[1]
+            .parse()
+            .map_err(|_| to_error(format!("invalid price '{}'", fields[1])))?;
+        let volume: f32 = match fields.get(2) {
+            Some(field) if !field.is_empty() => field
+                .parse()
+                .map_err(|_| to_error(format!("invalid volume '{}'", field)))?,
+            _ => 0.0,
+        };
+        let time = to_time(fields[0]).map_err(|err| to_error(err.to_string()))?;
+        ticks.push(Tick::new(time, price, volume));
+    }
+    Ok(ticks)
+}
+
+/// Parse corporate actions from comma separated values
+///
+/// Blank lines and a header line are skipped and actions
+/// are ordered by time
+pub fn from_actions(text: &str) -> Result<Vec<Action>> {
+    let mut actions = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let to_error = |message: String| Error::Parse {
+            path: None,
+            line: number + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.starts_with("time")) {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 3 {
+            return Err(to_error(String::from("expected 3 fields")));
+        }
+        let time = to_time(fields[0]).map_err(|err| to_error(err.to_string()))?;
+        let to_number = |field: &str| {
+            field
+                .parse::<f32>()
+                .ok()
+                .filter(|number| *number > 0.0)
+                .ok_or(to_error(format!("invalid value '{}'", fields[2])))
+        };
+        let action = match fields[1].to_lowercase().as_str() {
+            "dividend" => Action::Dividend {
+                time,
+                amount: to_number(fields[2])?,
+            },
+            "split" => {
+                let (numerator, denominator) = fields[2]
+                    .split_once(':')
+                    .ok_or(to_error(format!("invalid value '{}'", fields[2])))?;
+                Action::Split {
+                    time,
+                    numerator: to_number(numerator)?,
+                    denominator: to_number(denominator)?,
+                }
+            }
+            action => return Err(to_error(format!("invalid action '{}'", action))),
+        };
+        actions.push(action);
+    }
+    actions.sort_by_key(Action::get_time);
+    Ok(actions)
+}
+
+/// Returns corporate actions as comma separated values with
+/// a header line
+pub fn to_actions(actions: &[Action]) -> String {
+    let mut text = format!("{}\n", ACTIONS_HEADER);
+    for action in actions {
+        text.push_str(&match action {
+            Action::Dividend { time, amount } => format!("{},dividend,{}\n", time, amount),
+            Action::Split {
+                time,
+                numerator,
+                denominator,
+            } => format!("{},split,{}:{}\n", time, numerator, denominator),
+        });
+    }
+    text
+}
+
+/// Returns bars as comma separated values with a header line
+pub fn to_csv(bars: &[OHLC]) -> String {
+    let mut text = format!("{}\n", HEADER);
+    for bar in bars {
+        text.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            bar.time, bar.open, bar.high, bar.low, bar.close, bar.volume
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_time() {
+        assert_eq!(to_time("1710374400"), Ok(1_710_374_400));
+        assert_eq!(to_time("2024-03-14"), Ok(1_710_374_400));
+        assert_eq!(to_time("2024-03-14 15:30:00"), Ok(1_710_430_200));
+        assert_eq!(to_time("2024-03-14T11:30:00-04:00"), Ok(1_710_430_200));
+        assert_eq!(
+            to_time("14/03/2024"),
+            Err(Error::Invalid("invalid time '14/03/2024'".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let bars = from_csv(
+            "time,open,high,low,close\n2024-03-14,1,2,0.5,1.5\n\n1710460800,1.5,3,1,2.5\n",
+        )
+        .unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].time, 1_710_374_400);
+        assert_eq!(bars[1].high, 3.0);
+        assert_eq!(bars[1].volume, 0.0);
+        assert_eq!(
+            from_csv("2024-03-14,1,2,0.5,1.5,1200\n").unwrap()[0].volume,
+            1200.0
+        );
+        assert_eq!(
+            from_csv("2024-03-14,1,2,0.5,1.5,lots\n")
+                .map_err(String::from)
+                .err(),
+            Some("line 1: invalid volume 'lots'".to_string())
+        );
+        assert_eq!(
+            from_csv("2024-03-14,1,2,0.5\n").map_err(String::from).err(),
+            Some("line 1: expected 5 fields".to_string())
+        );
+        assert_eq!(
+            from_csv("2024-03-14,1,x,0.5,1\n").err(),
+            Some(Error::Parse {
+                path: None,
+                line: 1,
+                message: "invalid price 'x'".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let bars = vec![OHLC::new(1.0, 2.0, 0.5, 1.5).at(60).with_volume(10.0)];
+        let text = to_csv(&bars);
+        assert_eq!(text, "time,open,high,low,close,volume\n60,1,2,0.5,1.5,10\n");
+        assert_eq!(from_csv(&text).unwrap()[0].low, 0.5);
+    }
+
+    #[test]
+    fn test_from_trades() {
+        let ticks = from_trades("time,price,volume\n60,10.5,2\n\n2024-03-14,10\n").unwrap();
+        assert_eq!(
+            ticks,
+            vec![
+                Tick::new(60, 10.5, 2.0),
+                Tick::new(1_710_374_400, 10.0, 0.0)
+            ]
+        );
+        assert_eq!(
+            from_trades("60\n").map_err(String::from),
+            Err(String::from("line 1: expected 2 fields"))
+        );
+        assert!(from_trades("60,cheap\n").is_err());
+    }
+
+    #[test]
+    fn test_from_actions() {
+        let actions =
+            from_actions("time,action,value\n2024-03-15,split,4:1\n\n2024-03-14,Dividend,0.24\n")
+                .unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                Action::Dividend {
+                    time: 1_710_374_400,
+                    amount: 0.24
+                },
+                Action::Split {
+                    time: 1_710_460_800,
+                    numerator: 4.0,
+                    denominator: 1.0
+                },
+            ]
+        );
+        assert_eq!(from_actions(&to_actions(&actions)).unwrap(), actions);
+        assert_eq!(
+            from_actions("2024-03-15,split,4\n").map_err(String::from),
+            Err(String::from("line 1: invalid value '4'"))
+        );
+        assert_eq!(
+            from_actions("2024-03-15,merger,1\n").map_err(String::from),
+            Err(String::from("line 1: invalid action 'merger'"))
+        );
+        assert!(from_actions("2024-03-15,dividend\n").is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Bars read from local data files
+//!
+//! Every ticker is a `TICKER.csv` file in a directory,
+//! see `data::csv` for the format. With the `arrow` feature
+//! `TICKER.parquet` and `TICKER.arrow` files are read too,
+//! see `data::columnar`. Splits and dividends of a ticker are
+//! an `actions/TICKER.csv` file.
+
+use super::{csv, Provider};
+use crate::error::{Error, Result};
+use crate::price::action::Action;
+use crate::price::candle::Tick;
+use crate::price::{History, OHLC};
+use crate::utils::stream;
+use std::path::{Path, PathBuf};
+
+/// Extensions of data files in the order they are looked up
+#[cfg(not(feature = "arrow"))]
+pub const EXTENSIONS: &[&str] = &["csv"];
+/// Extensions of data files in the order they are looked up
+#[cfg(feature = "arrow")]
+pub const EXTENSIONS: &[&str] = &["csv", "parquet", "pq", "arrow", "feather", "ipc"];
+
+/// Provider of bars stored in a directory of files
+pub struct FileProvider {
+    pub dir: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Get the path of the data file of a ticker
+    ///
+    /// Default is: `TICKER.csv` when no file exists
+    pub fn get_path(&self, ticker: &str) -> PathBuf {
+        let ticker = ticker.to_uppercase();
+        EXTENSIONS
+            .iter()
+            .map(|extension| self.dir.join(format!("{}.{}", ticker, extension)))
+            .find(|path| path.exists())
+            .unwrap_or(self.dir.join(format!("{}.csv", ticker)))
+    }
+
+    /// Get the path of the file of actions of a ticker
+    pub fn get_actions_path(&self, ticker: &str) -> PathBuf {
+        self.dir
+            .join("actions")
+            .join(format!("{}.csv", ticker.to_uppercase()))
+    }
+}
+
+impl Provider for FileProvider {
+    /// Get every ticker with a data file sorted by name
+    fn get_tickers(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let mut tickers: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let extension = path.extension()?.to_str()?;
+                if !EXTENSIONS.contains(&extension) {
+                    return None;
+                }
+                Some(path.file_stem()?.to_string_lossy().to_uppercase())
+            })
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        let path = self.get_path(ticker);
+        if !path.exists() {
+            return Err(Error::NotFound(ticker.to_uppercase()));
+        }
+        stream::trace(&format!("Reading {}", path.display()));
+        let bars = read_bars(&path)?;
+        stream::debug(&format!(
+            "Read {} bars of {} from {}",
+            bars.len(),
+            ticker,
+            path.display()
+        ));
+        Ok(bars)
+    }
+
+    /// Get the actions of the file of actions of a ticker
+    ///
+    /// A ticker without one has no actions
+    fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+        let path = self.get_actions_path(ticker);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => csv::from_actions(&text).map_err(|err| err.in_file(&path)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(err) => Err(Error::Io {
+                path,
+                message: err.to_string(),
+            }),
+        }
+    }
+}
+
+/// Read the bars of a data file picked by its extension
+pub fn read_bars(path: &Path) -> Result<Vec<OHLC>> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => {
+            let text = std::fs::read_to_string(path).map_err(|err| Error::Io {
+                path: path.to_path_buf(),
+                message: err.to_string(),
+            })?;
+            csv::from_csv(&text).map_err(|err| err.in_file(path))
+        }
+        #[cfg(feature = "arrow")]
+        _ if super::columnar::Format::from_path(path).is_some() => {
+            Ok(super::columnar::read_history(path)?.timeframe)
+        }
+        _ => Err(to_unsupported(path)),
+    }
+}
+
+/// Read the recorded trades of a file picked by its extension
+///
+/// Comma separated values are `time,price[,volume]` lines.
+/// With the `websocket` feature `jsonl` files of messages
+/// recorded from an exchange stream are read too
+pub fn read_trades(path: &Path) -> Result<Vec<Tick>> {
+    let read = || {
+        std::fs::read_to_string(path).map_err(|err| Error::Io {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })
+    };
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => csv::from_trades(&read()?).map_err(|err| err.in_file(path)),
+        #[cfg(feature = "websocket")]
+        Some("jsonl") => Ok(read()?.lines().filter_map(super::live::to_tick).collect()),
+        _ => Err(Error::Invalid(format!(
+            "unsupported trades file '{}'",
+            path.display()
+        ))),
+    }
+}
+
+/// Write the history of a ticker to a data file picked by its
+/// extension
+///
+/// Comma separated values only keep the specified timeframe.
+/// With the `serde` feature `json` files hold the versioned
+/// document of `price::json`
+#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+pub fn write_history(path: &Path, ticker: &str, history: &History) -> Result<()> {
+    let write = |text: String| {
+        std::fs::write(path, text).map_err(|err| Error::Io {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })
+    };
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => write(csv::to_csv(&history.timeframe)),
+        #[cfg(feature = "serde")]
+        Some("json") => write(crate::price::json::to_json(ticker, history)?),
+        #[cfg(feature = "arrow")]
+        _ if super::columnar::Format::from_path(path).is_some() => {
+            super::columnar::write_history(path, history)
+        }
+        _ => Err(to_unsupported(path)),
+    }
+}
+
+/// Returns the error of a file in a format finfo does not know
+fn to_unsupported(path: &Path) -> Error {
+    Error::Invalid(format!(
+        "unsupported file '{}'; possibilities: {}",
+        path.display(),
+        EXTENSIONS
+            .iter()
+            .chain(cfg!(feature = "serde").then_some(&"json"))
+            .map(|extension| format!("'.{}'", extension))
+            .collect::<Vec<String>>()
+            .join(" ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::aggregate::{to_aggregate, Bars};
+
+    #[test]
+    fn test_get_bars() {
+        let dir = std::env::temp_dir().join("finfo-test-file-provider");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("BTC-USD.csv"), "2024-03-14,1,2,0.5,1.5\n").unwrap();
+
+        let provider = FileProvider::new(dir.clone());
+        assert_eq!(provider.get_bars("btc-usd").unwrap().len(), 1);
+        assert_eq!(provider.get_history("BTC-USD").unwrap().daily.len(), 1);
+        assert_eq!(
+            provider.get_bars("eth-usd").err(),
+            Some(Error::NotFound(String::from("ETH-USD")))
+        );
+        std::fs::write(dir.join("DOGE-USD.csv"), "2024-03-14,1,x,0.5,1.5\n").unwrap();
+        let err = provider.get_bars("DOGE-USD").err().unwrap().to_string();
+        assert!(err.starts_with(&dir.join("DOGE-USD.csv").display().to_string()));
+        assert!(err.ends_with("line 1: invalid price 'x'"));
+        std::fs::remove_file(dir.join("DOGE-USD.csv")).unwrap();
+        assert_eq!(provider.get_tickers(), vec!["BTC-USD"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_actions() {
+        let dir = std::env::temp_dir().join("finfo-test-file-actions");
+        std::fs::create_dir_all(dir.join("actions")).unwrap();
+        std::fs::write(dir.join("AAPL.csv"), "2024-03-14,1,2,0.5,1.5\n").unwrap();
+        std::fs::write(dir.join("actions/AAPL.csv"), "2024-03-15,split,4:1\n").unwrap();
+
+        let provider = FileProvider::new(dir.clone());
+        assert_eq!(provider.get_actions("aapl").unwrap().len(), 1);
+        assert_eq!(provider.get_actions("MSFT").unwrap(), vec![]);
+        // The directory of actions is not a ticker
+        assert_eq!(provider.get_tickers(), vec!["AAPL"]);
+        std::fs::write(dir.join("actions/MSFT.csv"), "2024-03-15,split,x\n").unwrap();
+        assert!(provider.get_actions("MSFT").is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_history() {
+        let dir = std::env::temp_dir().join("finfo-test-file-write");
+        std::fs::create_dir_all(&dir).unwrap();
+        let history = History::from_bars(vec![OHLC::new(1.0, 2.0, 0.5, 1.5).at(60)]);
+
+        write_history(&dir.join("AAPL.csv"), "AAPL", &history).unwrap();
+        assert_eq!(read_bars(&dir.join("AAPL.csv")).unwrap(), history.timeframe);
+        #[cfg(feature = "serde")]
+        {
+            write_history(&dir.join("AAPL.json"), "AAPL", &history).unwrap();
+            let text = std::fs::read_to_string(dir.join("AAPL.json")).unwrap();
+            let document = crate::price::json::from_json(&text).unwrap();
+            assert_eq!(document.ticker, "AAPL");
+            assert_eq!(document.history, history);
+        }
+        let err = write_history(&dir.join("AAPL.xlsx"), "AAPL", &history)
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("unsupported file"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_trades() {
+        let dir = super::super::mock::get_fixtures_dir().join("trades");
+        let ticks = read_trades(&dir.join("BTC-USD.csv")).unwrap();
+        assert_eq!(ticks.len(), 8);
+        // Trades recorded out of order are put back within the grace
+        let bars = Bars::Time(crate::price::candle::Period::Seconds(60));
+        let (bars, late) = to_aggregate(&ticks, bars, 15);
+        assert_eq!(late, 0);
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[0].close, 61_010.0);
+        assert!(read_trades(&dir.join("BTC-USD.txt")).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Requests to remote APIs over HTTP
+//!
+//! Needs the `http` feature. Responses of every status are
+//! returned so providers can read the errors remote APIs
+//! send in their bodies.
+
+use crate::error::{Error, Result};
+use crate::utils::stream;
+use std::time::Duration;
+
+/// User agent sent with every request
+pub const USER_AGENT: &str = concat!("finfo/", env!("CARGO_PKG_VERSION"));
+
+/// Seconds to wait for a response when none are given
+pub const DEFAULT_TIMEOUT: u64 = 10;
+
+/// Response of a remote API
+#[derive(Clone, PartialEq, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+    /// Time to wait before the next request when rate limited
+    pub retry_after: Option<Duration>,
+}
+
+/// Get the response of a `GET` request to `url`
+///
+/// Only failing to reach the remote API is an error
+pub fn get(url: &str, timeout: Duration) -> Result<Response> {
+    stream::debug(&format!("Fetching {}", url));
+    let agent = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .user_agent(USER_AGENT)
+        .build();
+    let response = match agent.get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(err)) => {
+            return Err(Error::Network {
+                url: url.to_string(),
+                message: err.to_string(),
+            })
+        }
+    };
+    let status = response.status();
+    let retry_after = response
+        .header("Retry-After")
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body = response.into_string().map_err(|err| Error::Network {
+        url: url.to_string(),
+        message: err.to_string(),
+    })?;
+    stream::trace(&format!("{} {} ({} bytes)", status, url, body.len()));
+    Ok(Response {
+        status,
+        body,
+        retry_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::mock::{MockServer, Reply};
+
+    #[test]
+    fn test_get() {
+        let server = MockServer::start(|path| match path {
+            "/ok" => Reply::new(200, "{}"),
+            _ => Reply::new(429, "slow down").with_header("Retry-After", "3"),
+        })
+        .unwrap();
+        let timeout = Duration::from_secs(DEFAULT_TIMEOUT);
+
+        let ok = get(&format!("{}/ok", server.url), timeout).unwrap();
+        assert_eq!((ok.status, ok.body.as_str()), (200, "{}"));
+        let limited = get(&format!("{}/other", server.url), timeout).unwrap();
+        assert_eq!(limited.status, 429);
+        assert_eq!(limited.retry_after, Some(Duration::from_secs(3)));
+        assert_eq!(server.get_requests(), vec!["/ok", "/other"]);
+
+        drop(server);
+        assert!(matches!(
+            get("http://127.0.0.1:1/closed", timeout),
+            Err(Error::Network { .. })
+        ));
+    }
+}
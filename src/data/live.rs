@@ -0,0 +1,284 @@
+//! Live trades streamed from crypto exchanges
+//!
+//! Needs the `websocket` feature. Trades of a pair are read
+//! from a WebSocket as they happen and built into bars by an
+//! `Aggregator`, so the bar in progress is known after every
+//! trade. The URL of an exchange is set by the
+//! `FINFO_BINANCE_STREAM_URL` or `FINFO_COINBASE_STREAM_URL`
+//! environment variable where `{symbol}` is the pair.
+
+use super::crypto::{self, Exchange};
+use crate::error::{Error, Result};
+use crate::price::aggregate::Aggregator;
+use crate::price::candle::Tick;
+use crate::price::OHLC;
+use crate::utils::stream;
+use serde_json::Value;
+use tungstenite::Message;
+
+/// Returns the URL of the trades of a symbol on an exchange
+/// when none is set
+pub fn to_stream_url(exchange: Exchange, symbol: &str) -> String {
+    match exchange {
+        Exchange::Binance => format!(
+            "wss://stream.binance.com:9443/ws/{}@trade",
+            symbol.to_lowercase()
+        ),
+        Exchange::Coinbase => String::from("wss://ws-feed.exchange.coinbase.com"),
+    }
+}
+
+/// Get the URL of the trades of a symbol on an exchange
+///
+/// Set by the `FINFO_<EXCHANGE>_STREAM_URL` environment
+/// variable otherwise `to_stream_url`
+pub fn get_stream_url(exchange: Exchange, symbol: &str) -> String {
+    let name = format!("FINFO_{}_STREAM_URL", exchange.to_name().to_uppercase());
+    match std::env::var(name).ok().filter(|url| !url.is_empty()) {
+        Some(url) => url
+            .replace("{symbol}", symbol)
+            .replace("{symbol_lower}", &symbol.to_lowercase()),
+        None => to_stream_url(exchange, symbol),
+    }
+}
+
+/// Returns the message subscribing to the trades of a symbol
+///
+/// Binance streams trades of the URL without one
+pub fn to_subscribe(exchange: Exchange, symbol: &str) -> Option<String> {
+    match exchange {
+        Exchange::Binance => None,
+        Exchange::Coinbase => Some(format!(
+            "{{\"type\":\"subscribe\",\"product_ids\":[\"{}\"],\"channels\":[\"ticker\"]}}",
+            symbol
+        )),
+    }
+}
+
+/// Returns the trade of a message of JSON
+///
+/// Binance `trade` and `aggTrade` and Coinbase `ticker`
+/// messages are trades; others such as heartbeats are `None`
+pub fn to_tick(text: &str) -> Option<Tick> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    let number = |value: &Value| match value {
+        Value::String(text) => text.parse::<f32>().ok(),
+        _ => value.as_f64().map(|number| number as f32),
+    };
+    match (value["e"].as_str(), value["type"].as_str()) {
+        (Some("trade" | "aggTrade"), _) => Some(Tick::new(
+            value["T"].as_i64()? / 1000,
+            number(&value["p"])?,
+            number(&value["q"]).unwrap_or_default(),
+        )),
+        (_, Some("ticker")) => {
+            let time = chrono::DateTime::parse_from_rfc3339(value["time"].as_str()?).ok()?;
+            Some(Tick::new(
+                time.timestamp(),
+                number(&value["price"])?,
+                number(&value["last_size"]).unwrap_or_default(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Stream of the trades of a pair
+pub struct LiveStream {
+    pub url: String,
+    /// Message sent once connected
+    pub subscribe: Option<String>,
+}
+
+impl LiveStream {
+    /// Create a stream of a pair such as `BTC-USD` on an exchange
+    pub fn new(exchange: Exchange, ticker: &str) -> Result<Self> {
+        let (base, quote) =
+            crypto::to_pair(ticker).ok_or(Error::Invalid(format!("invalid pair '{}'", ticker)))?;
+        let symbol = exchange.to_symbol(&base, &quote);
+        Ok(Self {
+            url: get_stream_url(exchange, &symbol),
+            subscribe: to_subscribe(exchange, &symbol),
+        })
+    }
+
+    /// Build bars from every trade until the stream closes or
+    /// `on_bar` returns `false`
+    ///
+    /// `on_bar` gets the bar in progress after every trade and
+    /// every bar once it closes with `true`. A bar only closes
+    /// when a trade past it arrives, so in a quiet market the
+    /// finished bar stays in progress until the next trade.
+    /// Once the stream closes the trades still held and the
+    /// bar in progress are closed
+    pub fn run<F: FnMut(&OHLC, bool) -> bool>(
+        &self,
+        mut aggregator: Aggregator,
+        mut on_bar: F,
+    ) -> Result<()> {
+        let to_error = |err: tungstenite::Error| Error::Network {
+            url: self.url.clone(),
+            message: err.to_string(),
+        };
+        stream::debug(&format!("Connecting to {}", self.url));
+        let (mut socket, _) = tungstenite::connect(self.url.as_str()).map_err(to_error)?;
+        if let Some(message) = &self.subscribe {
+            socket
+                .send(Message::text(message.as_str()))
+                .map_err(to_error)?;
+        }
+
+        loop {
+            let text = match socket.read() {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_))
+                | Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                    stream::debug(&format!("Closed {}", self.url));
+                    for bar in aggregator.finish() {
+                        if !on_bar(&bar, true) {
+                            break;
+                        }
+                    }
+                    return Ok(());
+                }
+                // Pings are answered by the socket itself
+                Ok(_) => continue,
+                Err(err) => return Err(to_error(err)),
+            };
+            let Some(tick) = to_tick(text.as_str()) else {
+                stream::trace(&format!("Skipped {}", text.as_str()));
+                continue;
+            };
+            let closed = aggregator.push(tick);
+            let is_done = !closed.iter().all(|bar| on_bar(bar, true))
+                || aggregator.get_bar().is_some_and(|bar| !on_bar(bar, false));
+            if is_done {
+                let _ = socket.close(None);
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::mock::{self, MockSocket};
+    use crate::price::aggregate::Bars;
+    use crate::price::candle::Period;
+
+    fn minutes() -> Aggregator {
+        Aggregator::new(Bars::Time(Period::Seconds(60)))
+    }
+
+    fn fixture(name: &str) -> Vec<String> {
+        std::fs::read_to_string(mock::get_fixtures_dir().join(name))
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_to_tick() {
+        let trades = fixture("binance/BTCUSDT-trades.jsonl");
+        assert_eq!(to_tick(&trades[0]), None);
+        assert_eq!(
+            to_tick(&trades[1]),
+            Some(Tick::new(1_709_251_205, 61_000.0, 0.5))
+        );
+        let tickers = fixture("coinbase/BTC-USD-ticker.jsonl");
+        assert_eq!(to_tick(&tickers[0]), None);
+        assert_eq!(
+            to_tick(&tickers[1]),
+            Some(Tick::new(1_709_251_205, 61_000.0, 0.5))
+        );
+        assert_eq!(to_tick("not json"), None);
+    }
+
+    #[test]
+    fn test_run() {
+        for (exchange, name) in [
+            (Exchange::Binance, "binance/BTCUSDT-trades.jsonl"),
+            (Exchange::Coinbase, "coinbase/BTC-USD-ticker.jsonl"),
+        ] {
+            let server = MockSocket::start(fixture(name)).unwrap();
+            let mut live = LiveStream::new(exchange, "BTC-USD").unwrap();
+            live.url = server.url.clone();
+
+            let (mut closed, mut updates) = (vec![], 0);
+            live.run(minutes(), |bar, is_closed| {
+                match is_closed {
+                    true => closed.push(bar.clone()),
+                    false => updates += 1,
+                }
+                true
+            })
+            .unwrap();
+            // The third minute closes with the stream
+            assert_eq!(updates, 8);
+            assert_eq!(
+                closed,
+                vec![
+                    OHLC::new(61_000.0, 61_050.5, 60_990.0, 61_010.0)
+                        .at(1_709_251_200)
+                        .with_volume(1.85),
+                    OHLC::new(61_020.0, 61_100.0, 61_020.0, 61_080.0)
+                        .at(1_709_251_260)
+                        .with_volume(0.9),
+                    OHLC::new(61_060.0, 61_060.0, 61_060.0, 61_060.0)
+                        .at(1_709_251_320)
+                        .with_volume(0.6),
+                ]
+            );
+            // Give the server time to read the subscription
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let subscribe: Vec<String> = live.subscribe.into_iter().collect();
+            assert_eq!(server.get_received(), subscribe);
+        }
+    }
+
+    #[test]
+    fn test_run_stop() {
+        let server = MockSocket::start(fixture("binance/BTCUSDT-trades.jsonl")).unwrap();
+        let mut live = LiveStream::new(Exchange::Binance, "BTCUSDT").unwrap();
+        live.url = server.url.clone();
+        let mut updates = 0;
+        live.run(minutes(), |_, _| {
+            updates += 1;
+            updates < 3
+        })
+        .unwrap();
+        assert_eq!(updates, 3);
+
+        // Bars of three trades from the same stream
+        let server = MockSocket::start(fixture("binance/BTCUSDT-trades.jsonl")).unwrap();
+        live.url = server.url.clone();
+        let mut closed = 0;
+        live.run(Aggregator::new(Bars::Ticks(3)), |_, is_closed| {
+            closed += is_closed as usize;
+            true
+        })
+        .unwrap();
+        // The last two trades close with the stream
+        assert_eq!(closed, 3);
+
+        // Trades held by the grace window are not dropped
+        let server = MockSocket::start(fixture("binance/BTCUSDT-trades.jsonl")).unwrap();
+        live.url = server.url.clone();
+        let mut volume = 0.0;
+        live.run(minutes().with_grace(600), |bar, is_closed| {
+            volume += if is_closed { bar.volume } else { 0.0 };
+            true
+        })
+        .unwrap();
+        assert!((volume - 3.35f32).abs() < 1e-4);
+
+        drop(server);
+        live.url = String::from("ws://127.0.0.1:1");
+        assert!(matches!(
+            live.run(minutes(), |_, _| true),
+            Err(Error::Network { .. })
+        ));
+    }
+}
@@ -0,0 +1,375 @@
+//! Local stand-in of remote APIs
+//!
+//! A `MockServer` answers HTTP requests on `127.0.0.1` from a
+//! handler such as recorded fixture responses, so providers
+//! are tested and demoed without a network. Every requested
+//! path is kept to check what a provider asked for. With the
+//! `websocket` feature a `MockSocket` streams recorded
+//! messages the same way.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Answer of a `MockServer` to a request
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Reply {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            headers: vec![],
+        }
+    }
+
+    /// Add a header to the reply
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// HTTP server answering every request with a handler
+pub struct MockServer {
+    /// Base URL of the server such as `http://127.0.0.1:8080`
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Start a server on a free port
+    pub fn start<F>(handler: F) -> std::io::Result<Self>
+    where
+        F: Fn(&str) -> Reply + Send + 'static,
+    {
+        Self::bind("127.0.0.1:0", handler)
+    }
+
+    /// Start a server on an address such as `127.0.0.1:8080`
+    ///
+    /// The handler gets the path of every request with its
+    /// query such as `/v8/finance/chart/AAPL?range=1y`
+    pub fn bind<F>(address: &str, handler: F) -> std::io::Result<Self>
+    where
+        F: Fn(&str) -> Reply + Send + 'static,
+    {
+        let listener = TcpListener::bind(address)?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let requests = Arc::new(Mutex::new(vec![]));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let (log, stop) = (requests.clone(), stopped.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if let Some(path) = to_path(&stream) {
+                    if let Ok(mut log) = log.lock() {
+                        log.push(path.clone());
+                    }
+                    show_reply(&handler(&path), stream);
+                }
+            }
+        });
+        Ok(Self {
+            url,
+            requests,
+            stopped,
+        })
+    }
+
+    /// Get the path of every request received so far
+    pub fn get_requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .map(|requests| requests.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // Wake the listener up so it sees the stop
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+    }
+}
+
+/// WebSocket server sending the same messages to every client
+///
+/// Needs the `websocket` feature. The connection is closed
+/// once every message is sent
+#[cfg(feature = "websocket")]
+pub struct MockSocket {
+    /// URL of the server such as `ws://127.0.0.1:8080`
+    pub url: String,
+    received: Arc<Mutex<Vec<String>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "websocket")]
+impl MockSocket {
+    /// Start a server on a free port
+    pub fn start(messages: Vec<String>) -> std::io::Result<Self> {
+        Self::bind("127.0.0.1:0", messages)
+    }
+
+    /// Start a server on an address such as `127.0.0.1:8081`
+    pub fn bind(address: &str, messages: Vec<String>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let received = Arc::new(Mutex::new(vec![]));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let (log, stop) = (received.clone(), stopped.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(Ok(mut socket)) = stream.map(tungstenite::accept) else {
+                    continue;
+                };
+                for message in &messages {
+                    let _ = socket.send(tungstenite::Message::text(message.as_str()));
+                }
+                let _ = socket.close(None);
+                // Keep what the client sent until it closes too
+                while let Ok(message) = socket.read() {
+                    if let (tungstenite::Message::Text(text), Ok(mut log)) = (message, log.lock()) {
+                        log.push(text.to_string());
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            url,
+            received,
+            stopped,
+        })
+    }
+
+    /// Get every text message received from clients so far
+    pub fn get_received(&self) -> Vec<String> {
+        self.received
+            .lock()
+            .map(|received| received.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl Drop for MockSocket {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect(self.url.trim_start_matches("ws://"));
+    }
+}
+
+/// Returns the path of a request read from a stream
+fn to_path(stream: &TcpStream) -> Option<String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let path = line.split_whitespace().nth(1)?.to_string();
+    // Skip the headers
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {}
+        }
+    }
+    Some(path)
+}
+
+/// Write a reply as an HTTP response
+fn show_reply<T: Write>(reply: &Reply, mut output: T) {
+    let reason = match reply.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reason,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    let _ = write!(output, "{}\r\n{}", head, reply.body);
+    let _ = output.flush();
+}
+
+/// Get the directory of bundled fixture responses
+pub fn get_fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// Returns a handler answering chart requests such as
+/// `/v8/finance/chart/AAPL` with `AAPL.json` in `dir`
+///
+/// Tickers without a file get a `404` with `missing.json`
+pub fn to_chart_handler(dir: PathBuf) -> impl Fn(&str) -> Reply + Send + 'static {
+    move |path| {
+        let path = path.split('?').next().unwrap_or_default();
+        let ticker = path
+            .strip_prefix("/v8/finance/chart/")
+            .unwrap_or_default()
+            .to_uppercase();
+        match std::fs::read_to_string(dir.join(format!("{}.json", ticker))) {
+            Ok(body) if !ticker.is_empty() => Reply::new(200, &body),
+            _ => Reply::new(
+                404,
+                &std::fs::read_to_string(dir.join("missing.json")).unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+/// Returns the value of a parameter in the query of a path
+#[cfg(feature = "http")]
+fn to_param<'a>(path: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Returns a handler answering Binance klines requests such as
+/// `/api/v3/klines?symbol=BTCUSDT` with `binance/BTCUSDT.json`
+/// and Coinbase candles requests such as
+/// `/api/v3/brokerage/market/products/BTC-USD/candles` with
+/// `coinbase/BTC-USD.json` in `dir`
+///
+/// Only bars from the start until the end and limit of the
+/// query are sent, as the exchanges do. Unknown symbols get
+/// a `400` with `binance/invalid.json` or a `404` with
+/// `coinbase/missing.json`
+#[cfg(feature = "http")]
+pub fn to_klines_handler(dir: PathBuf) -> impl Fn(&str) -> Reply + Send + 'static {
+    use serde_json::Value;
+
+    move |path| {
+        let read = |name: String| std::fs::read_to_string(dir.join(name)).ok();
+        let number = |name: &str| to_param(path, name).and_then(|value| value.parse::<i64>().ok());
+        let limit = number("limit").unwrap_or(i64::MAX) as usize;
+        let route = path.split('?').next().unwrap_or_default();
+
+        if route == "/api/v3/klines" {
+            let symbol = to_param(path, "symbol").unwrap_or_default().to_uppercase();
+            let Some(body) = read(format!("binance/{}.json", symbol)) else {
+                return Reply::new(
+                    400,
+                    &read("binance/invalid.json".into()).unwrap_or_default(),
+                );
+            };
+            let (start, end) = (number("startTime"), number("endTime"));
+            let klines: Vec<Value> = serde_json::from_str(&body).unwrap_or_default();
+            let klines: Vec<Value> = klines
+                .into_iter()
+                .filter(|kline| {
+                    let time = kline[0].as_i64().unwrap_or_default();
+                    start.is_none_or(|start| time >= start) && end.is_none_or(|end| time <= end)
+                })
+                .take(limit)
+                .collect();
+            return Reply::new(200, &Value::from(klines).to_string());
+        }
+
+        let product = route
+            .strip_prefix("/api/v3/brokerage/market/products/")
+            .and_then(|route| route.strip_suffix("/candles"))
+            .unwrap_or_default()
+            .to_uppercase();
+        let body = read(format!("coinbase/{}.json", product)).filter(|_| !product.is_empty());
+        let Some(body) = body else {
+            return Reply::new(
+                404,
+                &read("coinbase/missing.json".into()).unwrap_or_default(),
+            );
+        };
+        let (start, end) = (number("start"), number("end"));
+        let mut value: Value = serde_json::from_str(&body).unwrap_or_default();
+        let candles: Vec<Value> = value["candles"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|candle| {
+                let time = candle["start"]
+                    .as_str()
+                    .and_then(|time| time.parse::<i64>().ok())
+                    .unwrap_or_default();
+                start.is_none_or(|start| time >= start) && end.is_none_or(|end| time <= end)
+            })
+            .take(limit)
+            .collect();
+        value["candles"] = Value::from(candles);
+        Reply::new(200, &value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_reply() {
+        let mut output: Vec<u8> = vec![];
+        show_reply(
+            &Reply::new(429, "{}").with_header("Retry-After", "1"),
+            &mut output,
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "HTTP/1.1 429 Too Many Requests\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\nRetry-After: 1\r\n\r\n{}"
+        );
+    }
+
+    #[test]
+    fn test_to_chart_handler() {
+        let handler = to_chart_handler(get_fixtures_dir().join("yahoo"));
+        assert_eq!(handler("/v8/finance/chart/aapl?range=1y").status, 200);
+        let missing = handler("/v8/finance/chart/NOPE");
+        assert_eq!(missing.status, 404);
+        assert!(missing.body.contains("Not Found"));
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_to_klines_handler() {
+        let handler = to_klines_handler(get_fixtures_dir());
+        let klines = handler("/api/v3/klines?symbol=BTCUSDT&startTime=1709337600000&limit=2");
+        assert_eq!(klines.status, 200);
+        assert!(klines.body.starts_with("[[1709337600000,"));
+        assert_eq!(klines.body.matches("[17").count(), 2);
+        assert_eq!(handler("/api/v3/klines?symbol=NOPE").status, 400);
+
+        let candles = handler("/api/v3/brokerage/market/products/btc-usd/candles?end=1709337600");
+        assert_eq!(candles.body.matches("\"start\"").count(), 2);
+        assert_eq!(
+            handler("/api/v3/brokerage/market/products/NOPE/candles").status,
+            404
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! This crate loads the bars of a ticker from a data source
+//! and builds its `History`.
+//!
+//! Every source implements `Provider` so the rest of the
+//! cli does not care where bars come from such as
+//! Data(this lib) -> Price -> Text/Indicators
+
+pub mod cache;
+pub mod calendar;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+#[cfg(feature = "http")]
+pub mod crypto;
+pub mod csv;
+pub mod file;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "websocket")]
+pub mod live;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod route;
+#[cfg(feature = "http")]
+pub mod yahoo;
+
+use crate::error::Result;
+use crate::price::action::Action;
+use crate::price::{History, OHLC};
+use std::path::PathBuf;
+
+/// A source of bars for tickers
+pub trait Provider {
+    /// Get every bar of a ticker ordered from oldest to newest
+    ///
+    /// A ticker without any data is `Error::NotFound`
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>>;
+
+    /// Get the history of a ticker
+    fn get_history(&self, ticker: &str) -> Result<History> {
+        Ok(History::from_bars(self.get_bars(ticker)?))
+    }
+
+    /// Get every ticker with local data sorted by name
+    ///
+    /// Nothing is fetched so remote providers have none
+    fn get_tickers(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Get the splits and dividends of a ticker its bars are
+    /// not adjusted for, ordered by time
+    ///
+    /// Default is: no actions
+    fn get_actions(&self, _ticker: &str) -> Result<Vec<Action>> {
+        Ok(vec![])
+    }
+
+    /// Get the code of the exchange a ticker trades on such
+    /// as `NMS`, used to pick its trading calendar
+    ///
+    /// Default is: unknown
+    fn get_exchange(&self, _ticker: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Latest price of a ticker
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Quote {
+    pub symbol: String,
+    pub currency: String,
+    pub exchange: String,
+    pub price: f32,
+    pub previous_close: f32,
+    /// Unix timestamp (seconds) of the price
+    pub time: i64,
+}
+
+impl<P: Provider + ?Sized> Provider for Box<P> {
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        (**self).get_bars(ticker)
+    }
+
+    fn get_history(&self, ticker: &str) -> Result<History> {
+        (**self).get_history(ticker)
+    }
+
+    fn get_tickers(&self) -> Vec<String> {
+        (**self).get_tickers()
+    }
+
+    fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+        (**self).get_actions(ticker)
+    }
+
+    fn get_exchange(&self, ticker: &str) -> Result<Option<String>> {
+        (**self).get_exchange(ticker)
+    }
+}
+
+/// Get the directory of data files
+///
+/// Set by the `FINFO_DATA` environment variable
+/// otherwise `~/.finfo/data`
+pub fn get_data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("FINFO_DATA") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".finfo").join("data")
+}
+
+/// Get the directory of cached bars of remote APIs
+///
+/// Set by the `FINFO_CACHE` environment variable
+/// otherwise `~/.finfo/cache`
+pub fn get_cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("FINFO_CACHE") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".finfo").join("cache")
+}
@@ -0,0 +1,655 @@
+//! Routing of tickers to providers with fallbacks
+//!
+//! Rules map patterns of tickers to the names of providers
+//! tried in order, such as routing `*-USD` to a crypto
+//! exchange before the chart API. A provider failing or
+//! sending fewer than `min_bars` bars falls back to the next
+//! one. Timeframes of the `History` still short of `min_bars`
+//! bars are then taken from whichever provider tried has the
+//! most bars of them. Bars are adjusted for the splits and
+//! dividends of the provider they came from before they are
+//! merged.
+//!
+//! Routes are read from `$FINFO_ROUTES` or `~/.finfo/routes`
+//! with one rule a line and an optional `min_bars` setting:
+//!
+//! ```text
+//! # pattern = providers
+//! min_bars = 100
+//! *-USD = binance, coinbase, yahoo
+//! ^* = yahoo
+//! ```
+
+use super::file::FileProvider;
+use super::Provider;
+use crate::error::{Error, Result};
+use crate::price::action::{Action, Adjust};
+use crate::price::resample::Timeframe;
+use crate::price::{History, OHLC};
+use crate::utils::stream;
+use std::path::{Path, PathBuf};
+
+/// Fewest bars a provider sends before falling back
+pub const DEFAULT_MIN_BARS: usize = 1;
+
+/// Name of the bars a `History` is built from
+pub const BARS: &str = "bars";
+
+/// Names of providers tried in order for matching tickers
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rule {
+    /// Pattern where `*` is any text and `?` any character
+    pub pattern: String,
+    pub providers: Vec<String>,
+}
+
+impl Rule {
+    pub fn new(pattern: &str, providers: &[&str]) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            providers: providers.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Returns `true` if a ticker matches the pattern ignoring case
+    pub fn is_match(&self, ticker: &str) -> bool {
+        is_match(&self.pattern, ticker)
+    }
+}
+
+/// Returns `true` if a ticker matches a pattern where `*` is
+/// any text and `?` any character, ignoring case
+pub fn is_match(pattern: &str, ticker: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_uppercase().chars().collect();
+    let ticker: Vec<char> = ticker.to_uppercase().chars().collect();
+    // Position of the last `*` and of the ticker it matched up to
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < ticker.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == ticker[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Rules and settings of a routes file
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Routes {
+    pub rules: Vec<Rule>,
+    /// Fewest bars before falling back when set
+    pub min_bars: Option<usize>,
+}
+
+/// Parse rules of `pattern = provider, provider` lines and
+/// the `min_bars = N` setting
+///
+/// Blank lines and lines starting with `#` are skipped.
+/// Tickers never hold `_` so `min_bars` is not a pattern
+pub fn to_routes(text: &str) -> Result<Routes> {
+    let mut routes = Routes::default();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let to_error = |message: &str| Error::Parse {
+            path: None,
+            line: i + 1,
+            message: message.to_string(),
+        };
+        let (pattern, providers) = line
+            .split_once('=')
+            .ok_or(to_error("expected 'pattern = providers'"))?;
+        if pattern.trim() == "min_bars" {
+            let min_bars = providers
+                .trim()
+                .parse()
+                .map_err(|_| to_error("expected 'min_bars = N'"))?;
+            routes.min_bars = Some(min_bars);
+            continue;
+        }
+        let providers: Vec<&str> = providers
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        match (pattern.trim(), providers.is_empty()) {
+            ("", _) => return Err(to_error("missing pattern")),
+            (_, true) => return Err(to_error("missing providers")),
+            (pattern, false) => routes.rules.push(Rule::new(pattern, &providers)),
+        }
+    }
+    Ok(routes)
+}
+
+/// Read the routes of a file
+///
+/// A missing file has no rules nor settings
+pub fn read_routes(path: &Path) -> Result<Routes> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => to_routes(&text).map_err(|err| err.in_file(path)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Routes::default()),
+        Err(err) => Err(Error::Io {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// Get the file of routes
+///
+/// Set by the `FINFO_ROUTES` environment variable
+/// otherwise `~/.finfo/routes`
+pub fn get_routes_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("FINFO_ROUTES") {
+        return PathBuf::from(path);
+    }
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".finfo").join("routes")
+}
+
+/// Name of the provider of every timeframe of a `History`
+///
+/// `BARS` names the bars it was built from
+pub type Sources = Vec<(&'static str, String)>;
+
+/// Returns the sources of a history as readable text such as
+/// `AAPL: bars from yahoo, hourly from binance`
+///
+/// Empty timeframes are left out
+pub fn to_report(ticker: &str, sources: &Sources) -> String {
+    let sources: Vec<String> = sources
+        .iter()
+        .map(|(timeframe, provider)| format!("{} from {}", timeframe, provider))
+        .collect();
+    match sources.is_empty() {
+        true => format!("{}: no bars", ticker),
+        false => format!("{}: {}", ticker, sources.join(", ")),
+    }
+}
+
+/// Provider of bars routing tickers to named providers
+pub struct RouteProvider {
+    /// Data files read before any route
+    pub files: Option<FileProvider>,
+    providers: Vec<(String, Box<dyn Provider>)>,
+    pub rules: Vec<Rule>,
+    /// Providers of tickers matching no rule
+    pub default: Vec<String>,
+    /// Fewest bars before falling back to the next provider
+    pub min_bars: usize,
+    /// Actions bars are adjusted for
+    pub adjust: Adjust,
+}
+
+impl Default for RouteProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteProvider {
+    /// Create a provider without any routes
+    pub fn new() -> Self {
+        Self {
+            files: None,
+            providers: vec![],
+            rules: vec![],
+            default: vec![],
+            min_bars: DEFAULT_MIN_BARS,
+            adjust: Adjust::Raw,
+        }
+    }
+
+    /// Add a named provider tried after the others by default
+    pub fn with_provider<P: Provider + 'static>(mut self, name: &str, provider: P) -> Self {
+        self.providers.push((name.to_string(), Box::new(provider)));
+        self.default.push(name.to_string());
+        self
+    }
+
+    /// Set the rules checked in order before the default route
+    ///
+    /// Rules naming an unknown provider are `Error::Invalid`
+    pub fn with_rules(mut self, rules: Vec<Rule>) -> Result<Self> {
+        for rule in &rules {
+            if let Some(name) = rule
+                .providers
+                .iter()
+                .find(|name| self.get_provider(name).is_none())
+            {
+                return Err(Error::Invalid(format!(
+                    "unknown provider '{}' for '{}'",
+                    name, rule.pattern
+                )));
+            }
+        }
+        self.rules = rules;
+        Ok(self)
+    }
+
+    /// Set the rules and settings of a routes file
+    ///
+    /// `min_bars` is kept when the routes do not set it
+    pub fn with_routes(mut self, routes: Routes) -> Result<Self> {
+        self.min_bars = routes.min_bars.unwrap_or(self.min_bars);
+        self.with_rules(routes.rules)
+    }
+
+    /// Get a provider by its name
+    pub fn get_provider(&self, name: &str) -> Option<&dyn Provider> {
+        self.providers
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, provider)| provider.as_ref())
+    }
+
+    /// Get the names of the providers of a ticker in order
+    pub fn get_route(&self, ticker: &str) -> &[String] {
+        self.rules
+            .iter()
+            .find(|rule| rule.is_match(ticker))
+            .map_or(&self.default, |rule| &rule.providers)
+    }
+
+    /// Get the history of a ticker with the provider of
+    /// every timeframe
+    ///
+    /// Providers are tried in route order until one sends
+    /// at least `min_bars` bars. Timeframes short of `min_bars`
+    /// bars keep those of the provider with the most of them.
+    /// Without any bars the error of the last provider is
+    /// returned
+    pub fn get_sourced_history(&self, ticker: &str) -> Result<(History, Sources)> {
+        if let Some(files) = self
+            .files
+            .as_ref()
+            .filter(|files| files.get_path(ticker).exists())
+        {
+            let history = self.to_adjusted(files, ticker, files.get_history(ticker)?);
+            let sources = to_sources(&history, "files");
+            return Ok((history, sources));
+        }
+
+        let mut history = History::default();
+        let mut sources: Vec<(&'static str, Option<&str>)> = to_slots()
+            .into_iter()
+            .map(|timeframe| (timeframe, None))
+            .collect();
+        let mut last_err = Error::NotFound(ticker.to_uppercase());
+        for name in self.get_route(ticker) {
+            let Some(provider) = self.get_provider(name) else {
+                continue;
+            };
+            let other = match provider.get_history(ticker) {
+                Ok(other) => self.to_adjusted(provider, ticker, other),
+                Err(err) => {
+                    stream::debug(&format!("{}: {} failed: {}", ticker, name, err));
+                    last_err = err;
+                    continue;
+                }
+            };
+            for (timeframe, source) in sources.iter_mut() {
+                let (bars, other_bars) = (
+                    to_slot(&mut history, timeframe),
+                    to_slot_ref(&other, timeframe),
+                );
+                if bars.len() < self.min_bars && other_bars.len() > bars.len() {
+                    *bars = other_bars.clone();
+                    *source = Some(name);
+                }
+            }
+            if other.timeframe.len() >= self.min_bars {
+                break;
+            }
+            stream::debug(&format!(
+                "{}: {} sent {} bars, falling back",
+                ticker,
+                name,
+                other.timeframe.len()
+            ));
+        }
+        if history.timeframe.is_empty() {
+            return Err(last_err);
+        }
+        let sources = sources
+            .into_iter()
+            .filter_map(|(timeframe, source)| Some((timeframe, source?.to_string())))
+            .collect();
+        Ok((history, sources))
+    }
+
+    /// Returns the history of a provider adjusted for its actions
+    ///
+    /// Actions failing to load leave the bars as they traded
+    fn to_adjusted(&self, provider: &dyn Provider, ticker: &str, history: History) -> History {
+        if self.adjust == Adjust::Raw {
+            return history;
+        }
+        match provider.get_actions(ticker) {
+            Ok(actions) if actions.is_empty() => history,
+            Ok(actions) => {
+                stream::debug(&format!(
+                    "{}: adjusting for {} actions",
+                    ticker,
+                    actions.len()
+                ));
+                history.to_adjusted(&actions, self.adjust)
+            }
+            Err(err) => {
+                stream::warn(&format!(
+                    "{}; prices are not adjusted",
+                    err.for_ticker(ticker)
+                ));
+                history
+            }
+        }
+    }
+}
+
+impl Provider for RouteProvider {
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        Ok(self.get_sourced_history(ticker)?.0.timeframe)
+    }
+
+    /// Get the history of a ticker reporting its providers
+    fn get_history(&self, ticker: &str) -> Result<History> {
+        let (history, sources) = self.get_sourced_history(ticker)?;
+        stream::info(&to_report(ticker, &sources));
+        Ok(history)
+    }
+
+    /// Get the actions of the data file or of the first
+    /// provider of the route with any
+    ///
+    /// Actions already adjusted for are left out
+    fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+        let actions = match self
+            .files
+            .as_ref()
+            .filter(|files| files.get_path(ticker).exists())
+        {
+            Some(files) => files.get_actions(ticker)?,
+            None => self
+                .get_route(ticker)
+                .iter()
+                .filter_map(|name| self.get_provider(name)?.get_actions(ticker).ok())
+                .find(|actions| !actions.is_empty())
+                .unwrap_or_default(),
+        };
+        Ok(actions
+            .into_iter()
+            .filter(|action| !self.adjust.is_adjusted(action))
+            .collect())
+    }
+
+    /// Get the exchange code of the first provider of the
+    /// route that knows it
+    fn get_exchange(&self, ticker: &str) -> Result<Option<String>> {
+        if let Some(files) = self.files.as_ref() {
+            if files.get_path(ticker).exists() {
+                return Ok(None);
+            }
+        }
+        Ok(self
+            .get_route(ticker)
+            .iter()
+            .find_map(|name| self.get_provider(name)?.get_exchange(ticker).ok().flatten()))
+    }
+
+    fn get_tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self
+            .files
+            .iter()
+            .flat_map(|files| files.get_tickers())
+            .chain(
+                self.providers
+                    .iter()
+                    .flat_map(|(_, provider)| provider.get_tickers()),
+            )
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+}
+
+/// Returns the names of the bars and timeframes of a `History`
+fn to_slots() -> Vec<&'static str> {
+    std::iter::once(BARS)
+        .chain(Timeframe::ALL.iter().map(Timeframe::to_name))
+        .collect()
+}
+
+/// Returns the bars of a history by the name of its slot
+fn to_slot<'a>(history: &'a mut History, name: &str) -> &'a mut Vec<OHLC> {
+    match Timeframe::ALL
+        .iter()
+        .find(|timeframe| timeframe.to_name() == name)
+    {
+        Some(Timeframe::Hourly) => &mut history.hourly,
+        Some(Timeframe::Daily) => &mut history.daily,
+        Some(Timeframe::Weekly) => &mut history.weekly,
+        Some(Timeframe::Monthly) => &mut history.monthly,
+        Some(Timeframe::Yearly) => &mut history.yearly,
+        None => &mut history.timeframe,
+    }
+}
+
+/// Returns the bars of a history by the name of its slot
+fn to_slot_ref<'a>(history: &'a History, name: &str) -> &'a Vec<OHLC> {
+    match Timeframe::ALL
+        .iter()
+        .find(|timeframe| timeframe.to_name() == name)
+    {
+        Some(timeframe) => history.get_timeframe(*timeframe),
+        None => &history.timeframe,
+    }
+}
+
+/// Returns the sources of every timeframe of a history with bars
+fn to_sources(history: &History, name: &str) -> Sources {
+    to_slots()
+        .into_iter()
+        .filter(|slot| !to_slot_ref(history, slot).is_empty())
+        .map(|slot| (slot, name.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Provider of a fixed number of bars a day apart
+    struct MockProvider {
+        bars: usize,
+        interval: i64,
+    }
+
+    impl Provider for MockProvider {
+        fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+            match ticker {
+                "NOPE" => Err(Error::NotFound(ticker.to_string())),
+                _ => Ok((0..self.bars as i64)
+                    .map(|i| OHLC::new(1.0, 2.0, 0.5, 1.5).at(i * self.interval))
+                    .collect()),
+            }
+        }
+    }
+
+    fn provider() -> RouteProvider {
+        RouteProvider::new()
+            .with_provider(
+                "stocks",
+                MockProvider {
+                    bars: 0,
+                    interval: 86_400,
+                },
+            )
+            .with_provider(
+                "crypto",
+                MockProvider {
+                    bars: 48,
+                    interval: 3_600,
+                },
+            )
+            .with_provider(
+                "daily",
+                MockProvider {
+                    bars: 30,
+                    interval: 86_400,
+                },
+            )
+    }
+
+    #[test]
+    fn test_is_match() {
+        let rule = |pattern: &str| Rule::new(pattern, &["yahoo"]);
+        assert!(rule("*-USD").is_match("btc-usd"));
+        assert!(!rule("*-USD").is_match("BTC-USDT"));
+        assert!(rule("^*").is_match("^GSPC"));
+        assert!(!rule("^*").is_match("AAPL"));
+        assert!(rule("A?PL").is_match("AAPL"));
+        assert!(rule("*").is_match(""));
+        assert!(rule("*US*T").is_match("BTCUSDT"));
+    }
+
+    #[test]
+    fn test_to_routes() {
+        let routes =
+            to_routes("# crypto first\n*-USD = binance, yahoo\n\nmin_bars = 50\n^* =yahoo\n")
+                .unwrap();
+        assert_eq!(
+            routes.rules,
+            vec![
+                Rule::new("*-USD", &["binance", "yahoo"]),
+                Rule::new("^*", &["yahoo"]),
+            ]
+        );
+        assert_eq!(routes.min_bars, Some(50));
+        assert_eq!(
+            to_routes("min_bars = many"),
+            Err(Error::Parse {
+                path: None,
+                line: 1,
+                message: String::from("expected 'min_bars = N'")
+            })
+        );
+        assert_eq!(
+            to_routes("*-USD binance"),
+            Err(Error::Parse {
+                path: None,
+                line: 1,
+                message: String::from("expected 'pattern = providers'")
+            })
+        );
+        assert!(to_routes("\n*-USD = ,").is_err());
+        assert_eq!(
+            read_routes(Path::new("/nonexistent/routes")),
+            Ok(Routes::default())
+        );
+    }
+
+    #[test]
+    fn test_get_route() {
+        let routed = provider()
+            .with_rules(vec![Rule::new("*-USD", &["crypto", "daily"])])
+            .unwrap();
+        assert_eq!(routed.get_route("BTC-USD"), ["crypto", "daily"]);
+        assert_eq!(routed.get_route("AAPL"), ["stocks", "crypto", "daily"]);
+        assert_eq!(
+            provider().with_rules(vec![Rule::new("*", &["nope"])]).err(),
+            Some(Error::Invalid(String::from(
+                "unknown provider 'nope' for '*'"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_get_sourced_history() {
+        let mut provider = provider();
+        // Empty bars fall back to the next provider
+        let (history, sources) = provider.get_sourced_history("AAPL").unwrap();
+        assert_eq!(history.timeframe.len(), 48);
+        assert_eq!(
+            to_report("AAPL", &sources),
+            "AAPL: bars from crypto, hourly from crypto, daily from crypto, weekly from crypto, monthly from crypto, yearly from crypto"
+        );
+
+        // Too few bars fall back too, keeping the timeframes
+        // the next provider has fewer bars of
+        provider = provider
+            .with_routes(to_routes("min_bars = 50").unwrap())
+            .unwrap();
+        assert_eq!(provider.min_bars, 50);
+        let (history, sources) = provider.get_sourced_history("AAPL").unwrap();
+        assert_eq!(history.hourly.len(), 48);
+        assert_eq!(history.daily.len(), 30);
+        assert_eq!(
+            sources[..3],
+            [
+                (BARS, String::from("crypto")),
+                ("hourly", String::from("crypto")),
+                ("daily", String::from("daily")),
+            ]
+        );
+        assert_eq!(
+            provider.get_bars("NOPE"),
+            Err(Error::NotFound(String::from("NOPE")))
+        );
+    }
+
+    #[test]
+    fn test_get_sourced_history_files() {
+        let dir = std::env::temp_dir().join("finfo-test-route");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("AAPL.csv"), "60,1,2,0.5,1.5\n").unwrap();
+        let mut provider = provider();
+        provider.files = Some(FileProvider::new(dir.clone()));
+
+        let (_, sources) = provider.get_sourced_history("AAPL").unwrap();
+        assert!(sources.iter().all(|(_, name)| name == "files"));
+        assert_eq!(provider.get_tickers(), vec!["AAPL"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_sourced_history_adjusted() {
+        let dir = std::env::temp_dir().join("finfo-test-route-adjusted");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("actions")).unwrap();
+        std::fs::write(dir.join("AAPL.csv"), "0,4,4,4,4,10\n86400,1,1,1,1,40\n").unwrap();
+        std::fs::write(dir.join("actions/AAPL.csv"), "86400,split,4:1\n").unwrap();
+        let mut routed = provider();
+        routed.files = Some(FileProvider::new(dir.clone()));
+
+        let closes = |routed: &RouteProvider| -> Vec<f32> {
+            let (history, _) = routed.get_sourced_history("AAPL").unwrap();
+            history.timeframe.iter().map(|bar| bar.close).collect()
+        };
+        assert_eq!(closes(&routed), [4.0, 1.0]);
+        assert_eq!(routed.get_actions("AAPL").unwrap().len(), 1);
+        routed.adjust = Adjust::Splits;
+        assert_eq!(closes(&routed), [1.0, 1.0]);
+        // Nothing is left to adjust for
+        assert_eq!(routed.get_actions("AAPL").unwrap(), vec![]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,340 @@
+//! Bars fetched from a Yahoo Finance style chart API
+//!
+//! Needs the `http` feature. A chart of a ticker is requested
+//! from `BASE/v8/finance/chart/TICKER` and holds its bars,
+//! its latest quote and its splits and dividends. The base
+//! URL is set by the `FINFO_URL` environment variable so a
+//! local stand-in such as `data::mock` can be used.
+
+use super::http;
+use super::{Provider, Quote};
+use crate::error::{Error, Result};
+use crate::price::action::Action;
+use crate::price::OHLC;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Base URL of the chart API when none is set
+pub const DEFAULT_URL: &str = "https://query1.finance.yahoo.com";
+
+/// Get the base URL of the chart API
+///
+/// Set by the `FINFO_URL` environment variable
+/// otherwise `DEFAULT_URL`
+pub fn get_base_url() -> String {
+    std::env::var("FINFO_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or(DEFAULT_URL.to_string())
+}
+
+/// Everything a chart API knows about a ticker
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Chart {
+    pub quote: Quote,
+    /// Bars ordered from oldest to newest
+    pub bars: Vec<OHLC>,
+    /// Splits and dividends ordered from oldest to newest
+    pub actions: Vec<Action>,
+}
+
+/// Provider of bars from a chart API
+pub struct YahooProvider {
+    pub base_url: String,
+    /// Period of bars such as `1y` or `max`
+    pub range: String,
+    /// Length of a bar such as `1h` or `1d`
+    pub interval: String,
+    pub timeout: Duration,
+    /// Chart of the latest `get_bars` with its ticker
+    latest: Mutex<Option<(String, Chart)>>,
+}
+
+impl YahooProvider {
+    /// Create a provider of a year of daily bars
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            range: String::from("1y"),
+            interval: String::from("1d"),
+            timeout: Duration::from_secs(http::DEFAULT_TIMEOUT),
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Get the URL of the chart of a ticker
+    pub fn get_url(&self, ticker: &str) -> String {
+        format!(
+            "{}/v8/finance/chart/{}?range={}&interval={}&events=div%2Csplits",
+            self.base_url,
+            ticker.to_uppercase(),
+            self.range,
+            self.interval
+        )
+    }
+
+    /// Get the chart of a ticker
+    ///
+    /// Unknown tickers are `Error::NotFound`
+    pub fn get_chart(&self, ticker: &str) -> Result<Chart> {
+        let url = self.get_url(ticker);
+        let response = http::get(&url, self.timeout)?;
+        match (response.status, from_chart(&response.body)) {
+            (200, Ok(chart)) => Ok(chart),
+            (404, _) => Err(Error::NotFound(ticker.to_uppercase())),
+            (_, Err(err)) => Err(Error::Network {
+                url,
+                message: err.to_string(),
+            }),
+            (status, Ok(_)) => Err(Error::Network {
+                url,
+                message: format!("unexpected status {}", status),
+            }),
+        }
+    }
+
+    /// Get the chart of the latest `get_bars` of a ticker
+    /// otherwise request it
+    ///
+    /// Asking for the actions or the exchange right after
+    /// the bars then needs a single request
+    fn get_latest(&self, ticker: &str) -> Result<Chart> {
+        if let Ok(latest) = self.latest.lock() {
+            if let Some((_, chart)) = latest
+                .as_ref()
+                .filter(|(other, _)| other.eq_ignore_ascii_case(ticker))
+            {
+                return Ok(chart.clone());
+            }
+        }
+        self.get_chart(ticker)
+    }
+}
+
+impl Provider for YahooProvider {
+    /// Get the bars of a ticker, always requesting its chart
+    fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+        let chart = self.get_chart(ticker)?;
+        let bars = chart.bars.clone();
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some((ticker.to_string(), chart));
+        }
+        Ok(bars)
+    }
+
+    /// Get the dividends of a ticker
+    ///
+    /// Bars of the chart API are already adjusted for splits
+    fn get_actions(&self, ticker: &str) -> Result<Vec<Action>> {
+        let actions = self.get_latest(ticker)?.actions;
+        Ok(actions
+            .into_iter()
+            .filter(|action| matches!(action, Action::Dividend { .. }))
+            .collect())
+    }
+
+    /// Get the exchange code of the quote of a ticker
+    fn get_exchange(&self, ticker: &str) -> Result<Option<String>> {
+        let exchange = self.get_latest(ticker)?.quote.exchange;
+        Ok(Some(exchange).filter(|exchange| !exchange.is_empty()))
+    }
+}
+
+/// Parse a chart of JSON
+///
+/// Bars with a missing price are skipped and a missing
+/// volume is `0`. Errors sent by the API are `Error::Invalid`
+pub fn from_chart(text: &str) -> Result<Chart> {
+    let value: Value = serde_json::from_str(text).map_err(|err| Error::Parse {
+        path: None,
+        line: err.line(),
+        message: err.to_string(),
+    })?;
+    let chart = &value["chart"];
+    if let Some(error) = chart["error"].as_object() {
+        let field = |name: &str| error.get(name).and_then(Value::as_str).unwrap_or_default();
+        return Err(Error::Invalid(format!(
+            "{}: {}",
+            field("code"),
+            field("description")
+        )));
+    }
+    let result = &chart["result"][0];
+    if !result.is_object() {
+        return Err(Error::Invalid(String::from("chart has no result")));
+    }
+
+    let meta = &result["meta"];
+    let text = |name: &str| meta[name].as_str().unwrap_or_default().to_string();
+    let quote = Quote {
+        symbol: text("symbol"),
+        currency: text("currency"),
+        exchange: text("exchangeName"),
+        price: meta["regularMarketPrice"].as_f64().unwrap_or_default() as f32,
+        previous_close: meta["chartPreviousClose"]
+            .as_f64()
+            .or(meta["previousClose"].as_f64())
+            .unwrap_or_default() as f32,
+        time: meta["regularMarketTime"].as_i64().unwrap_or_default(),
+    };
+
+    let prices = &result["indicators"]["quote"][0];
+    let to_price = |name: &str, i: usize| prices[name][i].as_f64().map(|price| price as f32);
+    let times = result["timestamp"].as_array().cloned().unwrap_or_default();
+    let mut bars = vec![];
+    for (i, time) in times.iter().enumerate() {
+        let Some(time) = time.as_i64() else {
+            continue;
+        };
+        let (Some(open), Some(high), Some(low), Some(close)) = (
+            to_price("open", i),
+            to_price("high", i),
+            to_price("low", i),
+            to_price("close", i),
+        ) else {
+            continue;
+        };
+        bars.push(
+            OHLC::new(open, high, low, close)
+                .at(time)
+                .with_volume(to_price("volume", i).unwrap_or_default()),
+        );
+    }
+
+    let events = &result["events"];
+    let mut actions: Vec<Action> = vec![];
+    for dividend in events["dividends"]
+        .as_object()
+        .into_iter()
+        .flat_map(|map| map.values())
+    {
+        actions.push(Action::Dividend {
+            time: dividend["date"].as_i64().unwrap_or_default(),
+            amount: dividend["amount"].as_f64().unwrap_or_default() as f32,
+        });
+    }
+    for split in events["splits"]
+        .as_object()
+        .into_iter()
+        .flat_map(|map| map.values())
+    {
+        actions.push(Action::Split {
+            time: split["date"].as_i64().unwrap_or_default(),
+            numerator: split["numerator"].as_f64().unwrap_or(1.0) as f32,
+            denominator: split["denominator"].as_f64().unwrap_or(1.0) as f32,
+        });
+    }
+    actions.sort_by_key(Action::get_time);
+
+    Ok(Chart {
+        quote,
+        bars,
+        actions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::mock::{self, MockServer};
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(mock::get_fixtures_dir().join("yahoo").join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_from_chart() {
+        let chart = from_chart(&fixture("AAPL.json")).unwrap();
+        assert_eq!(chart.quote.symbol, "AAPL");
+        assert_eq!(chart.quote.exchange, "NMS");
+        assert_eq!(chart.quote.price, 173.72);
+        assert_eq!(chart.quote.previous_close, 170.73);
+        // The bar without prices is skipped
+        assert_eq!(chart.bars.len(), 5);
+        assert_eq!(chart.bars[0].time, 1_710_163_800);
+        assert_eq!(chart.bars[4].close, 173.72);
+        assert_eq!(chart.bars[4].volume, 75_604_200.0);
+        assert_eq!(
+            chart.actions,
+            vec![
+                Action::Dividend {
+                    time: 1_710_423_000,
+                    amount: 0.24
+                },
+                Action::Split {
+                    time: 1_710_509_400,
+                    numerator: 4.0,
+                    denominator: 1.0
+                },
+            ]
+        );
+
+        assert_eq!(
+            from_chart(&fixture("missing.json")),
+            Err(Error::Invalid(String::from(
+                "Not Found: No data found, symbol may be delisted"
+            )))
+        );
+        // Bars missing prices are dropped rather than zeroed
+        assert!(from_chart(&fixture("BROKEN.json")).unwrap().bars.is_empty());
+        assert!(matches!(from_chart("<html>"), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn test_get_chart() {
+        let server = MockServer::start(mock::to_chart_handler(
+            mock::get_fixtures_dir().join("yahoo"),
+        ))
+        .unwrap();
+        let provider = YahooProvider::new(&format!("{}/", server.url));
+
+        assert_eq!(provider.get_bars("aapl").unwrap().len(), 5);
+        assert_eq!(
+            provider.get_bars("NOPE"),
+            Err(Error::NotFound(String::from("NOPE")))
+        );
+        // The chart of the bars is reused for the actions
+        assert_eq!(provider.get_actions("AAPL").unwrap().len(), 1);
+        assert_eq!(server.get_requests().len(), 2);
+        assert_eq!(provider.get_actions("BROKEN").unwrap().len(), 0);
+        assert_eq!(server.get_requests().len(), 3);
+        assert_eq!(provider.get_exchange("BROKEN"), Ok(None));
+        provider.get_bars("AAPL").unwrap();
+        assert_eq!(provider.get_exchange("AAPL"), Ok(Some(String::from("NMS"))));
+        assert_eq!(server.get_requests().len(), 5);
+        assert_eq!(
+            server.get_requests()[0],
+            "/v8/finance/chart/AAPL?range=1y&interval=1d&events=div%2Csplits"
+        );
+    }
+
+    #[test]
+    fn test_get_chart_offline() {
+        let server = MockServer::start(mock::to_chart_handler(
+            mock::get_fixtures_dir().join("yahoo"),
+        ))
+        .unwrap();
+        let dir = std::env::temp_dir().join("finfo-test-yahoo-offline");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut provider = crate::data::cache::CacheProvider::new(
+            YahooProvider::new(&server.url),
+            dir.join("data"),
+            dir.join("cache"),
+        );
+
+        provider.offline = true;
+        assert_eq!(
+            provider.get_bars("AAPL"),
+            Err(Error::NotFound(String::from("AAPL")))
+        );
+        provider.offline = false;
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 5);
+        // Offline reads the cache even once it is stale
+        provider.offline = true;
+        provider.max_age = Duration::ZERO;
+        assert_eq!(provider.get_bars("AAPL").unwrap().len(), 5);
+        assert_eq!(server.get_requests().len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
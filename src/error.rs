@@ -0,0 +1,127 @@
+//! Errors of the library
+//!
+//! Every fallible function of the data layer returns an
+//! `Error` so tools embedding finfo can tell a missing
+//! ticker from a broken file. The cli only prints them, so
+//! `Error` turns into a `String` where its message is needed.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Result of a fallible function of the library
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors of the library
+///
+/// New variants may be added in minor versions so matches
+/// need a wildcard arm
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No data exists for a ticker
+    NotFound(String),
+    /// Reading or writing a file failed
+    Io { path: PathBuf, message: String },
+    /// A line of data could not be parsed
+    ///
+    /// `path` is the file of the data when known and
+    /// `line` starts at `1`
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    /// A value such as a time or an option is not valid
+    Invalid(String),
+    /// A request to a remote API failed
+    Network { url: String, message: String },
+}
+
+impl Error {
+    /// Returns the error with the file it happened in
+    pub fn in_file(self, file: &std::path::Path) -> Self {
+        match self {
+            Error::Parse { line, message, .. } => Error::Parse {
+                path: Some(file.to_path_buf()),
+                line,
+                message,
+            },
+            err => err,
+        }
+    }
+
+    /// Returns the message of an error about a ticker
+    ///
+    /// The ticker leads the message once, as `NotFound`
+    /// already names it
+    pub fn for_ticker(&self, ticker: &str) -> String {
+        match self {
+            Error::NotFound(_) => self.to_string(),
+            err => format!("{}: {}", ticker, err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(ticker) => write!(f, "{}: no data found", ticker),
+            Error::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            Error::Parse {
+                path: Some(path),
+                line,
+                message,
+            } => write!(f, "{}: line {}: {}", path.display(), line, message),
+            Error::Parse {
+                path: None,
+                line,
+                message,
+            } => write!(f, "line {}: {}", line, message),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Network { url, message } => write!(f, "{}: {}", url, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for String {
+    fn from(err: Error) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let parse = Error::Parse {
+            path: None,
+            line: 3,
+            message: String::from("invalid price 'x'"),
+        };
+        assert_eq!(parse.to_string(), "line 3: invalid price 'x'");
+        assert_eq!(
+            parse.in_file(std::path::Path::new("AAPL.csv")).to_string(),
+            "AAPL.csv: line 3: invalid price 'x'"
+        );
+        assert_eq!(
+            Error::NotFound(String::from("AAPL")).to_string(),
+            "AAPL: no data found"
+        );
+        assert_eq!(
+            Error::NotFound(String::from("AAPL")).for_ticker("AAPL"),
+            "AAPL: no data found"
+        );
+        assert_eq!(
+            Error::Invalid(String::from("bad bar")).for_ticker("AAPL"),
+            "AAPL: bad bar"
+        );
+        assert_eq!(
+            String::from(Error::Invalid(String::from("invalid time ''"))),
+            "invalid time ''"
+        );
+    }
+}
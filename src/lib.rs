@@ -0,0 +1,46 @@
+//! Financial information of tickers from the terminal
+//!
+//! This library holds everything the `finfo` cli is built
+//! on so other tools can embed the same analysis such as
+//! Data -> Price -> Text/Indicators
+//!
+//! - `data` loads bars of a ticker from a `Provider`
+//! - `price` holds `OHLC` bars, their `History` and the
+//!   indicators, patterns and levels found in them
+//! - `utils` renders prices as text, tables and charts
+//!
+//! Fallible functions return an `Error` of this crate.
+//!
+//! The default `http` feature fetches bars from remote chart
+//! APIs through `data::yahoo` and crypto exchanges through
+//! `data::crypto`, cached by `data::cache`.
+//!
+//! The `websocket` feature streams live trades into bars
+//! through `data::live`.
+//!
+//! The `serde` feature serializes the price types and reads
+//! and writes histories as versioned JSON in `price::json`.
+//!
+//! The `mock` feature adds `data::mock`, a local stand-in of
+//! the remote APIs serving the bundled fixtures.
+//!
+//! ```
+//! use finfo::{History, OHLC};
+//!
+//! let bars = vec![
+//!     OHLC::new(10.0, 12.0, 9.0, 11.0).at(1_710_374_400),
+//!     OHLC::new(11.0, 13.0, 10.5, 12.5).at(1_710_460_800),
+//! ];
+//! let history = History::from_bars(bars);
+//! assert_eq!(history.daily.len(), 2);
+//! assert_eq!(history.timeframe[1].to_direction(), 'U');
+//! ```
+
+pub mod data;
+pub mod error;
+pub mod price;
+pub mod utils;
+
+pub use data::Provider;
+pub use error::{Error, Result};
+pub use price::{History, OHLC};
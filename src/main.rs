@@ -1,10 +1,11 @@
-pub mod parser;
-pub mod price;
-pub mod utils;
+mod parser;
 
 fn main() {
     // Gather arguments from terminal
     let args: Vec<String> = std::env::args().collect();
 
-    _ = parser::parse_args(args);
+    if let Err(err) = parser::parse_args(args) {
+        finfo::utils::stream::error(&err);
+        std::process::exit(1);
+    }
 }
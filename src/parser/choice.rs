@@ -5,12 +5,20 @@ pub(crate) struct ChoiceFlag {
 }
 
 /// ``Choice::Switch`` have the capability to be enabled/disabled
+///
+/// ``Choice::Value`` is enabled with an optional value
+/// given as `--long=value`
 pub(crate) enum Choice {
     Switch {
         enable: bool,
         short: char,
         long: String,
     },
+    Value {
+        value: Option<String>,
+        short: char,
+        long: String,
+    },
     Name {
         short: char,
         long: String,
@@ -25,6 +33,10 @@ impl Choice {
                 short: *short,
                 long: long.to_string(),
             },
+            Choice::Value { short, long, .. } => ChoiceFlag {
+                short: *short,
+                long: long.to_string(),
+            },
             Choice::Name { short, long } => ChoiceFlag {
                 short: *short,
                 long: long.to_string(),
@@ -39,10 +51,10 @@ impl Choice {
     ///
     /// # Examples
     ///
-    /// ```
-    /// let choice: choice::ChoiceGlossary = choice::ChoiceGlossary::new();
-    /// choice.verbose.enable()
-    /// assert_eq!(choice.verbose.get_state().unwrap, true)
+    /// ```ignore
+    /// let mut choice = ChoiceGlossary::new();
+    /// choice.verbose.enable().unwrap();
+    /// assert_eq!(choice.verbose.get_state().unwrap(), true);
     /// ```
     pub(crate) fn enable(&mut self) -> Result<(), String> {
         match self {
@@ -62,11 +74,48 @@ impl Choice {
     pub(crate) fn get_state(&self) -> Result<bool, String> {
         match self {
             Self::Switch { enable, .. } => Ok(*enable),
+            Self::Value { value, .. } => Ok(value.is_some()),
             _ => Err(String::from(
                 "Choice does not have a switch. Try defining choice type as `Choice::Switch`",
             )),
         }
     }
+
+    /// Set the value of choice from an argument
+    ///
+    /// The value follows `=` in the argument otherwise
+    /// it is empty
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut choice = ChoiceGlossary::new();
+    /// choice.watch.set_value("--watch=5").unwrap();
+    /// assert_eq!(choice.watch.get_value().unwrap(), Some("5"));
+    /// ```
+    pub(crate) fn set_value(&mut self, arg: &str) -> Result<(), String> {
+        match self {
+            Self::Value { value, .. } => {
+                *value = Some(arg.split_once('=').map_or("", |(_, v)| v).to_string());
+                Ok(())
+            }
+            _ => Err(String::from(
+                "Choice does not have a value. Try defining choice type as `Choice::Value`",
+            )),
+        }
+    }
+
+    /// Get the value of choice
+    ///
+    /// The value is `None` when the choice was not set
+    pub(crate) fn get_value(&self) -> Result<Option<&str>, String> {
+        match self {
+            Self::Value { value, .. } => Ok(value.as_deref()),
+            _ => Err(String::from(
+                "Choice does not have a value. Try defining choice type as `Choice::Value`",
+            )),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -93,7 +142,7 @@ mod tests {
             long: String::from("help"),
         };
         _ = choice.enable();
-        assert_eq!(choice.get_state().unwrap(), true);
+        assert!(choice.get_state().unwrap());
 
         choice = Choice::Name {
             short: 'h',
@@ -104,4 +153,29 @@ mod tests {
             "Choice does not have a switch. Try defining choice type as `Choice::Switch`"
         );
     }
+
+    #[test]
+    fn test_set_value() {
+        let mut choice = Choice::Value {
+            value: None,
+            short: 'w',
+            long: String::from("watch"),
+        };
+        assert_eq!(choice.get_value().unwrap(), None);
+        assert!(!choice.get_state().unwrap());
+        _ = choice.set_value("--watch=5");
+        assert_eq!(choice.get_value().unwrap(), Some("5"));
+        _ = choice.set_value("-w");
+        assert_eq!(choice.get_value().unwrap(), Some(""));
+        assert!(choice.get_state().unwrap());
+
+        choice = Choice::Name {
+            short: 'h',
+            long: String::from("help"),
+        };
+        assert_eq!(
+            choice.set_value("-h").unwrap_err(),
+            "Choice does not have a value. Try defining choice type as `Choice::Value`"
+        );
+    }
 }
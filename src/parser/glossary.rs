@@ -6,7 +6,7 @@ pub trait Glossary {
     ///
     /// TODO: macro this behavior when a new
     /// choice is added to
-    /// ```
+    /// ```text
     /// struct ChoiceGlossary
     /// ```
     fn _glossary(&self) -> Vec<&super::choice::Choice>;
@@ -51,6 +51,8 @@ pub trait Glossary {
     /// Search glossary for ambigious options (if any)
     fn search(&self, mut arg: &str) -> Vec<String> {
         let mut find = vec![];
+        // Values are not part of the option name
+        arg = arg.split('=').next().unwrap_or_default();
         let glossary = self._glossary();
         let mut choice = super::choice::ChoiceFlag::default();
 
@@ -67,10 +69,8 @@ pub trait Glossary {
         }
 
         // Search range if multiple options
-        let left_index;
-        let right_index;
-        left_index = self._bisect_search(&choice, false);
-        right_index = self._bisect_search(&choice, true);
+        let left_index = self._bisect_search(&choice, false);
+        let right_index = self._bisect_search(&choice, true);
 
         if left_index != -1 && right_index != -1 {
             for index in left_index..right_index + 1 {
@@ -85,25 +85,43 @@ pub trait Glossary {
     /// if ambigous then will print out possible choices
     /// # Examples
     ///
-    /// ```
-    /// let choice: choice::ChoiceGlossary = choice::ChoiceGlossary::new();
-    /// assert_eq!(choice.is_choice("--verb", choice.verbose), true)
+    /// ```ignore
+    /// let choice = ChoiceGlossary::new();
+    /// assert_eq!(choice.is_choice("--verb", &choice.verbose), true);
     /// ```
     fn is_choice(&self, arg: &str, choice: &super::choice::Choice) -> bool {
         let option_rule = Regex::new(r#"^(-.{1}|^--.*)$"#).unwrap(); // short options - ; long options --
+        let arg = arg.split('=').next().unwrap_or_default(); // values --long=value
         let args_nodash = arg.trim_start_matches("-");
-        return choice._to_flag().long.starts_with(args_nodash) && option_rule.is_match(arg);
+        let flag = choice._to_flag();
+        // Short options only match their letter and long options a prefix
+        let is_match = if arg.starts_with("--") {
+            flag.long.starts_with(args_nodash)
+        } else {
+            arg.len() == 2 && arg.ends_with(flag.short)
+        };
+        is_match && option_rule.is_match(arg)
     }
 }
 
 /// All available choices to use within cli
 pub(crate) struct ChoiceGlossary {
+    pub(crate) adjust: super::choice::Choice,
+    pub(crate) check: super::choice::Choice,
+    pub(crate) color: super::choice::Choice,
+    pub(crate) columns: super::choice::Choice,
+    pub(crate) export: super::choice::Choice,
     pub(crate) help: super::choice::Choice,
+    pub(crate) log_file: super::choice::Choice,
     pub(crate) nocache: super::choice::Choice,
     pub(crate) offline: super::choice::Choice,
     pub(crate) quiet: super::choice::Choice,
+    pub(crate) sort: super::choice::Choice,
+    pub(crate) stream: super::choice::Choice,
+    pub(crate) theme: super::choice::Choice,
     pub(crate) verbose: super::choice::Choice,
     pub(crate) version: super::choice::Choice,
+    pub(crate) watch: super::choice::Choice,
 }
 
 impl Glossary for ChoiceGlossary {
@@ -111,27 +129,67 @@ impl Glossary for ChoiceGlossary {
     ///
     /// TODO: macro this behavior when a new
     /// choice is added to
-    /// ```
+    /// ```text
     /// struct ChoiceGlossary
     /// ```
     fn _glossary(&self) -> Vec<&super::choice::Choice> {
         vec![
+            &self.adjust,
+            &self.check,
+            &self.color,
+            &self.columns,
+            &self.export,
             &self.help,
+            &self.log_file,
             &self.nocache,
             &self.offline,
             &self.quiet,
+            &self.sort,
+            &self.stream,
+            &self.theme,
             &self.verbose,
             &self.version,
+            &self.watch,
         ]
     }
 
     fn new() -> Self {
         Self {
+            adjust: super::choice::Choice::Value {
+                value: None,
+                short: 'a',
+                long: String::from("adjust"),
+            },
+            check: super::choice::Choice::Value {
+                value: None,
+                short: 'k',
+                long: String::from("check"),
+            },
+            color: super::choice::Choice::Value {
+                value: None,
+                short: 'c',
+                long: String::from("color"),
+            },
+            columns: super::choice::Choice::Value {
+                value: None,
+                short: 'C',
+                long: String::from("columns"),
+            },
+            export: super::choice::Choice::Value {
+                value: None,
+                short: 'e',
+                long: String::from("export"),
+            },
             help: super::choice::Choice::Switch {
                 enable: false,
                 short: 'h',
                 long: String::from("help"),
             },
+            log_file: super::choice::Choice::Value {
+                value: None,
+                short: 'l',
+                long: String::from("log-file"),
+            },
             nocache: super::choice::Choice::Switch {
                 enable: false,
                 short: 'n',
@@ -147,6 +205,21 @@ impl Glossary for ChoiceGlossary {
                 short: 'q',
                 long: String::from("quiet"),
             },
+            sort: super::choice::Choice::Value {
+                value: None,
+                short: 's',
+                long: String::from("sort"),
+            },
+            stream: super::choice::Choice::Value {
+                value: None,
+                short: 'S',
+                long: String::from("stream"),
+            },
+            theme: super::choice::Choice::Value {
+                value: None,
+                short: 't',
+                long: String::from("theme"),
+            },
             verbose: super::choice::Choice::Switch {
                 enable: false,
                 short: 'v',
@@ -156,16 +229,23 @@ impl Glossary for ChoiceGlossary {
                 short: 'V',
                 long: String::from("version"),
             },
+            watch: super::choice::Choice::Value {
+                value: None,
+                short: 'w',
+                long: String::from("watch"),
+            },
         }
     }
 }
 
+#[cfg(test)]
 struct MockGlossary {
     help: super::choice::Choice,
     verbose: super::choice::Choice,
     version: super::choice::Choice,
 }
 
+#[cfg(test)]
 impl Glossary for MockGlossary {
     fn _glossary(&self) -> Vec<&super::choice::Choice> {
         vec![&self.help, &self.verbose, &self.version]
@@ -195,10 +275,14 @@ mod tests {
     #[test]
     fn test_is_choice() {
         let glossary = MockGlossary::new();
-        assert_eq!(glossary.is_choice("--hlpe", &glossary.help), false);
-        assert_eq!(glossary.is_choice("--hel", &glossary.help), true);
-        assert_eq!(glossary.is_choice("-h", &glossary.help), true);
-        assert_eq!(glossary.is_choice("-he", &glossary.help), false);
+        assert!(!glossary.is_choice("--hlpe", &glossary.help));
+        assert!(glossary.is_choice("--hel", &glossary.help));
+        assert!(glossary.is_choice("-h", &glossary.help));
+        assert!(!glossary.is_choice("-he", &glossary.help));
+        assert!(glossary.is_choice("--help=now", &glossary.help));
+        assert!(glossary.is_choice("-V", &glossary.version));
+        assert!(!glossary.is_choice("-V", &glossary.verbose));
+        assert!(!glossary.is_choice("-v", &glossary.version));
     }
 
     #[test]
@@ -207,5 +291,6 @@ mod tests {
         assert_eq!(glossary.search("--ver").len(), 2);
         assert_eq!(glossary.search("--h").len(), 1);
         assert_eq!(glossary.search("--n").len(), 0);
+        assert_eq!(glossary.search("--verb=2").len(), 1);
     }
 }
@@ -7,7 +7,13 @@
 mod choice;
 mod glossary;
 use self::glossary::Glossary;
-use crate::utils;
+use finfo::data::{self, Provider};
+use finfo::utils;
+use regex::Regex;
+use std::io::{IsTerminal, Write};
+
+/// Values of `--color` which may follow `-c` as the next argument
+const COLORS: [&str; 3] = ["auto", "always", "never"];
 
 pub fn parse_args(args: Vec<String>) -> Result<(), String> {
     let mut choice = glossary::ChoiceGlossary::new();
@@ -17,6 +23,17 @@ pub fn parse_args(args: Vec<String>) -> Result<(), String> {
     let mut args_iter: std::iter::Peekable<std::slice::Iter<'_, String>> = args.iter().peekable();
     let mut arg_option: Option<&String> = args_iter.next();
 
+    let ticker_re = Regex::new(r"^[A-Za-z0-9.^=-]+$").unwrap();
+    let mut command: Option<&str> = None;
+    let mut tickers: Vec<&String> = vec![];
+    let verbose_re = Regex::new(r"^-vv+$").unwrap();
+    let mut verbosity: usize = 0;
+
+    // Colors follow the terminal until `--color` says otherwise
+    set_color(cmd_name, "auto");
+    // Themes are configured by `FINFO_THEME` until `--theme` says otherwise
+    set_theme(cmd_name, &std::env::var("FINFO_THEME").unwrap_or_default());
+
     while arg_option.is_some() {
         let arg: &String = arg_option.unwrap();
 
@@ -46,31 +63,25 @@ pub fn parse_args(args: Vec<String>) -> Result<(), String> {
                         }
                         // skip main call if more arguments...
                     }
-                    // Historical information
-                    arg if "history".starts_with(arg) => {
-                        // TODO - Ticker will consume Info which impl InfoBuilder trait
-                        utils::stream::log(
-                            std::io::stdout().lock(),
-                            "Getting historical ticker info.....",
-                            &choice.verbose.get_state()?,
-                        );
-                        break;
+                    // Interactive dashboard
+                    arg if command.is_none()
+                        && tickers.is_empty()
+                        && "dashboard".starts_with(arg) =>
+                    {
+                        command = Some("dashboard");
                     }
-                    // Last command should be ticker symbol
-                    arg if arg == &args[args.len() - 1] => {
-                        utils::stream::log(
-                            std::io::stdout().lock(),
-                            "Getting basic ticker info.....",
-                            &choice.verbose.get_state()?,
-                        );
+                    // Historical information
+                    arg if command.is_none()
+                        && tickers.is_empty()
+                        && "history".starts_with(arg) =>
+                    {
+                        command = Some("history");
                     }
+                    // Remaining commands should be ticker symbols
+                    arg if ticker_re.is_match(arg) => tickers.push(arg),
                     // Command not found
                     _ => {
-                        utils::stream::log(
-                            std::io::stdout().lock(),
-                            &format!("{}: invalid command '{}'", cmd_name, arg),
-                            &true,
-                        );
+                        utils::stream::error(&format!("{}: invalid command '{}'", cmd_name, arg));
                         utils::stream::show_usage(cmd_name);
                         std::process::exit(0);
                     }
@@ -81,20 +92,57 @@ pub fn parse_args(args: Vec<String>) -> Result<(), String> {
                 utils::stream::show_help(cmd_name);
                 std::process::exit(0);
             }
+            arg if choice.is_choice(arg, &choice.adjust) => choice.adjust.set_value(arg)?,
+            arg if choice.is_choice(arg, &choice.check) => choice.check.set_value(arg)?,
+            arg if choice.is_choice(arg, &choice.color) => {
+                choice
+                    .color
+                    .set_value(&to_choice_arg(arg, &mut args_iter, &COLORS))?;
+                set_color(cmd_name, choice.color.get_value()?.unwrap_or_default());
+            }
+            arg if choice.is_choice(arg, &choice.columns) => choice
+                .columns
+                .set_value(&to_value_arg(arg, &mut args_iter))?,
+            arg if choice.is_choice(arg, &choice.export) => choice
+                .export
+                .set_value(&to_value_arg(arg, &mut args_iter))?,
+            arg if choice.is_choice(arg, &choice.sort) => {
+                choice.sort.set_value(&to_value_arg(arg, &mut args_iter))?
+            }
+            arg if choice.is_choice(arg, &choice.log_file) => {
+                choice
+                    .log_file
+                    .set_value(&to_value_arg(arg, &mut args_iter))?;
+                let path = choice.log_file.get_value()?.unwrap_or_default();
+                if let Err(err) = utils::stream::set_log_file(std::path::Path::new(path)) {
+                    utils::stream::error(&format!("{}: {}", cmd_name, err));
+                    std::process::exit(1);
+                }
+            }
             arg if choice.is_choice(arg, &choice.nocache) => choice.nocache.enable()?,
             arg if choice.is_choice(arg, &choice.offline) => choice.offline.enable()?,
             arg if choice.is_choice(arg, &choice.quiet) => choice.quiet.enable()?,
-            arg if choice.is_choice(arg, &choice.verbose) => choice.verbose.enable()?,
+            arg if choice.is_choice(arg, &choice.stream) => choice.stream.set_value(arg)?,
+            arg if choice.is_choice(arg, &choice.theme) => {
+                choice.theme.set_value(&to_value_arg(arg, &mut args_iter))?;
+                set_theme(cmd_name, choice.theme.get_value()?.unwrap_or_default());
+            }
+            arg if choice.is_choice(arg, &choice.verbose) => {
+                choice.verbose.enable()?;
+                verbosity += 1;
+            }
+            // Repeated verbose such as `-vvv`
+            arg if verbose_re.is_match(arg) => {
+                choice.verbose.enable()?;
+                verbosity += arg.len() - 1;
+            }
+            arg if choice.is_choice(arg, &choice.watch) => choice.watch.set_value(arg)?,
             arg if choice.is_choice(arg, &choice.version) => {
                 utils::stream::show_license(cmd_name);
                 std::process::exit(0);
             }
             _ => {
-                utils::stream::log(
-                    std::io::stdout().lock(),
-                    &format!("{}: invalid option '{}'", cmd_name, arg),
-                    &true,
-                );
+                utils::stream::error(&format!("{}: invalid option '{}'", cmd_name, arg));
                 utils::stream::show_usage(cmd_name);
                 std::process::exit(0);
             }
@@ -102,5 +150,508 @@ pub fn parse_args(args: Vec<String>) -> Result<(), String> {
         arg_option = args_iter.next();
     }
 
+    let quiet = choice.quiet.get_state()?;
+    utils::stream::set_level(utils::stream::to_level(verbosity, quiet));
+    utils::stream::trace(&format!("Arguments: {:?}", &args[1..]));
+    // Refreshes fetch again once the cache is older than the interval
+    let interval = match choice.watch.get_value()?.map(utils::watch::to_interval) {
+        Some(Ok(interval)) => Some(interval),
+        Some(Err(err)) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err));
+            utils::stream::show_usage(cmd_name);
+            std::process::exit(0);
+        }
+        None => None,
+    };
+    // Without a remote provider only files are read
+    let offline = choice.offline.get_state()? || cfg!(not(feature = "http"));
+    let mut provider = to_provider(offline, choice.nocache.get_state()?, interval)?;
+    if let Some(value) = choice.adjust.get_value()? {
+        match finfo::price::action::to_adjust(value) {
+            Ok(adjust) => provider.adjust = adjust,
+            Err(err) => {
+                utils::stream::error(&format!("{}: {}", cmd_name, err));
+                utils::stream::show_usage(cmd_name);
+                std::process::exit(0);
+            }
+        }
+    }
+
+    // Dashboard defaults to every ticker with data
+    if command == Some("dashboard") {
+        let tickers: Vec<String> = if tickers.is_empty() {
+            provider.get_tickers()
+        } else {
+            tickers.iter().map(|ticker| ticker.to_uppercase()).collect()
+        };
+        // The log pane shows information unless quiet
+        let level = match quiet {
+            true => utils::stream::Level::Error,
+            false => utils::stream::get_level().max(utils::stream::Level::Info),
+        };
+        return utils::dashboard::Dashboard::new(provider, tickers, level, 2).run();
+    }
+
+    if tickers.is_empty() {
+        utils::stream::show_usage(cmd_name);
+        std::process::exit(0);
+    }
+    // Trades are streamed into bars instead of fetched
+    if let Some(period) = choice.stream.get_value()? {
+        if offline {
+            utils::stream::error(&format!("{}: streaming needs the network", cmd_name));
+            std::process::exit(1);
+        }
+        show_stream(cmd_name, &provider, &tickers, period);
+        return Ok(());
+    }
+    // Bars are checked and maybe repaired with `--check`
+    let check = match choice
+        .check
+        .get_value()?
+        .map(finfo::price::validate::to_repair)
+    {
+        Some(Ok(repair)) => Some(repair),
+        Some(Err(err)) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err));
+            utils::stream::show_usage(cmd_name);
+            std::process::exit(0);
+        }
+        None => None,
+    };
+    // Histories are written to files instead of shown
+    if let Some(path) = choice.export.get_value()? {
+        export(cmd_name, &provider, &tickers, path, check);
+        return Ok(());
+    }
+    // Table of bars for the history
+    let mut table = utils::table::Table::new(2);
+    let columns = choice.columns.get_value()?.map(utils::table::to_columns);
+    let sort = choice.sort.get_value()?.map(utils::table::to_sort);
+    match (columns.transpose(), sort.transpose()) {
+        (Ok(columns), Ok(sort)) => {
+            table.columns = columns.unwrap_or(table.columns);
+            table.sort = sort;
+        }
+        (Err(err), _) | (_, Err(err)) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err));
+            utils::stream::show_usage(cmd_name);
+            std::process::exit(0);
+        }
+    }
+
+    let calendars = data::calendar::get_calendars(&data::calendar::get_calendars_dir());
+    let render = |output: &mut Vec<u8>| {
+        for ticker in &tickers {
+            let ticker = ticker.to_uppercase();
+            let msg = match command {
+                Some("history") => "Getting historical ticker info.....",
+                _ => "Getting basic ticker info.....",
+            };
+            utils::stream::info(msg);
+            let history = provider.get_history(&ticker);
+            // The quotes fetched with the history name the exchange
+            let exchange = provider.get_exchange(&ticker).ok().flatten();
+            let calendar = data::calendar::find_calendar(&calendars, &ticker, exchange.as_deref());
+            let history = match history {
+                Ok(history) => to_checked(&ticker, history, calendar, check, &mut *output),
+                Err(err) => {
+                    utils::stream::error(&format!("{}: {}", cmd_name, err.for_ticker(&ticker)));
+                    continue;
+                }
+            };
+
+            let now = chrono::Utc::now().timestamp();
+            match command {
+                Some("history") => {
+                    history.show_history(&mut *output, Some(&table), 2);
+                    utils::chart::Chart::new(20, 2).show_chart(&history.timeframe, &mut *output);
+                    if let Some(calendar) = calendar {
+                        history.show_market(&mut *output, calendar, now);
+                    }
+                }
+                _ => {
+                    let market = calendar.map(|calendar| match calendar.to_session(now) {
+                        Some(session) => session.to_name(),
+                        None => "closed",
+                    });
+                    utils::compact::show_compact(&ticker, &history, market, &mut *output, 20, 2)
+                }
+            }
+        }
+    };
+
+    match interval {
+        Some(interval) => {
+            // Offline refreshes only re-read the files
+            let source = if offline {
+                "cache and data files (offline)"
+            } else {
+                "data files and remote provider"
+            };
+            utils::stream::info(&format!(
+                "Watching every {}s from {}.....",
+                interval.as_secs_f32(),
+                source
+            ));
+            let in_place = std::io::stdout().is_terminal();
+            utils::watch::Watch::new(interval, in_place).run(render, std::io::stdout());
+        }
+        None => {
+            let mut frame: Vec<u8> = vec![];
+            render(&mut frame);
+            let _ = std::io::stdout().write_all(&frame);
+        }
+    }
+
     Ok(())
 }
+
+/// Returns the provider of bars reading data files first
+/// then routing tickers to cached remote providers
+///
+/// Routes are read from `$FINFO_ROUTES` and tickers matching
+/// none are fetched from the chart API. Without the file
+/// crypto pairs are fetched from the exchanges first
+fn to_provider(
+    offline: bool,
+    nocache: bool,
+    max_age: Option<std::time::Duration>,
+) -> finfo::error::Result<data::route::RouteProvider> {
+    let to_cached = |remote: Box<dyn Provider>, cache_dir: std::path::PathBuf| {
+        let mut provider = data::cache::CacheProvider::new(remote, data::get_data_dir(), cache_dir);
+        provider.offline = offline;
+        provider.nocache = nocache;
+        provider.max_age = max_age.map_or(provider.max_age, |age| provider.max_age.min(age));
+        provider
+    };
+    let mut provider = data::route::RouteProvider::new();
+    provider.files = Some(data::file::FileProvider::new(data::get_data_dir()));
+
+    #[cfg(feature = "http")]
+    {
+        use data::crypto::{CryptoProvider, Exchange};
+        let chart = data::yahoo::YahooProvider::new(&data::yahoo::get_base_url());
+        provider =
+            provider.with_provider("yahoo", to_cached(Box::new(chart), data::get_cache_dir()));
+        // Exchanges cache apart as they name pairs like the chart API
+        for exchange in [Exchange::Binance, Exchange::Coinbase] {
+            let remote = CryptoProvider::new(exchange, &exchange.get_base_url());
+            let cache_dir = data::get_cache_dir().join(exchange.to_name());
+            provider =
+                provider.with_provider(exchange.to_name(), to_cached(Box::new(remote), cache_dir));
+        }
+        provider.default = vec![String::from("yahoo")];
+    }
+    #[cfg(not(feature = "http"))]
+    {
+        let remote = data::file::FileProvider::new(data::get_cache_dir());
+        provider =
+            provider.with_provider("cache", to_cached(Box::new(remote), data::get_cache_dir()));
+    }
+
+    let path = data::route::get_routes_path();
+    let routes = data::route::read_routes(&path)?;
+    // Pairs go to the exchanges unless a routes file says otherwise
+    #[cfg(feature = "http")]
+    let routes = match path.exists() {
+        true => routes,
+        false => data::route::Routes {
+            rules: to_default_rules(),
+            ..routes
+        },
+    };
+    provider
+        .with_routes(routes)
+        .map_err(|err| finfo::Error::Invalid(format!("{}: {}", path.display(), err)))
+}
+
+/// Returns the rules used without a routes file
+///
+/// Pairs quoted in a currency such as `BTC-USD` or in a
+/// stablecoin such as `ETHUSDT` are fetched from the
+/// exchanges before the chart API
+#[cfg(feature = "http")]
+fn to_default_rules() -> Vec<data::route::Rule> {
+    const PROVIDERS: [&str; 3] = ["binance", "coinbase", "yahoo"];
+    let stablecoins = ["USDT", "USDC", "BUSD", "FDUSD"];
+    data::crypto::QUOTES
+        .iter()
+        .map(|quote| format!("*-{}", quote))
+        .chain(stablecoins.iter().map(|quote| format!("*{}", quote)))
+        .map(|pattern| data::route::Rule::new(&pattern, &PROVIDERS))
+        .collect()
+}
+
+/// Stream the trades of a pair into bars of a `--stream` value
+///
+/// The exchange is the first one routed to for the pair. The
+/// bar in progress is redrawn after every trade and closed
+/// bars are kept above it
+#[cfg(feature = "websocket")]
+fn show_stream(
+    cmd_name: &str,
+    provider: &data::route::RouteProvider,
+    tickers: &[&String],
+    value: &str,
+) {
+    use data::crypto::Exchange;
+    let bars = match finfo::price::aggregate::to_bars(value) {
+        Ok(bars) => bars,
+        Err(err) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err));
+            utils::stream::show_usage(cmd_name);
+            std::process::exit(0);
+        }
+    };
+    if tickers.len() > 1 {
+        utils::stream::error(&format!("{}: streaming takes a single pair", cmd_name));
+        std::process::exit(1);
+    }
+    let ticker = tickers[0].to_uppercase();
+    let exchange = provider
+        .get_route(&ticker)
+        .iter()
+        .find_map(|name| match name.as_str() {
+            "coinbase" => Some(Exchange::Coinbase),
+            "binance" => Some(Exchange::Binance),
+            _ => None,
+        })
+        .unwrap_or(Exchange::Binance);
+    let live = match data::live::LiveStream::new(exchange, &ticker) {
+        Ok(live) => live,
+        Err(err) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err.for_ticker(&ticker)));
+            std::process::exit(1);
+        }
+    };
+
+    utils::stream::info(&format!(
+        "Streaming {} trades from {}.....",
+        ticker,
+        exchange.to_name()
+    ));
+    let in_place = std::io::stdout().is_terminal();
+    let mut watch = utils::watch::Watch::new(std::time::Duration::ZERO, in_place);
+    let aggregator = finfo::price::aggregate::Aggregator::new(bars);
+    let result = live.run(aggregator, |bar, is_closed| {
+        let date = chrono::DateTime::from_timestamp(bar.time, 0)
+            .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let mut frame: Vec<u8> = vec![];
+        let status = if is_closed { " (closed)" } else { "" };
+        let _ = writeln!(frame, "{} {}{}", ticker, date, status);
+        bar.show_ohlc(&mut frame, 2);
+        watch.show_frame(
+            String::from_utf8_lossy(&frame).to_string(),
+            std::io::stdout(),
+        );
+        // The next bar is drawn below the closed one
+        if is_closed {
+            watch = utils::watch::Watch::new(std::time::Duration::ZERO, in_place);
+        }
+        true
+    });
+    if let Err(err) = result {
+        utils::stream::error(&format!("{}: {}", cmd_name, err.for_ticker(&ticker)));
+        std::process::exit(1);
+    }
+}
+
+/// Exits as streaming needs the `websocket` feature
+#[cfg(not(feature = "websocket"))]
+fn show_stream(
+    cmd_name: &str,
+    _provider: &data::route::RouteProvider,
+    _tickers: &[&String],
+    _value: &str,
+) {
+    utils::stream::error(&format!(
+        "{}: streaming needs the websocket feature",
+        cmd_name
+    ));
+    std::process::exit(1);
+}
+
+/// Write the history of every ticker to a data file picked
+/// by the extension of `path`
+///
+/// A `{ticker}` in `path` is replaced by each ticker and is
+/// needed for several tickers
+fn export<P: Provider>(
+    cmd_name: &str,
+    provider: &P,
+    tickers: &[&String],
+    path: &str,
+    check: Option<Option<finfo::price::validate::Repair>>,
+) {
+    let calendars = data::calendar::get_calendars(&data::calendar::get_calendars_dir());
+    if tickers.len() > 1 && !path.contains("{ticker}") {
+        utils::stream::error(&format!(
+            "{}: exporting several tickers needs '{{ticker}}' in '{}'",
+            cmd_name, path
+        ));
+        std::process::exit(1);
+    }
+    for ticker in tickers {
+        let ticker = ticker.to_uppercase();
+        let path = std::path::PathBuf::from(path.replace("{ticker}", &ticker));
+        match provider.get_history(&ticker).and_then(|history| {
+            let exchange = provider.get_exchange(&ticker).ok().flatten();
+            let calendar = data::calendar::find_calendar(&calendars, &ticker, exchange.as_deref());
+            let history = to_checked(&ticker, history, calendar, check, std::io::stdout());
+            data::file::write_history(&path, &ticker, &history)
+        }) {
+            Ok(()) => utils::stream::info(&format!("Exported {} to {}", ticker, path.display())),
+            Err(err) => utils::stream::error(&format!("{}: {}", cmd_name, err.for_ticker(&ticker))),
+        }
+    }
+}
+
+/// Returns the history checked by a `--check` value
+///
+/// With `--check` the report is shown and bars are repaired
+/// when asked. Otherwise bars breaking the rules of an `OHLC`
+/// or of time order are only warned about. Missing sessions
+/// are found with the calendar of the ticker when known
+fn to_checked<T: Write>(
+    ticker: &str,
+    history: finfo::price::History,
+    calendar: Option<&data::calendar::Calendar>,
+    check: Option<Option<finfo::price::validate::Repair>>,
+    output: T,
+) -> finfo::price::History {
+    let mut validator = finfo::price::validate::Validator::new();
+    if let Some(calendar) = calendar {
+        validator = validator.with_sessions(calendar.clone());
+    }
+    let report = validator.validate(&history.timeframe);
+    match check {
+        Some(repair) => {
+            report.show_report(output, ticker);
+            match repair {
+                Some(repair) => history.to_repaired(&validator, repair),
+                None => history,
+            }
+        }
+        None => {
+            if report.to_broken() > 0 {
+                utils::stream::warn(&format!(
+                    "{}: {} bad bars; see --check",
+                    ticker,
+                    report.to_broken()
+                ));
+            }
+            history
+        }
+    }
+}
+
+/// Set the level of colors from a `--color` value
+///
+/// Exits on values other than `auto`, `always` or `never`
+fn set_color(cmd_name: &str, value: &str) {
+    let is_terminal = std::io::stdout().is_terminal();
+    match utils::color::to_level(value, is_terminal, |name| std::env::var(name).ok()) {
+        Ok(level) => utils::color::set_level(level),
+        Err(err) => {
+            utils::stream::error(&format!("{}: {}", cmd_name, err));
+            utils::stream::show_usage(cmd_name);
+            std::process::exit(0);
+        }
+    }
+}
+
+/// Set the theme from a list of theme names
+///
+/// Exits on unknown names
+fn set_theme(cmd_name: &str, value: &str) {
+    if let Err(err) = utils::theme::set_theme(value) {
+        utils::stream::error(&format!("{}: {}", cmd_name, err));
+        utils::stream::show_usage(cmd_name);
+        std::process::exit(0);
+    }
+}
+
+/// Returns an option with its value attached as `--long=value`
+///
+/// A value given as the next argument such as `-s close`
+/// is taken from `args_iter`
+fn to_value_arg<'a, I: Iterator<Item = &'a String>>(
+    arg: &str,
+    args_iter: &mut std::iter::Peekable<I>,
+) -> String {
+    if arg.contains('=') {
+        return arg.to_string();
+    }
+    // Short options are not values but `-volume` is
+    let is_option = |next: &&String| next.starts_with("--") || next.len() == 2;
+    match args_iter.next_if(|next| !(next.starts_with('-') && is_option(next))) {
+        Some(value) => format!("{}={}", arg, value),
+        None => arg.to_string(),
+    }
+}
+
+/// Returns an option with its optional value attached as `--long=value`
+///
+/// Unlike `to_value_arg` the next argument is only taken
+/// when it is one of `values`, such as `-c never`
+fn to_choice_arg<'a, I: Iterator<Item = &'a String>>(
+    arg: &str,
+    args_iter: &mut std::iter::Peekable<I>,
+    values: &[&str],
+) -> String {
+    if arg.contains('=') {
+        return arg.to_string();
+    }
+    match args_iter.next_if(|next| values.contains(&next.as_str())) {
+        Some(value) => format!("{}={}", arg, value),
+        None => arg.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_default_rules() {
+        let rules = to_default_rules();
+        let to_route = |ticker: &str| {
+            rules
+                .iter()
+                .find(|rule| rule.is_match(ticker))
+                .map(|rule| rule.providers.clone())
+        };
+        let crypto = Some(vec![
+            String::from("binance"),
+            String::from("coinbase"),
+            String::from("yahoo"),
+        ]);
+        assert_eq!(to_route("BTC-USD"), crypto);
+        assert_eq!(to_route("ethusdt"), crypto);
+        assert_eq!(to_route("SOL-EUR"), crypto);
+        assert_eq!(to_route("AAPL"), None);
+        assert_eq!(to_route("BRK-B"), None);
+    }
+
+    #[test]
+    fn test_short_color() {
+        let choice = glossary::ChoiceGlossary::new();
+        assert!(choice.is_choice("-c", &choice.color));
+        assert!(!choice.is_choice("-c", &choice.check));
+        assert!(!choice.is_choice("-c", &choice.columns));
+        assert!(choice.is_choice("--c", &choice.check));
+
+        let args: Vec<String> = vec![String::from("never"), String::from("AAPL")];
+        let mut args_iter = args.iter().peekable();
+        assert_eq!(to_choice_arg("-c", &mut args_iter, &COLORS), "-c=never");
+        assert_eq!(args_iter.next().unwrap(), "AAPL");
+
+        let args: Vec<String> = vec![String::from("AAPL")];
+        let mut args_iter = args.iter().peekable();
+        assert_eq!(to_choice_arg("-c", &mut args_iter, &COLORS), "-c");
+        assert_eq!(args_iter.next().unwrap(), "AAPL");
+    }
+}
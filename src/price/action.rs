@@ -0,0 +1,248 @@
+//! Corporate actions of a ticker
+//!
+//! Splits and dividends change the price of a ticker without
+//! any trading so they are kept next to its bars. Bars before
+//! an action are back-adjusted so prices on both sides of it
+//! compare: a split divides earlier prices by its ratio and
+//! multiplies their volume, and a dividend scales earlier
+//! prices by `1 - amount / close` of the bar before it.
+
+use super::resample::{to_resample, Timeframe};
+use super::{History, OHLC};
+
+/// A split or dividend taking effect at `time`
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Action {
+    /// Cash paid for every share
+    Dividend { time: i64, amount: f32 },
+    /// Every `denominator` shares became `numerator` shares
+    Split {
+        time: i64,
+        numerator: f32,
+        denominator: f32,
+    },
+}
+
+impl Action {
+    /// Get the Unix timestamp (seconds) the action takes effect
+    pub fn get_time(&self) -> i64 {
+        match self {
+            Action::Dividend { time, .. } | Action::Split { time, .. } => *time,
+        }
+    }
+
+    /// Returns the factors of the price and volume of bars
+    /// before the action where `close` is the last close
+    /// before it
+    ///
+    /// Actions which cannot apply such as a dividend worth
+    /// the whole price are `(1.0, 1.0)`
+    pub fn to_factors(&self, close: f32) -> (f32, f32) {
+        match *self {
+            Action::Split {
+                numerator,
+                denominator,
+                ..
+            } if numerator > 0.0 && denominator > 0.0 => {
+                (denominator / numerator, numerator / denominator)
+            }
+            Action::Dividend { amount, .. } if amount > 0.0 && close > amount => {
+                (1.0 - amount / close, 1.0)
+            }
+            _ => (1.0, 1.0),
+        }
+    }
+}
+
+/// Actions bars are adjusted for
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Adjust {
+    /// Prices as the provider sends them, which for some
+    /// such as the chart API are already split-adjusted
+    #[default]
+    Raw,
+    Splits,
+    /// Splits and dividends
+    All,
+}
+
+impl Adjust {
+    /// Returns `true` if bars are adjusted for an action
+    pub fn is_adjusted(self, action: &Action) -> bool {
+        match action {
+            Action::Split { .. } => self != Adjust::Raw,
+            Action::Dividend { .. } => self == Adjust::All,
+        }
+    }
+}
+
+/// Returns the adjustment of a value such as `splits`, `all`
+/// or `raw`
+///
+/// An empty value is `splits`
+pub fn to_adjust(value: &str) -> Result<Adjust, String> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "splits" => Ok(Adjust::Splits),
+        "all" | "dividends" => Ok(Adjust::All),
+        "raw" | "none" => Ok(Adjust::Raw),
+        value => Err(format!(
+            "invalid adjustment '{}'; possibilities: 'splits' 'all' 'raw'",
+            value
+        )),
+    }
+}
+
+/// Returns bars ordered from oldest to newest back-adjusted
+/// for actions in any order
+///
+/// A bar is adjusted for every action after it opened so a
+/// bar spanning an action is adjusted as a whole
+pub fn to_adjusted(bars: &[OHLC], actions: &[Action], adjust: Adjust) -> Vec<OHLC> {
+    let mut actions: Vec<&Action> = actions
+        .iter()
+        .filter(|action| adjust.is_adjusted(action))
+        .collect();
+    actions.sort_by_key(|action| std::cmp::Reverse(action.get_time()));
+    let mut actions = actions.into_iter().peekable();
+
+    let (mut price, mut volume) = (1.0f32, 1.0f32);
+    let mut adjusted = bars.to_vec();
+    for bar in adjusted.iter_mut().rev() {
+        // Dividends compare to the close as it traded
+        while let Some(action) = actions.next_if(|action| action.get_time() > bar.time) {
+            let (price_factor, volume_factor) = action.to_factors(bar.close);
+            price *= price_factor;
+            volume *= volume_factor;
+        }
+        bar.open *= price;
+        bar.high *= price;
+        bar.low *= price;
+        bar.close *= price;
+        bar.volume *= volume;
+    }
+    adjusted
+}
+
+impl History {
+    /// Returns the history back-adjusted for actions, see
+    /// `to_adjusted`
+    ///
+    /// Only the hourly, daily and specified bars are adjusted.
+    /// Weekly, monthly and yearly bars would span actions so
+    /// they are resampled again from the finest adjusted bars
+    pub fn to_adjusted(&self, actions: &[Action], adjust: Adjust) -> History {
+        let to_bars = |bars: &Vec<OHLC>| to_adjusted(bars, actions, adjust);
+        let hourly = to_bars(&self.hourly);
+        let daily = to_bars(&self.daily);
+        let timeframe = to_bars(&self.timeframe);
+        let finest = [&daily, &hourly, &timeframe]
+            .into_iter()
+            .find(|bars| !bars.is_empty())
+            .unwrap_or(&timeframe);
+        let to_longer = |bars: &Vec<OHLC>, timeframe: Timeframe| match bars.is_empty() {
+            true => vec![],
+            false => to_resample(finest, timeframe),
+        };
+        let weekly = to_longer(&self.weekly, Timeframe::Weekly);
+        let monthly = to_longer(&self.monthly, Timeframe::Monthly);
+        let yearly = to_longer(&self.yearly, Timeframe::Yearly);
+        History::new(hourly, daily, weekly, monthly, yearly, timeframe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars() -> Vec<OHLC> {
+        (0..4)
+            .map(|i| {
+                let price = if i < 2 { 400.0 } else { 100.0 };
+                OHLC::new(price, price, price, price)
+                    .at(i * 86_400)
+                    .with_volume(10.0)
+            })
+            .collect()
+    }
+
+    fn actions() -> Vec<Action> {
+        vec![
+            Action::Split {
+                time: 2 * 86_400,
+                numerator: 4.0,
+                denominator: 1.0,
+            },
+            Action::Dividend {
+                time: 3 * 86_400,
+                amount: 10.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_to_adjust() {
+        assert_eq!(to_adjust(""), Ok(Adjust::Splits));
+        assert_eq!(to_adjust("ALL"), Ok(Adjust::All));
+        assert_eq!(to_adjust("raw"), Ok(Adjust::Raw));
+        assert!(to_adjust("some").is_err());
+    }
+
+    #[test]
+    fn test_to_adjusted() {
+        assert_eq!(to_adjusted(&bars(), &actions(), Adjust::Raw), bars());
+
+        // The split no longer jumps
+        let adjusted = to_adjusted(&bars(), &actions(), Adjust::Splits);
+        let closes: Vec<f32> = adjusted.iter().map(|bar| bar.close).collect();
+        assert_eq!(closes, [100.0, 100.0, 100.0, 100.0]);
+        assert_eq!(adjusted[0].volume, 40.0);
+        assert_eq!(adjusted[3].volume, 10.0);
+        assert_eq!(adjusted[2].to_percent(), bars()[2].to_percent());
+
+        // The dividend scales every bar before it by 0.9
+        let adjusted = to_adjusted(&bars(), &actions(), Adjust::All);
+        let closes: Vec<f32> = adjusted.iter().map(|bar| bar.close).collect();
+        assert_eq!(closes, [90.0, 90.0, 90.0, 100.0]);
+        assert_eq!(adjusted[0].volume, 40.0);
+
+        // Actions a history cannot see change nothing
+        let later = [Action::Split {
+            time: 0,
+            numerator: 2.0,
+            denominator: 0.0,
+        }];
+        assert_eq!(to_adjusted(&bars(), &later, Adjust::All), bars());
+    }
+
+    #[test]
+    fn test_history_to_adjusted() {
+        let history = History::from_bars(bars());
+        let adjusted = history.to_adjusted(&actions(), Adjust::Splits);
+        assert_eq!(adjusted.timeframe[0].close, 100.0);
+        assert_eq!(adjusted.daily[1].high, 100.0);
+        assert_eq!(adjusted.yearly.len(), history.yearly.len());
+
+        // A split in the middle of a week leaves its bar flat
+        // 1970-01-05 is a Monday and the split is on Wednesday
+        let bars: Vec<OHLC> = (4..9)
+            .map(|i| {
+                let price = if i < 7 { 400.0 } else { 100.0 };
+                OHLC::new(price, price, price, price).at(i * 86_400)
+            })
+            .collect();
+        let split = [Action::Split {
+            time: 7 * 86_400,
+            numerator: 4.0,
+            denominator: 1.0,
+        }];
+        let adjusted = History::from_bars(bars).to_adjusted(&split, Adjust::Splits);
+        let week = adjusted.weekly.last().unwrap();
+        assert_eq!(
+            (week.open, week.high, week.low, week.close),
+            (100.0, 100.0, 100.0, 100.0)
+        );
+        assert_eq!(adjusted.yearly[0].low, 100.0);
+    }
+}
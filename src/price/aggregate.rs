@@ -0,0 +1,287 @@
+//! Bars aggregated from a stream of trades
+//!
+//! Trades from a recorded file or a live stream are built
+//! into bars of a period of time or bars closing after a
+//! number of trades, an amount of volume or an amount of
+//! money traded. Trades may arrive out of order: they are
+//! held for a grace window and released in time order, and
+//! trades older than the released ones are dropped as late.
+
+use super::candle::{self, Candle, Period, Tick};
+use super::OHLC;
+
+/// Seconds trades are held for when none are given
+pub const DEFAULT_GRACE: i64 = 0;
+
+/// Kind of bar built from trades
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bars {
+    /// Bars of a period of time
+    Time(Period),
+    /// Bars of a number of trades
+    Ticks(usize),
+    /// Bars of an amount of volume
+    Volume(f64),
+    /// Bars of an amount of money, price times volume
+    Dollar(f64),
+}
+
+/// Returns the kind of bar of a value such as `5m` or `daily`
+/// for time bars, `100t` for tick bars, `50v` for volume bars
+/// or `1e6$` for dollar bars
+///
+/// An empty value is a minute
+pub fn to_bars(value: &str) -> Result<Bars, String> {
+    let value = value.trim().to_lowercase();
+    let to_error = || format!("invalid bars '{}'", value);
+    if let Some(count) = value.strip_suffix('t') {
+        return match count.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(Bars::Ticks(count)),
+            _ => Err(to_error()),
+        };
+    }
+    let amount = |amount: &str| amount.parse::<f64>().ok().filter(|amount| *amount > 0.0);
+    if let Some(volume) = value.strip_suffix('v') {
+        return amount(volume).map(Bars::Volume).ok_or_else(to_error);
+    }
+    if let Some(dollars) = value.strip_suffix('$') {
+        return amount(dollars).map(Bars::Dollar).ok_or_else(to_error);
+    }
+    candle::to_period(&value)
+        .map(Bars::Time)
+        .map_err(|_| to_error())
+}
+
+/// Builder of bars from trades in any order
+pub struct Aggregator {
+    pub bars: Bars,
+    /// Seconds a trade is held for older trades to arrive
+    pub grace: i64,
+    /// Number of trades dropped as late
+    pub late: usize,
+    /// Trades held ordered by time
+    pending: Vec<Tick>,
+    /// Time of the newest trade seen
+    newest: i64,
+    /// Time of the last trade released
+    released: Option<i64>,
+    candle: Candle,
+    bar: Option<OHLC>,
+    /// Trades or amount in the bar in progress
+    count: usize,
+    amount: f64,
+}
+
+impl Aggregator {
+    pub fn new(bars: Bars) -> Self {
+        let period = match bars {
+            Bars::Time(period) => period,
+            _ => Period::Seconds(60),
+        };
+        Self {
+            bars,
+            grace: DEFAULT_GRACE,
+            late: 0,
+            pending: vec![],
+            newest: i64::MIN,
+            released: None,
+            candle: Candle::new(period),
+            bar: None,
+            count: 0,
+            amount: 0.0,
+        }
+    }
+
+    /// Hold trades for `grace` seconds
+    pub fn with_grace(mut self, grace: i64) -> Self {
+        self.grace = grace.max(0);
+        self
+    }
+
+    /// Get the bar in progress
+    ///
+    /// Trades still held are not part of it
+    pub fn get_bar(&self) -> Option<&OHLC> {
+        match self.bars {
+            Bars::Time(_) => self.candle.get_bar(),
+            _ => self.bar.as_ref(),
+        }
+    }
+
+    /// Add a trade and get every bar it closed
+    ///
+    /// A trade older than the trades already released is
+    /// dropped and counted in `late`
+    pub fn push(&mut self, tick: Tick) -> Vec<OHLC> {
+        if self.released.is_some_and(|released| tick.time < released) {
+            self.late += 1;
+            return vec![];
+        }
+        self.newest = self.newest.max(tick.time);
+        let index = self.pending.partition_point(|held| held.time <= tick.time);
+        self.pending.insert(index, tick);
+
+        let ready = self
+            .pending
+            .partition_point(|held| held.time <= self.newest - self.grace);
+        let ticks: Vec<Tick> = self.pending.drain(..ready).collect();
+        ticks
+            .into_iter()
+            .filter_map(|tick| self.release(tick))
+            .collect()
+    }
+
+    /// Release every trade held and close the bar in progress
+    pub fn finish(&mut self) -> Vec<OHLC> {
+        let ticks: Vec<Tick> = self.pending.drain(..).collect();
+        let mut closed: Vec<OHLC> = ticks
+            .into_iter()
+            .filter_map(|tick| self.release(tick))
+            .collect();
+        let last = match self.bars {
+            Bars::Time(_) => self.candle.finish(),
+            _ => self.take_bar(),
+        };
+        closed.extend(last);
+        closed
+    }
+
+    /// Add a released trade to the bar in progress and
+    /// return the bar it closed
+    fn release(&mut self, tick: Tick) -> Option<OHLC> {
+        self.released = Some(tick.time);
+        if let Bars::Time(_) = self.bars {
+            return self.candle.push(tick);
+        }
+        match self.bar.as_mut() {
+            Some(bar) => {
+                bar.high = bar.high.max(tick.price);
+                bar.low = bar.low.min(tick.price);
+                bar.close = tick.price;
+                bar.volume += tick.volume;
+            }
+            None => {
+                self.bar = Some(
+                    OHLC::new(tick.price, tick.price, tick.price, tick.price)
+                        .at(tick.time)
+                        .with_volume(tick.volume),
+                )
+            }
+        }
+        self.count += 1;
+        self.amount += match self.bars {
+            Bars::Dollar(_) => tick.price as f64 * tick.volume as f64,
+            _ => tick.volume as f64,
+        };
+        let is_full = match self.bars {
+            Bars::Ticks(count) => self.count >= count,
+            Bars::Volume(amount) | Bars::Dollar(amount) => self.amount >= amount,
+            Bars::Time(_) => false,
+        };
+        match is_full {
+            true => self.take_bar(),
+            false => None,
+        }
+    }
+
+    /// Take the bar in progress to start the next one
+    fn take_bar(&mut self) -> Option<OHLC> {
+        self.count = 0;
+        self.amount = 0.0;
+        self.bar.take()
+    }
+}
+
+/// Returns the bars of recorded trades with the number of
+/// trades dropped as late
+pub fn to_aggregate(ticks: &[Tick], bars: Bars, grace: i64) -> (Vec<OHLC>, usize) {
+    let mut aggregator = Aggregator::new(bars).with_grace(grace);
+    let mut closed: Vec<OHLC> = ticks
+        .iter()
+        .flat_map(|tick| aggregator.push(*tick))
+        .collect();
+    closed.extend(aggregator.finish());
+    (closed, aggregator.late)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks() -> Vec<Tick> {
+        vec![
+            Tick::new(0, 10.0, 1.0),
+            Tick::new(30, 12.0, 2.0),
+            Tick::new(20, 9.0, 1.0),
+            Tick::new(65, 11.0, 3.0),
+            Tick::new(50, 10.5, 1.0),
+            Tick::new(130, 13.0, 1.0),
+        ]
+    }
+
+    #[test]
+    fn test_to_bars() {
+        assert_eq!(to_bars(""), Ok(Bars::Time(Period::Seconds(60))));
+        assert_eq!(to_bars("5m"), Ok(Bars::Time(Period::Seconds(300))));
+        assert_eq!(to_bars("100T"), Ok(Bars::Ticks(100)));
+        assert_eq!(to_bars("2.5v"), Ok(Bars::Volume(2.5)));
+        assert_eq!(to_bars("1e6$"), Ok(Bars::Dollar(1_000_000.0)));
+        assert_eq!(to_bars("0t"), Err(String::from("invalid bars '0t'")));
+        assert!(to_bars("-1$").is_err());
+        assert!(to_bars("soon").is_err());
+    }
+
+    #[test]
+    fn test_push_grace() {
+        // Without grace out of order trades are late
+        let (bars, late) = to_aggregate(&ticks(), Bars::Time(Period::Seconds(60)), 0);
+        assert_eq!(late, 2);
+        assert_eq!(
+            bars,
+            vec![
+                OHLC::new(10.0, 12.0, 10.0, 12.0).with_volume(3.0),
+                OHLC::new(11.0, 11.0, 11.0, 11.0).at(60).with_volume(3.0),
+                OHLC::new(13.0, 13.0, 13.0, 13.0).at(120).with_volume(1.0),
+            ]
+        );
+
+        // A grace window puts them back in order
+        let (bars, late) = to_aggregate(&ticks(), Bars::Time(Period::Seconds(60)), 20);
+        assert_eq!(late, 0);
+        assert_eq!(bars[0], OHLC::new(10.0, 12.0, 9.0, 10.5).with_volume(5.0));
+
+        // Held trades are not in the bar in progress yet
+        let mut aggregator = Aggregator::new(Bars::Ticks(10)).with_grace(20);
+        aggregator.push(Tick::new(0, 10.0, 1.0));
+        assert_eq!(aggregator.get_bar(), None);
+        aggregator.push(Tick::new(25, 11.0, 1.0));
+        assert_eq!(aggregator.get_bar().unwrap().close, 10.0);
+    }
+
+    #[test]
+    fn test_push_bars() {
+        let (bars, _) = to_aggregate(&ticks(), Bars::Ticks(2), 60);
+        assert_eq!(
+            bars,
+            vec![
+                OHLC::new(10.0, 10.0, 9.0, 9.0).with_volume(2.0),
+                OHLC::new(12.0, 12.0, 10.5, 10.5).at(30).with_volume(3.0),
+                OHLC::new(11.0, 13.0, 11.0, 13.0).at(65).with_volume(4.0),
+            ]
+        );
+
+        let (bars, _) = to_aggregate(&ticks(), Bars::Volume(3.0), 60);
+        assert_eq!(
+            bars.iter().map(|bar| bar.volume).collect::<Vec<f32>>(),
+            [4.0, 4.0, 1.0]
+        );
+
+        // Dollar bars close once 40 of price times volume trade
+        let (bars, _) = to_aggregate(&ticks(), Bars::Dollar(40.0), 60);
+        assert_eq!(
+            bars.iter().map(|bar| bar.time).collect::<Vec<i64>>(),
+            [0, 50, 130]
+        );
+        assert_eq!(bars[0].close, 12.0);
+    }
+}
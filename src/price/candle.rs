@@ -0,0 +1,157 @@
+//! Bars built from trades as they happen
+//!
+//! Every trade updates the high, low, close and volume of the
+//! bar in progress until one lands in the next period, which
+//! closes the bar and opens a new one at the trade's price.
+
+use super::resample::Timeframe;
+use super::OHLC;
+
+/// A single trade
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct Tick {
+    /// Unix timestamp (seconds) of the trade
+    pub time: i64,
+    pub price: f32,
+    pub volume: f32,
+}
+
+impl Tick {
+    pub fn new(time: i64, price: f32, volume: f32) -> Self {
+        Self {
+            time,
+            price,
+            volume,
+        }
+    }
+}
+
+/// Length of a bar built from trades
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Period {
+    /// Periods of seconds aligned to the Unix epoch
+    Seconds(i64),
+    Timeframe(Timeframe),
+}
+
+impl Period {
+    /// Returns the Unix timestamp of the start of the
+    /// period containing `time`
+    pub fn to_start(&self, time: i64) -> i64 {
+        match self {
+            Period::Seconds(seconds) => time - time.rem_euclid(*seconds),
+            Period::Timeframe(timeframe) => timeframe.to_start(time),
+        }
+    }
+}
+
+/// Returns the period of a value such as `30s`, `5m`, `1h`
+/// or a timeframe such as `daily`
+///
+/// An empty value is a minute
+pub fn to_period(value: &str) -> Result<Period, String> {
+    let value = value.trim().to_lowercase();
+    if value.is_empty() {
+        return Ok(Period::Seconds(60));
+    }
+    if let Some(timeframe) = Timeframe::ALL
+        .iter()
+        .find(|timeframe| timeframe.to_name() == value)
+    {
+        return Ok(Period::Timeframe(*timeframe));
+    }
+    let units = [("s", 1), ("m", 60), ("h", 3_600), ("d", 86_400)];
+    let count = units
+        .iter()
+        .find_map(|(suffix, seconds)| Some((value.strip_suffix(suffix)?, *seconds)));
+    match count.map(|(count, seconds)| (count.parse::<i64>(), seconds)) {
+        Some((Ok(count), seconds)) if count > 0 => Ok(Period::Seconds(count * seconds)),
+        _ => Err(format!("invalid period '{}'", value)),
+    }
+}
+
+/// Builder of bars from trades ordered by time
+pub struct Candle {
+    pub period: Period,
+    bar: Option<OHLC>,
+}
+
+impl Candle {
+    pub fn new(period: Period) -> Self {
+        Self { period, bar: None }
+    }
+
+    /// Get the bar in progress
+    pub fn get_bar(&self) -> Option<&OHLC> {
+        self.bar.as_ref()
+    }
+
+    /// Close the bar in progress
+    pub fn finish(&mut self) -> Option<OHLC> {
+        self.bar.take()
+    }
+
+    /// Add a trade to the bar in progress
+    ///
+    /// Returns the closed bar when the trade opens a new one.
+    /// Trades older than the bar in progress are skipped
+    pub fn push(&mut self, tick: Tick) -> Option<OHLC> {
+        let start = self.period.to_start(tick.time);
+        let opened = OHLC::new(tick.price, tick.price, tick.price, tick.price)
+            .at(start)
+            .with_volume(tick.volume);
+        match self.bar.as_mut() {
+            Some(bar) if start == bar.time => {
+                bar.high = bar.high.max(tick.price);
+                bar.low = bar.low.min(tick.price);
+                bar.close = tick.price;
+                bar.volume += tick.volume;
+                None
+            }
+            Some(bar) if start < bar.time => None,
+            _ => self.bar.replace(opened),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_period() {
+        assert_eq!(to_period(""), Ok(Period::Seconds(60)));
+        assert_eq!(to_period("30s"), Ok(Period::Seconds(30)));
+        assert_eq!(to_period("5M"), Ok(Period::Seconds(300)));
+        assert_eq!(to_period("daily"), Ok(Period::Timeframe(Timeframe::Daily)));
+        assert_eq!(to_period("0m"), Err(String::from("invalid period '0m'")));
+        assert!(to_period("soon").is_err());
+        assert_eq!(Period::Seconds(60).to_start(119), 60);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut candle = Candle::new(Period::Seconds(60));
+        assert_eq!(candle.push(Tick::new(61, 10.0, 1.0)), None);
+        assert_eq!(candle.push(Tick::new(70, 12.0, 2.0)), None);
+        assert_eq!(candle.push(Tick::new(80, 9.0, 1.0)), None);
+        assert_eq!(candle.push(Tick::new(90, 11.0, 1.0)), None);
+        assert_eq!(
+            candle.get_bar(),
+            Some(&OHLC::new(10.0, 12.0, 9.0, 11.0).at(60).with_volume(5.0))
+        );
+
+        // A trade of the next minute closes the bar
+        let closed = candle.push(Tick::new(125, 11.5, 1.0)).unwrap();
+        assert_eq!(closed.close, 11.0);
+        assert_eq!(
+            candle.get_bar(),
+            Some(&OHLC::new(11.5, 11.5, 11.5, 11.5).at(120).with_volume(1.0))
+        );
+        // Late trades of a closed bar are skipped
+        assert_eq!(candle.push(Tick::new(100, 1.0, 1.0)), None);
+        assert_eq!(candle.get_bar().unwrap().low, 11.5);
+        assert_eq!(candle.finish().unwrap().time, 120);
+        assert_eq!(candle.get_bar(), None);
+    }
+}
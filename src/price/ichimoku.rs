@@ -0,0 +1,284 @@
+//! Ichimoku Kinko Hyo cloud
+//!
+//! Lines are aligned to the index of the bar they are
+//! plotted on, so Senkou spans run `displacement` bars
+//! past the latest bar and the Chikou span stops
+//! `displacement` bars before it.
+
+use super::{write_direction, OHLC};
+use std::io::Write;
+
+/// Ichimoku lines of a series of bars
+///
+/// A value is `None` when there are not enough bars
+/// to compute it at that index
+#[derive(Default)]
+pub struct Ichimoku {
+    pub tenkan: Vec<Option<f32>>,
+    pub kijun: Vec<Option<f32>>,
+    pub senkou_a: Vec<Option<f32>>,
+    pub senkou_b: Vec<Option<f32>>,
+    pub chikou: Vec<Option<f32>>,
+    pub displacement: usize,
+}
+
+/// Returns the midpoint of the highest high and lowest low
+/// of the `period` bars ending at every index
+fn to_midpoints(bars: &[OHLC], period: usize) -> Vec<Option<f32>> {
+    (0..bars.len())
+        .map(|i| {
+            if period == 0 || i + 1 < period {
+                return None;
+            }
+            let window = &bars[i + 1 - period..=i];
+            let high = window.iter().map(|bar| bar.high).fold(f32::MIN, f32::max);
+            let low = window.iter().map(|bar| bar.low).fold(f32::MAX, f32::min);
+            Some((high + low) / 2.0)
+        })
+        .collect()
+}
+
+/// Returns the Ichimoku lines
+///
+/// Default `tenkan` period is: `9`
+///
+/// Default `kijun` period is: `26` which is also the displacement
+///
+/// Default `senkou` period is: `52`
+pub fn to_ichimoku(
+    bars: &[OHLC],
+    tenkan: Option<usize>,
+    kijun: Option<usize>,
+    senkou: Option<usize>,
+) -> Ichimoku {
+    let tenkan: usize = tenkan.unwrap_or(9);
+    let kijun: usize = kijun.unwrap_or(26);
+    let senkou: usize = senkou.unwrap_or(52);
+
+    let tenkan_line = to_midpoints(bars, tenkan);
+    let kijun_line = to_midpoints(bars, kijun);
+
+    // Senkou spans are displaced forward into the future
+    let mut senkou_a = vec![None; kijun];
+    senkou_a.extend(
+        tenkan_line
+            .iter()
+            .zip(&kijun_line)
+            .map(|(tenkan, kijun)| Some((tenkan.as_ref()? + kijun.as_ref()?) / 2.0)),
+    );
+    let mut senkou_b = vec![None; kijun];
+    senkou_b.extend(to_midpoints(bars, senkou));
+
+    // Chikou span is displaced back into the past
+    let chikou = (0..bars.len())
+        .map(|i| bars.get(i + kijun).map(|bar| bar.close))
+        .collect();
+
+    Ichimoku {
+        tenkan: tenkan_line,
+        kijun: kijun_line,
+        senkou_a,
+        senkou_b,
+        chikou,
+        displacement: kijun,
+    }
+}
+
+impl Ichimoku {
+    /// Returns the top and bottom of the cloud at an index
+    fn to_cloud(&self, index: usize) -> Option<(f32, f32)> {
+        let a = (*self.senkou_a.get(index)?)?;
+        let b = (*self.senkou_b.get(index)?)?;
+        Some((a.max(b), a.min(b)))
+    }
+
+    /// Returns where the latest close sits against the cloud
+    ///
+    /// `'U'` = Above the cloud
+    ///
+    /// `'D'` = Below the cloud
+    ///
+    /// `'N'` = Inside the cloud
+    ///
+    /// `None` when there are not enough bars for a cloud
+    pub fn to_cloud_position(&self, bars: &[OHLC]) -> Option<char> {
+        let last = bars.last()?;
+        match self.to_cloud(bars.len() - 1)? {
+            (top, _) if last.close > top => Some('U'),
+            (_, bottom) if last.close < bottom => Some('D'),
+            _ => Some('N'),
+        }
+    }
+
+    /// Returns the direction of the latest Tenkan/Kijun cross
+    ///
+    /// `'U'` = Tenkan crossed above Kijun
+    ///
+    /// `'D'` = Tenkan crossed below Kijun
+    ///
+    /// `'N'` = No cross found
+    pub fn to_tk_cross(&self) -> char {
+        let lines: Vec<(f32, f32)> = self
+            .tenkan
+            .iter()
+            .zip(&self.kijun)
+            .filter_map(|(tenkan, kijun)| Some(((*tenkan)?, (*kijun)?)))
+            .collect();
+        for pair in lines.windows(2).rev() {
+            let (prev, curr) = (pair[0].0 - pair[0].1, pair[1].0 - pair[1].1);
+            if prev <= 0.0 && curr > 0.0 {
+                return 'U';
+            } else if prev >= 0.0 && curr < 0.0 {
+                return 'D';
+            }
+        }
+        'N'
+    }
+
+    /// Returns the color of the furthest cloud ahead
+    ///
+    /// `'U'` = Bullish cloud (Senkou A above Senkou B)
+    ///
+    /// `'D'` = Bearish cloud (Senkou A below Senkou B)
+    ///
+    /// `'N'` = Flat cloud or not enough bars
+    pub fn to_cloud_color(&self) -> char {
+        let last = self.senkou_a.len().min(self.senkou_b.len());
+        match (
+            self.senkou_a.get(last.wrapping_sub(1)).copied().flatten(),
+            self.senkou_b.get(last.wrapping_sub(1)).copied().flatten(),
+        ) {
+            (Some(a), Some(b)) if a > b => 'U',
+            (Some(a), Some(b)) if a < b => 'D',
+            _ => 'N',
+        }
+    }
+
+    /// Write the Ichimoku verdict of the latest bar
+    ///
+    /// To prevent losing precision specfiy
+    /// the level of `precision` after decimal needed
+    pub fn show_ichimoku<T: Write>(&self, bars: &[OHLC], mut output: T, precision: usize) {
+        let _ = write!(output, "Ichimoku: [Cloud] - ");
+        match self.to_cloud_position(bars) {
+            Some(position) => {
+                let _ = write!(
+                    output,
+                    "{} ",
+                    match position {
+                        'U' => "above",
+                        'D' => "below",
+                        _ => "inside",
+                    }
+                );
+                write_direction(&mut output, position);
+            }
+            None => {
+                let _ = write!(output, "n/a");
+            }
+        }
+        let _ = write!(output, "  [TK Cross] - ");
+        write_direction(&mut output, self.to_tk_cross());
+        let _ = write!(output, "  [Cloud Ahead] - ");
+        write_direction(&mut output, self.to_cloud_color());
+        if let (Some(Some(tenkan)), Some(Some(kijun))) = (self.tenkan.last(), self.kijun.last()) {
+            let _ = write!(output, "  [Tenkan] - {1:.0$}", precision, tenkan);
+            let _ = write!(output, "  [Kijun] - {1:.0$}", precision, kijun);
+        }
+        let _ = writeln!(output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rising(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let base = 10.0 + i as f32;
+                OHLC::new(base, base + 1.5, base - 0.5, base + 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_ichimoku() {
+        let bars = rising(60);
+        let ichimoku = to_ichimoku(&bars, None, None, None);
+        assert_eq!(ichimoku.tenkan.len(), 60);
+        assert_eq!(ichimoku.senkou_a.len(), 86);
+        assert_eq!(ichimoku.tenkan[7], None);
+        // Highest high 19.5 and lowest low 9.5 of the first 9 bars
+        assert_eq!(ichimoku.tenkan[8], Some(14.5));
+        assert_eq!(ichimoku.kijun[25], Some(23.0));
+        assert_eq!(ichimoku.senkou_a[51], Some((31.5 + 23.0) / 2.0));
+        assert_eq!(ichimoku.senkou_b[26 + 51], Some(36.0));
+        assert_eq!(ichimoku.chikou[0], Some(bars[26].close));
+        assert_eq!(ichimoku.chikou[34], None);
+    }
+
+    #[test]
+    fn test_to_cloud_position() {
+        let bars = rising(80);
+        let ichimoku = to_ichimoku(&bars, None, None, None);
+        assert_eq!(ichimoku.to_cloud_position(&bars), Some('U'));
+
+        let falling: Vec<OHLC> = bars
+            .iter()
+            .rev()
+            .map(|bar| OHLC::new(bar.close, bar.high, bar.low, bar.open))
+            .collect();
+        let ichimoku = to_ichimoku(&falling, None, None, None);
+        assert_eq!(ichimoku.to_cloud_position(&falling), Some('D'));
+
+        // A close back between Senkou A and B
+        let (top, bottom) = to_ichimoku(&bars, None, None, None)
+            .to_cloud(bars.len())
+            .unwrap();
+        let cloud = (top + bottom) / 2.0;
+        let mut inside = bars.clone();
+        inside.push(OHLC::new(cloud, cloud + 0.5, cloud - 0.5, cloud));
+        let ichimoku = to_ichimoku(&inside, None, None, None);
+        assert_eq!(ichimoku.to_cloud_position(&inside), Some('N'));
+
+        let ichimoku = to_ichimoku(&bars[..10], None, None, None);
+        assert_eq!(ichimoku.to_cloud_position(&bars[..10]), None);
+    }
+
+    #[test]
+    fn test_to_tk_cross() {
+        let mut bars: Vec<OHLC> = rising(40).into_iter().rev().collect();
+        assert_eq!(to_ichimoku(&bars, None, None, None).to_tk_cross(), 'N');
+
+        bars.extend((0..10).map(|i| {
+            let base = 10.0 + 2.0 * i as f32;
+            OHLC::new(base, base + 2.5, base - 0.5, base + 2.0)
+        }));
+        assert_eq!(to_ichimoku(&bars, None, None, None).to_tk_cross(), 'U');
+    }
+
+    #[test]
+    fn test_to_cloud_color() {
+        assert_eq!(
+            to_ichimoku(&rising(60), None, None, None).to_cloud_color(),
+            'U'
+        );
+        assert_eq!(
+            to_ichimoku(&rising(20), None, None, None).to_cloud_color(),
+            'N'
+        );
+    }
+
+    #[test]
+    fn test_show_ichimoku() {
+        let bars = rising(3);
+        let ichimoku = to_ichimoku(&bars, None, None, None);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        ichimoku.show_ichimoku(&bars, &mut output, 2);
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "Ichimoku: [Cloud] - n/a  [TK Cross] - \x1b[38;2;255;255;0m⬌ Side\x1b[0m  [Cloud Ahead] - \x1b[38;2;255;255;0m⬌ Side\x1b[0m\n"
+        );
+    }
+}
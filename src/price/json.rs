@@ -0,0 +1,136 @@
+//! Prices as versioned JSON
+//!
+//! Needs the `serde` feature. A document holds the history
+//! of a ticker along with the version of its schema so other
+//! tools can refuse documents they do not understand:
+//!
+//! ```text
+//! {
+//!   "schema": 1,
+//!   "ticker": "AAPL",
+//!   "history": {
+//!     "hourly": [OHLC], "daily": [OHLC], "weekly": [OHLC],
+//!     "monthly": [OHLC], "yearly": [OHLC], "timeframe": [OHLC]
+//!   }
+//! }
+//! ```
+//!
+//! Every `OHLC` is an object of `open`, `high`, `low` and
+//! `close` prices, the `time` it opened as a Unix timestamp
+//! in seconds and its `volume`. Missing timeframes are empty
+//! and a missing `time` or `volume` is `0`.
+//!
+//! Adding fields keeps the version, renaming or removing
+//! fields bumps it. The full JSON Schema is `SCHEMA`.
+
+use super::History;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Version of the schema written by `to_json`
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema of the current version
+pub const SCHEMA: &str = include_str!("../../schema/history-v1.schema.json");
+
+/// History of a ticker as written to JSON
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Document {
+    pub schema: u32,
+    pub ticker: String,
+    pub history: History,
+}
+
+impl Document {
+    /// Create a document of the current schema version
+    pub fn new(ticker: &str, history: History) -> Self {
+        Self {
+            schema: SCHEMA_VERSION,
+            ticker: ticker.to_string(),
+            history,
+        }
+    }
+}
+
+/// Returns the history of a ticker as JSON
+pub fn to_json(ticker: &str, history: &History) -> Result<String> {
+    let document = Document::new(ticker, history.clone());
+    serde_json::to_string_pretty(&document).map_err(|err| Error::Invalid(err.to_string()))
+}
+
+/// Parse a document of JSON
+///
+/// Documents of another schema version are `Error::Invalid`
+pub fn from_json(text: &str) -> Result<Document> {
+    let to_error = |err: serde_json::Error| Error::Parse {
+        path: None,
+        line: err.line(),
+        message: err.to_string(),
+    };
+    let value: serde_json::Value = serde_json::from_str(text).map_err(to_error)?;
+    match value.get("schema").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == SCHEMA_VERSION as u64 => {}
+        Some(version) => {
+            return Err(Error::Invalid(format!(
+                "unsupported schema {}; expected {}",
+                version, SCHEMA_VERSION
+            )))
+        }
+        None => return Err(Error::Invalid(String::from("missing schema version"))),
+    }
+    serde_json::from_value(value).map_err(to_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::OHLC;
+
+    #[test]
+    fn test_to_json() {
+        let bars = vec![
+            OHLC::new(1.0, 2.0, 0.5, 1.5).at(1_710_374_400),
+            OHLC::new(1.5, 3.0, 1.0, 2.5)
+                .at(1_710_460_800)
+                .with_volume(10.0),
+        ];
+        let history = History::from_bars(bars);
+        let text = to_json("AAPL", &history).unwrap();
+        assert!(text.contains("\"schema\": 1"));
+
+        let document = from_json(&text).unwrap();
+        assert_eq!(document.ticker, "AAPL");
+        assert_eq!(document.history, history);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let document = from_json(
+            r#"{"schema": 1, "ticker": "BTC-USD",
+                "history": {"timeframe": [{"open": 1, "high": 2, "low": 0.5, "close": 1.5}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            document.history.timeframe,
+            vec![OHLC::new(1.0, 2.0, 0.5, 1.5)]
+        );
+        assert!(document.history.daily.is_empty());
+
+        assert_eq!(
+            from_json(r#"{"schema": 2, "ticker": "AAPL", "history": {}}"#),
+            Err(Error::Invalid(String::from(
+                "unsupported schema 2; expected 1"
+            )))
+        );
+        assert!(matches!(
+            from_json("{\"schema\": 1,\n\"ticker\": 5}"),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(schema["properties"]["schema"]["const"], SCHEMA_VERSION);
+    }
+}
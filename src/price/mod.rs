@@ -5,22 +5,52 @@
 //! All data from fininacial instruments will pass through
 //! this lib such as Data -> Price(this lib) -> Text/Indicators
 
-use crate::utils::color::{GREEN, RED, RESET, YELLOW};
+pub mod action;
+pub mod aggregate;
+pub mod candle;
+pub mod ichimoku;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod pattern;
+pub mod pivot;
+pub mod resample;
+pub mod swing;
+pub mod transform;
+pub mod trend;
+pub mod validate;
+
+use crate::utils::color::reset;
+use crate::utils::table::Table;
+use crate::utils::theme;
 use std::io::Write;
 
 /// Open-High-Low-Close (OHLC)
-#[derive(Default)]
+///
+/// `time` is the Unix timestamp (seconds) of
+/// when the bar opened, `0` when unknown
+///
+/// `volume` is the amount traded during the bar,
+/// `0` when unknown
+#[derive(Default, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OHLC {
     pub open: f32,
     pub high: f32,
     pub low: f32,
     pub close: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time: i64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub volume: f32,
 }
 
 /// Popular trends in OHLC
 ///
 /// `timeframe` field allows to store
 /// specifed timeframe
+#[derive(Default, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct History {
     pub hourly: Vec<OHLC>,
     pub daily: Vec<OHLC>,
@@ -39,25 +69,144 @@ impl History {
         yearly: Vec<OHLC>,
         timeframe: Vec<OHLC>,
     ) -> Self {
-        return Self {
+        Self {
             hourly,
             daily,
             weekly,
             monthly,
             yearly,
             timeframe,
+        }
+    }
+
+    /// Build the history of bars ordered from oldest to newest
+    ///
+    /// The bars are kept as the specified timeframe and
+    /// resampled into every timeframe at least as long
+    /// as the time between them
+    pub fn from_bars(bars: Vec<OHLC>) -> Self {
+        let interval = resample::to_interval(&bars).unwrap_or_default();
+        let to_timeframe = |timeframe: resample::Timeframe| {
+            if interval > timeframe.to_seconds() {
+                vec![]
+            } else {
+                resample::to_resample(&bars, timeframe)
+            }
         };
+        Self {
+            hourly: to_timeframe(resample::Timeframe::Hourly),
+            daily: to_timeframe(resample::Timeframe::Daily),
+            weekly: to_timeframe(resample::Timeframe::Weekly),
+            monthly: to_timeframe(resample::Timeframe::Monthly),
+            yearly: to_timeframe(resample::Timeframe::Yearly),
+            timeframe: bars,
+        }
+    }
+
+    /// Get the bars of a timeframe
+    pub fn get_timeframe(&self, timeframe: resample::Timeframe) -> &Vec<OHLC> {
+        match timeframe {
+            resample::Timeframe::Hourly => &self.hourly,
+            resample::Timeframe::Daily => &self.daily,
+            resample::Timeframe::Weekly => &self.weekly,
+            resample::Timeframe::Monthly => &self.monthly,
+            resample::Timeframe::Yearly => &self.yearly,
+        }
+    }
+
+    /// Print the history of the specified timeframe
+    ///
+    /// The table of bars is followed by the trend, Ichimoku,
+    /// candlestick patterns of the last 10 bars, pivots,
+    /// Fibonacci and key levels found in the timeframe
+    ///
+    /// Default `table` is: every column in time order
+    ///
+    /// To prevent losing precision specfiy
+    /// the level of `precision` after decimal needed
+    pub fn show_history<T: Write>(&self, mut output: T, table: Option<&Table>, precision: usize) {
+        match table {
+            Some(table) => table.show_table(&self.timeframe, &mut output),
+            None => Table::new(precision).show_table(&self.timeframe, &mut output),
+        }
+        trend::show_trend(&self.timeframe, &mut output, 14, precision);
+        ichimoku::to_ichimoku(&self.timeframe, None, None, None).show_ichimoku(
+            &self.timeframe,
+            &mut output,
+            precision,
+        );
+        pattern::show_patterns(&self.timeframe, &mut output, None, 10);
+        self.show_pivots(&mut output, pivot::Method::Classic, precision);
+        let swings = swing::to_fractals(&self.timeframe, 2);
+        if let (Some((high, low, direction)), Some(last)) =
+            (pivot::to_swing(&swings), self.timeframe.last())
+        {
+            let _ = writeln!(output, "Fibonacci:");
+            pivot::show_levels(
+                &pivot::to_fibonacci(high, low, direction),
+                last.close,
+                &mut output,
+                precision,
+            );
+        }
+        swing::show_zones(
+            &swing::to_zones(&self.timeframe, &swings, None),
+            2,
+            &mut output,
+            precision,
+        );
+    }
+
+    /// Print the pivot levels of the daily, weekly and
+    /// monthly timeframes against their latest close
+    ///
+    /// The latest bar is the period in progress so pivots
+    /// are taken from the bar before it
+    ///
+    /// To prevent losing precision specfiy
+    /// the level of `precision` after decimal needed
+    pub fn show_pivots<T: Write>(&self, mut output: T, method: pivot::Method, precision: usize) {
+        for (name, bars) in [
+            ("Daily", &self.daily),
+            ("Weekly", &self.weekly),
+            ("Monthly", &self.monthly),
+        ] {
+            if bars.len() < 2 {
+                continue;
+            }
+            let _ = writeln!(output, "{} Pivots ({}):", name, method.to_name());
+            pivot::show_levels(
+                &pivot::to_pivots(&bars[bars.len() - 2], method),
+                bars[bars.len() - 1].close,
+                &mut output,
+                precision,
+            );
+        }
     }
 }
 
 impl OHLC {
     pub fn new(open: f32, high: f32, low: f32, close: f32) -> Self {
-        return Self {
+        Self {
             open,
             high,
             low,
             close,
-        };
+            time: 0,
+            volume: 0.0,
+        }
+    }
+
+    /// Set the Unix timestamp (seconds) of when the bar opened
+    pub fn at(mut self, time: i64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Set the volume traded during the bar
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
     }
 
     /// Print the OHLC
@@ -66,27 +215,30 @@ impl OHLC {
     /// the level of `precision` after decimal needed
     ///
     pub fn show_ohlc<T: Write>(&self, mut output: T, precision: usize) {
-        let _ = write!(output, " [Open] - {1:.0$}", precision, self.open);
-        let _ = write!(output, "  [High] - {1:.0$}", precision, self.high);
-        let _ = write!(output, "  [Low] - {1:.0$}", precision, self.low);
-        let _ = writeln!(output, "  [Close] - {1:.0$}", precision, self.close);
-        let sep = "-".repeat(61);
-        let _ = writeln!(output, "{}", sep);
+        let line = format!(
+            " [Open] - {1:.0$}  [High] - {2:.0$}  [Low] - {3:.0$}  [Close] - {4:.0$}",
+            precision, self.open, self.high, self.low, self.close
+        );
+        // Separator as long as the line it underlines
+        let sep = "-".repeat(line.chars().count());
+        let _ = writeln!(output, "{}\n{}", line, sep);
     }
 
     /// Write the direction and strength of a candle
     pub fn show_direction<T: Write>(&self, mut output: T) {
         let _ = write!(output, "Direction: ");
-        let _ = match self.to_direction() {
-            'U' => writeln!(output, "{}⬆ Up{}", GREEN, RESET),
-            'D' => writeln!(output, "{}⬇ Down{}", RED, RESET),
-            _ => writeln!(output, "{}⬌ Side{}", YELLOW, RESET),
-        };
+        write_direction(&mut output, self.to_direction());
+        let _ = writeln!(output);
     }
 
     /// Write the strength of a candle
     pub fn show_strong<T: Write>(&self, mut output: T) {
-        let strong_indicator = if self.is_strong(None) { '✅' } else { '❌' };
+        let symbols = theme::get_symbols();
+        let strong_indicator = if self.is_strong(None) {
+            symbols.strong
+        } else {
+            symbols.weak
+        };
         let _ = writeln!(output, "Strong: {}", strong_indicator);
     }
 
@@ -95,13 +247,10 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_bot_wick(), -0.8)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_percent(), -0.8);
     /// ```
     pub fn to_percent(&self) -> f32 {
         (self.close - self.open) / self.open
@@ -111,13 +260,10 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_point(), -4.0)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_point(), -4.0);
     /// ```
     pub fn to_point(&self) -> f32 {
         self.close - self.open
@@ -128,13 +274,10 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_range(), 12.5)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_range(), 12.5);
     /// ```
     pub fn to_range(&self) -> f32 {
         self.high - self.low
@@ -153,21 +296,18 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_direction(), 'D')
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_direction(), 'D');
     /// ```
     pub fn to_direction(&self) -> char {
         if self.close > self.open {
-            return 'U';
+            'U'
         } else if self.close < self.open {
-            return 'D';
+            'D'
         } else {
-            return 'N';
+            'N'
         }
     }
 
@@ -176,20 +316,17 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_top_wick(), 8.0)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_top_wick(), 8.0);
     /// ```
     pub fn to_top_wick(&self) -> f32 {
         let dir: char = self.to_direction();
         if dir == 'D' {
-            return self.high - self.open;
+            self.high - self.open
         } else {
-            return self.high - self.close;
+            self.high - self.close
         }
     }
 
@@ -198,20 +335,17 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.to_bot_wick(), 0.5)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.to_bot_wick(), 0.5);
     /// ```
     pub fn to_bot_wick(&self) -> f32 {
         let dir: char = self.to_direction();
         if dir == 'D' {
-            return self.close - self.low;
+            self.close - self.low
         } else {
-            return self.open - self.low;
+            self.open - self.low
         }
     }
 
@@ -226,24 +360,21 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.is_weak(), false)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.is_weak(None), false);
     /// ```
     pub fn is_weak(&self, gauge: Option<f32>) -> bool {
-        let gauge: f32 = gauge.or(Some(1.0)).unwrap();
+        let gauge: f32 = gauge.unwrap_or(1.0);
         let body: f32 = self.close - self.open;
         if body == 0.0 {
-            return false;
+            false
         } else if body > 0.0 {
-            return self.to_top_wick() / body > gauge;
+            self.to_top_wick() / body > gauge
         } else {
-            return self.to_bot_wick() / body < -gauge;
-        };
+            self.to_bot_wick() / body < -gauge
+        }
     }
 
     /// Returns `true` if the candle is relatively strong based on
@@ -257,27 +388,48 @@ impl OHLC {
     /// # Examples
     ///
     /// ```
-    /// let bar: OHLC = OHLC {
-    ///     open: 5.0,
-    ///     close: 1.0,
-    ///     low: 0.5,
-    ///     high: 13.0,
-    ///  };
-    /// assert!eq(bar.is_strong(), true)
+    /// use finfo::OHLC;
+    ///
+    /// let bar: OHLC = OHLC::new(5.0, 13.0, 0.5, 1.0);
+    /// assert_eq!(bar.is_strong(None), true);
     /// ```
     pub fn is_strong(&self, gauge: Option<f32>) -> bool {
-        let gauge: f32 = gauge.or(Some(2.0)).unwrap();
+        let gauge: f32 = gauge.unwrap_or(2.0);
         let body: f32 = self.close - self.open;
         if body == 0.0 {
-            return false;
+            false
         } else if body > 0.0 {
-            return body / self.to_top_wick() > gauge;
+            body / self.to_top_wick() > gauge
         } else {
-            return body / self.to_bot_wick() < -gauge;
-        };
+            body / self.to_bot_wick() < -gauge
+        }
     }
 }
 
+/// Returns the color, arrow and label of a direction
+/// in the current theme
+///
+/// Uses the same `'U'`, `'D'` and `'N'` vocabulary
+/// returned by `OHLC::to_direction`
+pub(crate) fn to_style(direction: char) -> (&'static str, &'static str, &'static str) {
+    let label = match direction {
+        'U' => "Up",
+        'D' => "Down",
+        _ => "Side",
+    };
+    (
+        theme::to_color(direction),
+        theme::to_arrow(direction),
+        label,
+    )
+}
+
+/// Write the colored arrow and label of a direction
+pub(crate) fn write_direction<T: Write>(mut output: T, direction: char) {
+    let (color, arrow, label) = to_style(direction);
+    let _ = write!(output, "{}{} {}{}", color, arrow, label, reset());
+}
+
 ////////// OHLC //////////
 #[cfg(test)]
 pub mod tests {
@@ -290,22 +442,25 @@ pub mod tests {
             close: 5.0,
             low: 2.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 1.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         let bar3: OHLC = OHLC {
             open: 5.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
-        assert_eq!(bar1.is_weak(None), true);
-        assert_eq!(bar2.is_weak(Some(1.0)), false);
-        assert_eq!(bar3.is_weak(None), false);
+        assert!(bar1.is_weak(None));
+        assert!(!bar2.is_weak(Some(1.0)));
+        assert!(!bar3.is_weak(None));
     }
 
     #[test]
@@ -315,22 +470,25 @@ pub mod tests {
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 1.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         let bar3: OHLC = OHLC {
             open: 5.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
-        assert_eq!(bar1.is_strong(None), true);
-        assert_eq!(bar2.is_strong(Some(3.0)), true);
-        assert_eq!(bar3.is_strong(None), false);
+        assert!(bar1.is_strong(None));
+        assert!(bar2.is_strong(Some(3.0)));
+        assert!(!bar3.is_strong(None));
     }
 
     #[test]
@@ -340,18 +498,21 @@ pub mod tests {
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 1.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         let bar3: OHLC = OHLC {
             open: 5.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         assert_eq!(bar1.to_direction(), 'D');
         assert_eq!(bar2.to_direction(), 'U');
@@ -364,6 +525,7 @@ pub mod tests {
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         assert_eq!(bar1.to_range(), 12.5);
     }
@@ -375,12 +537,14 @@ pub mod tests {
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 1.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         assert_eq!(bar1.to_point(), -4.0);
         assert_eq!(bar2.to_point(), 4.0);
@@ -393,18 +557,21 @@ pub mod tests {
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 1.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         let bar3: OHLC = OHLC {
             open: 1.0,
             close: 1.0,
             low: 0.6,
             high: 1.5454,
+            ..Default::default()
         };
         assert_eq!(bar1.to_percent(), -0.8);
         assert_eq!(bar2.to_percent(), 4.0);
@@ -418,18 +585,21 @@ pub mod tests {
             close: 5.0,
             low: 1.6,
             high: 8.54,
+            ..Default::default()
         };
         let bar2: OHLC = OHLC {
             open: 5.0,
             close: 1.0,
             low: 0.5,
             high: 13.0,
+            ..Default::default()
         };
         let bar3: OHLC = OHLC {
             open: 5.0,
             close: 5.0,
             low: 5.5,
             high: 6.0,
+            ..Default::default()
         };
         let mut output1: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
         let mut output2: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
@@ -458,7 +628,7 @@ pub mod tests {
         bar1.show_ohlc(&mut output, 2);
         assert_eq!(
             output.into_inner(),
-            b" [Open] - 4.03  [High] - 10.00  [Low] - 1.00  [Close] - 6.00\n-------------------------------------------------------------\n"
+            b" [Open] - 4.03  [High] - 10.00  [Low] - 1.00  [Close] - 6.00\n------------------------------------------------------------\n"
         );
     }
 
@@ -473,4 +643,72 @@ pub mod tests {
         assert_eq!(output1.into_inner(), b"Strong: \xE2\x9D\x8C\n");
         assert_eq!(output2.clone().into_inner(), b"Strong: \xE2\x9C\x85\n");
     }
+
+    #[test]
+    fn test_show_history() {
+        let history = History::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![OHLC::new(5.0, 7.0, 5.0, 7.0)],
+        );
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        history.show_history(&mut output, None, 2);
+        let text = String::from_utf8(output.into_inner()).unwrap();
+        assert!(text.starts_with("Date  Open  High   Low  Close  Volume"));
+        assert!(text.contains("\n-     5.00  7.00  5.00   7.00       0  "));
+        assert!(text.contains("Trend: "));
+        assert!(text.contains("Ichimoku: "));
+        assert!(text.contains(
+            "Patterns: 1\n [-] Bullish Marubozu - \x1b[38;2;0;255;0m⬆ Up\x1b[0m (100%)\n"
+        ));
+        // A single bar has no confirmed swing to measure
+        assert!(!text.contains("Fibonacci:"));
+        assert!(text.ends_with("Key Levels: 0\n"));
+    }
+
+    #[test]
+    fn test_from_bars() {
+        let hour = 3_600;
+        let bars: Vec<OHLC> = (0..48)
+            .map(|i| OHLC::new(10.0, 11.0 + i as f32, 9.0, 10.5).at(1_710_374_400 + i * hour))
+            .collect();
+        let history = History::from_bars(bars);
+        assert_eq!(history.timeframe.len(), 48);
+        assert_eq!(history.hourly.len(), 48);
+        assert_eq!(history.daily.len(), 2);
+        assert_eq!(history.daily[1].high, 58.0);
+        assert_eq!(history.weekly.len(), 1);
+        assert_eq!(history.get_timeframe(resample::Timeframe::Yearly).len(), 1);
+
+        let daily: Vec<OHLC> = (0..3)
+            .map(|i| OHLC::new(10.0, 11.0, 9.0, 10.5).at(1_710_374_400 + i * 86_400))
+            .collect();
+        let history = History::from_bars(daily);
+        assert!(history.hourly.is_empty());
+        assert_eq!(history.daily.len(), 3);
+    }
+
+    #[test]
+    fn test_show_pivots() {
+        let history = History::new(
+            vec![],
+            vec![
+                OHLC::new(10.0, 12.0, 9.0, 11.0),
+                OHLC::new(11.0, 11.5, 10.5, 11.2),
+            ],
+            vec![OHLC::new(10.0, 12.0, 9.0, 11.0)],
+            vec![],
+            vec![],
+            vec![],
+        );
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        history.show_pivots(&mut output, pivot::Method::DeMark, 2);
+        assert_eq!(
+            output.into_inner(),
+            b"Daily Pivots (DeMark):\n [R1] - 13.00\n >> [Close] - 11.20\n [P] - 11.00\n [S1] - 10.00\n"
+        );
+    }
 }
@@ -0,0 +1,518 @@
+//! Candlestick pattern recognition over a series of bars
+//!
+//! Single bar patterns build on the body and wick metrics
+//! of `OHLC`, while multi bar patterns compare neighbouring
+//! bars. Every match carries a confidence score between
+//! `0.0` and `1.0` of how clearly the bars fit the pattern.
+
+use super::{write_direction, OHLC};
+use chrono::DateTime;
+use std::io::Write;
+
+/// Known candlestick patterns
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Doji,
+    LongLeggedDoji,
+    DragonflyDoji,
+    GravestoneDoji,
+    Hammer,
+    HangingMan,
+    InvertedHammer,
+    ShootingStar,
+    BullishMarubozu,
+    BearishMarubozu,
+    BullishEngulfing,
+    BearishEngulfing,
+    BullishHarami,
+    BearishHarami,
+    Piercing,
+    DarkCloudCover,
+    MorningStar,
+    EveningStar,
+    ThreeWhiteSoldiers,
+    ThreeBlackCrows,
+}
+
+impl Kind {
+    /// Returns the readable name of a pattern
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Kind::Doji => "Doji",
+            Kind::LongLeggedDoji => "Long-Legged Doji",
+            Kind::DragonflyDoji => "Dragonfly Doji",
+            Kind::GravestoneDoji => "Gravestone Doji",
+            Kind::Hammer => "Hammer",
+            Kind::HangingMan => "Hanging Man",
+            Kind::InvertedHammer => "Inverted Hammer",
+            Kind::ShootingStar => "Shooting Star",
+            Kind::BullishMarubozu => "Bullish Marubozu",
+            Kind::BearishMarubozu => "Bearish Marubozu",
+            Kind::BullishEngulfing => "Bullish Engulfing",
+            Kind::BearishEngulfing => "Bearish Engulfing",
+            Kind::BullishHarami => "Bullish Harami",
+            Kind::BearishHarami => "Bearish Harami",
+            Kind::Piercing => "Piercing Line",
+            Kind::DarkCloudCover => "Dark Cloud Cover",
+            Kind::MorningStar => "Morning Star",
+            Kind::EveningStar => "Evening Star",
+            Kind::ThreeWhiteSoldiers => "Three White Soldiers",
+            Kind::ThreeBlackCrows => "Three Black Crows",
+        }
+    }
+}
+
+/// A detected pattern ending at the bar `index`
+///
+/// `direction` is the bias of the pattern using the
+/// `'U'`, `'D'` and `'N'` vocabulary of `OHLC::to_direction`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pattern {
+    pub kind: Kind,
+    pub index: usize,
+    pub direction: char,
+    pub confidence: f32,
+}
+
+/// Tolerances used when matching patterns
+pub struct Tolerance {
+    /// Largest body to range ratio of a doji
+    pub doji: f32,
+    /// Smallest wick to body ratio of a long wick
+    pub wick: f32,
+    /// Largest wick to range ratio of a shaved (no wick) end
+    pub shaved: f32,
+    /// Smallest body to range ratio of a long body
+    pub long_body: f32,
+    /// Number of bars used to gauge the prior trend
+    pub lookback: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            doji: 0.1,
+            wick: 2.0,
+            shaved: 0.1,
+            long_body: 0.6,
+            lookback: 3,
+        }
+    }
+}
+
+/// Returns how far `value` passes a minimum `threshold`
+/// as a score between `0.5` and `1.0`
+fn to_score_above(value: f32, threshold: f32) -> f32 {
+    if threshold <= 0.0 {
+        return 1.0;
+    }
+    (value / threshold).clamp(1.0, 2.0) / 2.0
+}
+
+/// Returns how far `value` stays under a maximum `threshold`
+/// as a score between `0.5` and `1.0`
+fn to_score_below(value: f32, threshold: f32) -> f32 {
+    if threshold <= 0.0 {
+        return 1.0;
+    }
+    1.0 - (value / threshold).clamp(0.0, 1.0) / 2.0
+}
+
+fn to_body(bar: &OHLC) -> f32 {
+    bar.to_point().abs()
+}
+
+/// Returns the direction of closes over the `lookback`
+/// bars before `index`
+fn to_prior(bars: &[OHLC], index: usize, lookback: usize) -> char {
+    if lookback == 0 || index < lookback {
+        return 'N';
+    }
+    let first = &bars[index - lookback];
+    let last = &bars[index - 1];
+    OHLC::new(first.close, 0.0, 0.0, last.close).to_direction()
+}
+
+/// Returns patterns made of only the bar at `index`
+fn to_single(bars: &[OHLC], index: usize, tolerance: &Tolerance) -> Vec<Pattern> {
+    let bar = &bars[index];
+    let range = bar.to_range();
+    if range <= 0.0 {
+        return vec![];
+    }
+    let body = to_body(bar);
+    let top = bar.to_top_wick();
+    let bot = bar.to_bot_wick();
+    let prior = to_prior(bars, index, tolerance.lookback);
+    let pattern = |kind: Kind, direction: char, confidence: f32| Pattern {
+        kind,
+        index,
+        direction,
+        confidence,
+    };
+    let mut patterns = vec![];
+
+    if body / range <= tolerance.doji {
+        let doji_score = to_score_below(body / range, tolerance.doji);
+        if top / range <= tolerance.shaved {
+            let score = (doji_score + to_score_below(top / range, tolerance.shaved)) / 2.0;
+            patterns.push(pattern(Kind::DragonflyDoji, 'U', score));
+        } else if bot / range <= tolerance.shaved {
+            let score = (doji_score + to_score_below(bot / range, tolerance.shaved)) / 2.0;
+            patterns.push(pattern(Kind::GravestoneDoji, 'D', score));
+        } else if top.min(bot) / range >= 0.3 {
+            patterns.push(pattern(Kind::LongLeggedDoji, 'N', doji_score));
+        } else {
+            patterns.push(pattern(Kind::Doji, 'N', doji_score));
+        }
+        return patterns;
+    }
+
+    if top + bot <= tolerance.shaved * range {
+        let score = to_score_below((top + bot) / range, tolerance.shaved);
+        match bar.to_direction() {
+            'U' => patterns.push(pattern(Kind::BullishMarubozu, 'U', score)),
+            _ => patterns.push(pattern(Kind::BearishMarubozu, 'D', score)),
+        }
+    }
+
+    if bot >= tolerance.wick * body && top <= tolerance.shaved * range {
+        let score = (to_score_above(bot / body, tolerance.wick)
+            + to_score_below(top / range, tolerance.shaved))
+            / 2.0;
+        match prior {
+            'D' => patterns.push(pattern(Kind::Hammer, 'U', score)),
+            'U' => patterns.push(pattern(Kind::HangingMan, 'D', score)),
+            _ => {}
+        }
+    }
+
+    if top >= tolerance.wick * body && bot <= tolerance.shaved * range {
+        let score = (to_score_above(top / body, tolerance.wick)
+            + to_score_below(bot / range, tolerance.shaved))
+            / 2.0;
+        match prior {
+            'U' => patterns.push(pattern(Kind::ShootingStar, 'D', score)),
+            'D' => patterns.push(pattern(Kind::InvertedHammer, 'U', score)),
+            _ => {}
+        }
+    }
+    patterns
+}
+
+/// Returns patterns made of the bars before and at `index`
+fn to_multi(bars: &[OHLC], index: usize, tolerance: &Tolerance) -> Vec<Pattern> {
+    let mut patterns = vec![];
+    let pattern = |kind: Kind, direction: char, confidence: f32| Pattern {
+        kind,
+        index,
+        direction,
+        confidence,
+    };
+    if index < 1 {
+        return patterns;
+    }
+
+    let prev = &bars[index - 1];
+    let curr = &bars[index];
+    let (prev_body, curr_body) = (to_body(prev), to_body(curr));
+    let prev_mid = (prev.open + prev.close) / 2.0;
+
+    match (prev.to_direction(), curr.to_direction()) {
+        ('D', 'U') => {
+            if curr.open <= prev.close && curr.close >= prev.open && curr_body > prev_body {
+                let score = to_score_above(curr_body / prev_body, 1.0);
+                patterns.push(pattern(Kind::BullishEngulfing, 'U', score));
+            } else if curr.open > prev.close && curr.close < prev.open {
+                let score = to_score_below(curr_body / prev_body, 1.0);
+                patterns.push(pattern(Kind::BullishHarami, 'U', score));
+            } else if curr.open < prev.close && curr.close > prev_mid && curr.close < prev.open {
+                let score = to_score_above(curr.close - prev.close, prev_mid - prev.close);
+                patterns.push(pattern(Kind::Piercing, 'U', score));
+            }
+        }
+        ('U', 'D') => {
+            if curr.open >= prev.close && curr.close <= prev.open && curr_body > prev_body {
+                let score = to_score_above(curr_body / prev_body, 1.0);
+                patterns.push(pattern(Kind::BearishEngulfing, 'D', score));
+            } else if curr.open < prev.close && curr.close > prev.open {
+                let score = to_score_below(curr_body / prev_body, 1.0);
+                patterns.push(pattern(Kind::BearishHarami, 'D', score));
+            } else if curr.open > prev.close && curr.close < prev_mid && curr.close > prev.open {
+                let score = to_score_above(prev.close - curr.close, prev.close - prev_mid);
+                patterns.push(pattern(Kind::DarkCloudCover, 'D', score));
+            }
+        }
+        _ => {}
+    }
+
+    if index < 2 {
+        return patterns;
+    }
+
+    let first = &bars[index - 2];
+    let first_body = to_body(first);
+    let first_range = first.to_range();
+    let first_mid = (first.open + first.close) / 2.0;
+    let is_long = first_range > 0.0 && first_body / first_range >= tolerance.long_body;
+    let is_star = first_body > 0.0 && prev_body / first_body <= 0.3;
+    if is_long && is_star {
+        let score = (to_score_above(first_body / first_range, tolerance.long_body)
+            + to_score_below(prev_body / first_body, 0.3))
+            / 2.0;
+        match (first.to_direction(), curr.to_direction()) {
+            ('D', 'U') if curr.close > first_mid => {
+                patterns.push(pattern(Kind::MorningStar, 'U', score))
+            }
+            ('U', 'D') if curr.close < first_mid => {
+                patterns.push(pattern(Kind::EveningStar, 'D', score))
+            }
+            _ => {}
+        }
+    }
+
+    let three = &bars[index - 2..=index];
+    let is_long_body =
+        |bar: &OHLC| bar.to_range() > 0.0 && to_body(bar) / bar.to_range() >= tolerance.long_body;
+    if three.iter().all(is_long_body) {
+        let score = three
+            .iter()
+            .map(|bar| to_score_above(to_body(bar) / bar.to_range(), tolerance.long_body))
+            .sum::<f32>()
+            / 3.0;
+        let soldiers = three.windows(2).all(|pair| {
+            pair[1].to_direction() == 'U'
+                && pair[0].to_direction() == 'U'
+                && pair[1].close > pair[0].close
+                && pair[1].open >= pair[0].open
+                && pair[1].open <= pair[0].close
+        });
+        let crows = three.windows(2).all(|pair| {
+            pair[1].to_direction() == 'D'
+                && pair[0].to_direction() == 'D'
+                && pair[1].close < pair[0].close
+                && pair[1].open <= pair[0].open
+                && pair[1].open >= pair[0].close
+        });
+        if soldiers {
+            patterns.push(pattern(Kind::ThreeWhiteSoldiers, 'U', score));
+        } else if crows {
+            patterns.push(pattern(Kind::ThreeBlackCrows, 'D', score));
+        }
+    }
+    patterns
+}
+
+/// Returns every pattern detected in a series of bars
+///
+/// Default `tolerance` is: `Tolerance::default()`
+pub fn to_patterns(bars: &[OHLC], tolerance: Option<Tolerance>) -> Vec<Pattern> {
+    let tolerance: Tolerance = tolerance.unwrap_or_default();
+    let mut patterns = vec![];
+    for index in 0..bars.len() {
+        patterns.extend(to_single(bars, index, &tolerance));
+        patterns.extend(to_multi(bars, index, &tolerance));
+    }
+    patterns
+}
+
+/// Write the list of patterns ending in the `last` bars of a
+/// series with the date of their bar
+///
+/// Earlier bars still count for the patterns spanning them
+pub fn show_patterns<T: Write>(
+    bars: &[OHLC],
+    mut output: T,
+    tolerance: Option<Tolerance>,
+    last: usize,
+) {
+    let start = bars.len().saturating_sub(last);
+    let patterns: Vec<Pattern> = to_patterns(bars, tolerance)
+        .into_iter()
+        .filter(|pattern| pattern.index >= start)
+        .collect();
+    let _ = writeln!(output, "Patterns: {}", patterns.len());
+    for pattern in patterns {
+        let time = bars[pattern.index].time;
+        let format = match time % 86_400 {
+            0 => "%Y-%m-%d",
+            _ => "%Y-%m-%d %H:%M",
+        };
+        let date = match DateTime::from_timestamp(time, 0) {
+            Some(date) if time != 0 => date.format(format).to_string(),
+            _ => String::from("-"),
+        };
+        let _ = write!(output, " [{}] {} - ", date, pattern.kind.to_name());
+        write_direction(&mut output, pattern.direction);
+        let _ = writeln!(output, " ({:.0}%)", pattern.confidence * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn falling(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let base = 100.0 - 2.0 * i as f32;
+                OHLC::new(base, base + 0.5, base - 2.5, base - 2.0)
+            })
+            .collect()
+    }
+
+    fn rising(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let base = 10.0 + 2.0 * i as f32;
+                OHLC::new(base, base + 2.5, base - 0.5, base + 2.0)
+            })
+            .collect()
+    }
+
+    fn kinds(bars: &[OHLC]) -> Vec<Kind> {
+        to_patterns(bars, None)
+            .iter()
+            .filter(|pattern| pattern.index == bars.len() - 1)
+            .map(|pattern| pattern.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_doji() {
+        assert_eq!(
+            kinds(&[OHLC::new(5.0, 6.0, 4.0, 5.05)]),
+            vec![Kind::LongLeggedDoji]
+        );
+        assert_eq!(
+            kinds(&[OHLC::new(5.0, 5.05, 4.0, 5.0)]),
+            vec![Kind::DragonflyDoji]
+        );
+        assert_eq!(
+            kinds(&[OHLC::new(5.0, 6.0, 4.95, 5.0)]),
+            vec![Kind::GravestoneDoji]
+        );
+        assert_eq!(kinds(&[OHLC::new(5.0, 5.3, 4.0, 5.0)]), vec![Kind::Doji]);
+    }
+
+    #[test]
+    fn test_hammer() {
+        let mut bars = falling(4);
+        bars.push(OHLC::new(90.0, 91.05, 87.0, 91.0));
+        assert_eq!(kinds(&bars), vec![Kind::Hammer]);
+
+        let mut bars = rising(4);
+        bars.push(OHLC::new(20.0, 21.05, 17.0, 21.0));
+        assert_eq!(kinds(&bars), vec![Kind::HangingMan]);
+    }
+
+    #[test]
+    fn test_shooting_star() {
+        let mut bars = rising(4);
+        bars.push(OHLC::new(18.0, 21.0, 16.95, 17.0));
+        assert_eq!(kinds(&bars), vec![Kind::ShootingStar]);
+
+        let mut bars = falling(4);
+        bars.push(OHLC::new(91.0, 94.0, 89.95, 90.0));
+        assert_eq!(kinds(&bars), vec![Kind::InvertedHammer]);
+    }
+
+    #[test]
+    fn test_marubozu() {
+        assert_eq!(
+            kinds(&[OHLC::new(5.0, 7.0, 5.0, 7.0)]),
+            vec![Kind::BullishMarubozu]
+        );
+        assert_eq!(
+            kinds(&[OHLC::new(7.0, 7.0, 5.0, 5.0)]),
+            vec![Kind::BearishMarubozu]
+        );
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let bars = [OHLC::new(6.0, 6.2, 4.8, 5.0), OHLC::new(4.9, 6.4, 4.7, 6.3)];
+        assert!(kinds(&bars).contains(&Kind::BullishEngulfing));
+        let bars = [OHLC::new(5.0, 6.2, 4.8, 6.0), OHLC::new(6.1, 6.3, 4.6, 4.7)];
+        assert!(kinds(&bars).contains(&Kind::BearishEngulfing));
+    }
+
+    #[test]
+    fn test_harami() {
+        let bars = [OHLC::new(8.0, 8.2, 3.8, 4.0), OHLC::new(5.0, 6.5, 4.5, 6.0)];
+        assert!(kinds(&bars).contains(&Kind::BullishHarami));
+        let bars = [OHLC::new(4.0, 8.2, 3.8, 8.0), OHLC::new(7.0, 7.5, 5.5, 6.0)];
+        assert!(kinds(&bars).contains(&Kind::BearishHarami));
+    }
+
+    #[test]
+    fn test_piercing() {
+        let bars = [OHLC::new(8.0, 8.2, 3.8, 4.0), OHLC::new(3.5, 7.2, 3.4, 7.0)];
+        assert!(kinds(&bars).contains(&Kind::Piercing));
+        let bars = [OHLC::new(4.0, 8.2, 3.8, 8.0), OHLC::new(8.5, 8.6, 4.8, 5.0)];
+        assert!(kinds(&bars).contains(&Kind::DarkCloudCover));
+    }
+
+    #[test]
+    fn test_star() {
+        let bars = [
+            OHLC::new(10.0, 10.2, 5.8, 6.0),
+            OHLC::new(5.8, 6.2, 5.2, 5.6),
+            OHLC::new(6.0, 9.2, 5.9, 9.0),
+        ];
+        assert!(kinds(&bars).contains(&Kind::MorningStar));
+        let bars = [
+            OHLC::new(6.0, 10.2, 5.8, 10.0),
+            OHLC::new(10.2, 10.8, 9.8, 10.4),
+            OHLC::new(10.0, 10.1, 6.8, 7.0),
+        ];
+        assert!(kinds(&bars).contains(&Kind::EveningStar));
+    }
+
+    #[test]
+    fn test_three_soldiers() {
+        assert!(kinds(&rising(3)).contains(&Kind::ThreeWhiteSoldiers));
+        let crows = [
+            OHLC::new(10.0, 10.1, 7.9, 8.0),
+            OHLC::new(8.5, 8.6, 6.4, 6.5),
+            OHLC::new(7.0, 7.1, 4.9, 5.0),
+        ];
+        assert!(kinds(&crows).contains(&Kind::ThreeBlackCrows));
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let bar = [OHLC::new(5.0, 6.0, 4.0, 5.3)];
+        assert!(kinds(&bar).is_empty());
+        let tolerance = Tolerance {
+            doji: 0.2,
+            ..Default::default()
+        };
+        let patterns = to_patterns(&bar, Some(tolerance));
+        assert_eq!(patterns[0].kind, Kind::LongLeggedDoji);
+        assert!(patterns[0].confidence > 0.5 && patterns[0].confidence < 1.0);
+    }
+
+    #[test]
+    fn test_show_patterns() {
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_patterns(&[OHLC::new(5.0, 7.0, 5.0, 7.0)], &mut output, None, 10);
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "Patterns: 1\n [-] Bullish Marubozu - \x1b[38;2;0;255;0m⬆ Up\x1b[0m (100%)\n"
+        );
+
+        // Only the patterns of the last bar are listed
+        let bars: Vec<OHLC> = (0..3)
+            .map(|i| OHLC::new(5.0, 7.0, 5.0, 7.0).at(1_710_374_400 + i * 86_400))
+            .collect();
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_patterns(&bars, &mut output, None, 1);
+        let text = String::from_utf8(output.into_inner()).unwrap();
+        assert!(text.contains(" [2024-03-16] Bullish Marubozu"));
+        assert!(text
+            .lines()
+            .skip(1)
+            .all(|line| line.starts_with(" [2024-03-16] ")));
+    }
+}
@@ -0,0 +1,313 @@
+//! Support and resistance levels
+//!
+//! Pivot points are computed from the previous period's
+//! `OHLC` while Fibonacci levels are measured between the
+//! latest confirmed swing high and low of a series of bars.
+
+use super::swing::Swing;
+use super::OHLC;
+use std::io::Write;
+
+/// Ways of computing pivot points
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+    Classic,
+    Fibonacci,
+    Woodie,
+    Camarilla,
+    DeMark,
+}
+
+impl Method {
+    /// Returns the readable name of a method
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Method::Classic => "Classic",
+            Method::Fibonacci => "Fibonacci",
+            Method::Woodie => "Woodie",
+            Method::Camarilla => "Camarilla",
+            Method::DeMark => "DeMark",
+        }
+    }
+}
+
+/// A named price level
+#[derive(Clone, PartialEq, Debug)]
+pub struct Level {
+    pub name: String,
+    pub price: f32,
+}
+
+impl Level {
+    fn new(name: &str, price: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            price,
+        }
+    }
+}
+
+/// Returns the pivot levels of the previous period's bar
+/// ordered from highest to lowest price
+pub fn to_pivots(bar: &OHLC, method: Method) -> Vec<Level> {
+    let (high, low, close) = (bar.high, bar.low, bar.close);
+    let range = bar.to_range();
+    let classic = (high + low + close) / 3.0;
+
+    let mut levels = match method {
+        Method::Classic => vec![
+            Level::new("R3", high + 2.0 * (classic - low)),
+            Level::new("R2", classic + range),
+            Level::new("R1", 2.0 * classic - low),
+            Level::new("P", classic),
+            Level::new("S1", 2.0 * classic - high),
+            Level::new("S2", classic - range),
+            Level::new("S3", low - 2.0 * (high - classic)),
+        ],
+        Method::Fibonacci => vec![
+            Level::new("R3", classic + range),
+            Level::new("R2", classic + 0.618 * range),
+            Level::new("R1", classic + 0.382 * range),
+            Level::new("P", classic),
+            Level::new("S1", classic - 0.382 * range),
+            Level::new("S2", classic - 0.618 * range),
+            Level::new("S3", classic - range),
+        ],
+        Method::Woodie => {
+            let pivot = (high + low + 2.0 * close) / 4.0;
+            vec![
+                Level::new("R3", high + 2.0 * (pivot - low)),
+                Level::new("R2", pivot + range),
+                Level::new("R1", 2.0 * pivot - low),
+                Level::new("P", pivot),
+                Level::new("S1", 2.0 * pivot - high),
+                Level::new("S2", pivot - range),
+                Level::new("S3", low - 2.0 * (high - pivot)),
+            ]
+        }
+        Method::Camarilla => vec![
+            Level::new("R4", close + range * 1.1 / 2.0),
+            Level::new("R3", close + range * 1.1 / 4.0),
+            Level::new("R2", close + range * 1.1 / 6.0),
+            Level::new("R1", close + range * 1.1 / 12.0),
+            Level::new("P", classic),
+            Level::new("S1", close - range * 1.1 / 12.0),
+            Level::new("S2", close - range * 1.1 / 6.0),
+            Level::new("S3", close - range * 1.1 / 4.0),
+            Level::new("S4", close - range * 1.1 / 2.0),
+        ],
+        Method::DeMark => {
+            let x = match bar.to_direction() {
+                'D' => high + 2.0 * low + close,
+                'U' => 2.0 * high + low + close,
+                _ => high + low + 2.0 * close,
+            };
+            vec![
+                Level::new("R1", x / 2.0 - low),
+                Level::new("P", x / 4.0),
+                Level::new("S1", x / 2.0 - high),
+            ]
+        }
+    };
+    levels.sort_by(|a, b| b.price.total_cmp(&a.price));
+    levels
+}
+
+/// Returns the swing high, swing low and direction of the
+/// latest swing between confirmed swing points such as the
+/// ones of `swing::to_fractals`
+///
+/// Direction is `'U'` when the low came before the high
+/// otherwise `'D'`
+pub fn to_swing(swings: &[Swing]) -> Option<(f32, f32, char)> {
+    let high = swings.iter().rev().find(|swing| swing.high)?;
+    let low = swings.iter().rev().find(|swing| !swing.high)?;
+    if high.price <= low.price {
+        return None;
+    }
+    let direction = if low.index <= high.index { 'U' } else { 'D' };
+    Some((high.price, low.price, direction))
+}
+
+/// Returns the Fibonacci retracement and extension levels
+/// of a swing ordered from highest to lowest price
+///
+/// Retracements are measured back from the end of the swing
+/// and extensions project past it
+pub fn to_fibonacci(high: f32, low: f32, direction: char) -> Vec<Level> {
+    let range = high - low;
+    let retracements = [0.0, 0.236, 0.382, 0.5, 0.618, 0.786, 1.0];
+    let extensions = [1.272, 1.618, 2.618];
+
+    let mut levels: Vec<Level> = retracements
+        .iter()
+        .map(|ratio| {
+            let price = if direction == 'U' {
+                high - ratio * range
+            } else {
+                low + ratio * range
+            };
+            Level::new(&format!("{:.1}%", ratio * 100.0), price)
+        })
+        .collect();
+    levels.extend(extensions.iter().map(|ratio| {
+        let price = if direction == 'U' {
+            low + ratio * range
+        } else {
+            high - ratio * range
+        };
+        Level::new(&format!("{:.1}%", ratio * 100.0), price)
+    }));
+    levels.sort_by(|a, b| b.price.total_cmp(&a.price));
+    levels
+}
+
+/// Write levels with a marker showing where `close` sits
+///
+/// To prevent losing precision specfiy
+/// the level of `precision` after decimal needed
+pub fn show_levels<T: Write>(levels: &[Level], close: f32, mut output: T, precision: usize) {
+    let mut marked = false;
+    for level in levels {
+        if !marked && close >= level.price {
+            let _ = writeln!(output, " >> [Close] - {1:.0$}", precision, close);
+            marked = true;
+        }
+        let _ = writeln!(
+            output,
+            " [{}] - {2:.1$}",
+            level.name, precision, level.price
+        );
+    }
+    if !marked {
+        let _ = writeln!(output, " >> [Close] - {1:.0$}", precision, close);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_prices(levels: &[Level]) -> Vec<(String, f32)> {
+        levels
+            .iter()
+            .map(|level| (level.name.clone(), (level.price * 100.0).round() / 100.0))
+            .collect()
+    }
+
+    fn named(levels: &[(&str, f32)]) -> Vec<(String, f32)> {
+        levels
+            .iter()
+            .map(|(name, price)| (name.to_string(), *price))
+            .collect()
+    }
+
+    #[test]
+    fn test_to_pivots_classic() {
+        let bar = OHLC::new(10.0, 12.0, 9.0, 11.0);
+        assert_eq!(
+            to_prices(&to_pivots(&bar, Method::Classic)),
+            named(&[
+                ("R3", 15.33),
+                ("R2", 13.67),
+                ("R1", 12.33),
+                ("P", 10.67),
+                ("S1", 9.33),
+                ("S2", 7.67),
+                ("S3", 6.33),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_to_pivots_fibonacci() {
+        let bar = OHLC::new(10.0, 12.0, 9.0, 11.0);
+        let levels = to_prices(&to_pivots(&bar, Method::Fibonacci));
+        assert_eq!(levels[0], ("R3".to_string(), 13.67));
+        assert_eq!(levels[2], ("R1".to_string(), 11.81));
+        assert_eq!(levels[5], ("S2".to_string(), 8.81));
+    }
+
+    #[test]
+    fn test_to_pivots_woodie() {
+        let bar = OHLC::new(10.0, 12.0, 9.0, 11.0);
+        let levels = to_prices(&to_pivots(&bar, Method::Woodie));
+        assert_eq!(levels[3], ("P".to_string(), 10.75));
+        assert_eq!(levels[2], ("R1".to_string(), 12.5));
+    }
+
+    #[test]
+    fn test_to_pivots_camarilla() {
+        let bar = OHLC::new(10.0, 12.0, 9.0, 11.0);
+        let levels = to_prices(&to_pivots(&bar, Method::Camarilla));
+        assert_eq!(levels.len(), 9);
+        assert_eq!(levels[0], ("R4".to_string(), 12.65));
+        assert_eq!(levels[3], ("R1".to_string(), 11.28));
+        assert_eq!(levels[8], ("S4".to_string(), 9.35));
+    }
+
+    #[test]
+    fn test_to_pivots_demark() {
+        let up = OHLC::new(10.0, 12.0, 9.0, 11.0);
+        assert_eq!(
+            to_prices(&to_pivots(&up, Method::DeMark)),
+            named(&[("R1", 13.0), ("P", 11.0), ("S1", 10.0)])
+        );
+        let down = OHLC::new(11.0, 12.0, 9.0, 10.0);
+        assert_eq!(
+            to_prices(&to_pivots(&down, Method::DeMark)),
+            named(&[("R1", 11.0), ("P", 10.0), ("S1", 8.0)])
+        );
+    }
+
+    #[test]
+    fn test_to_swing() {
+        let bars = vec![
+            OHLC::new(6.0, 7.0, 5.0, 6.0),
+            OHLC::new(5.0, 6.0, 4.0, 5.5),
+            OHLC::new(5.5, 9.0, 5.0, 8.5),
+            OHLC::new(8.5, 8.8, 6.0, 6.5),
+            OHLC::new(6.5, 7.0, 3.0, 3.5),
+            OHLC::new(3.5, 5.0, 3.2, 4.5),
+            // The lowest low is not confirmed yet
+            OHLC::new(4.5, 5.0, 1.0, 2.0),
+        ];
+        let swings = super::super::swing::to_fractals(&bars, 1);
+        // The high of 9.0 came before the low of 3.0
+        assert_eq!(to_swing(&swings), Some((9.0, 3.0, 'D')));
+        assert_eq!(to_swing(&swings[..2]), Some((9.0, 4.0, 'U')));
+        assert_eq!(to_swing(&swings[..1]), None);
+        assert_eq!(to_swing(&[]), None);
+    }
+
+    #[test]
+    fn test_to_fibonacci() {
+        let up = to_prices(&to_fibonacci(20.0, 10.0, 'U'));
+        assert_eq!(up[0], ("261.8%".to_string(), 36.18));
+        assert_eq!(up[3], ("0.0%".to_string(), 20.0));
+        assert_eq!(up[5], ("38.2%".to_string(), 16.18));
+        assert_eq!(up[9], ("100.0%".to_string(), 10.0));
+
+        let down = to_prices(&to_fibonacci(20.0, 10.0, 'D'));
+        assert_eq!(down[0], ("100.0%".to_string(), 20.0));
+        assert_eq!(down[9], ("261.8%".to_string(), -6.18));
+    }
+
+    #[test]
+    fn test_show_levels() {
+        let levels = to_pivots(&OHLC::new(10.0, 12.0, 9.0, 11.0), Method::DeMark);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_levels(&levels, 11.2, &mut output, 2);
+        assert_eq!(
+            output.into_inner(),
+            b" [R1] - 13.00\n >> [Close] - 11.20\n [P] - 11.00\n [S1] - 10.00\n"
+        );
+
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_levels(&levels, 1.0, &mut output, 1);
+        assert_eq!(
+            output.into_inner(),
+            b" [R1] - 13.0\n [P] - 11.0\n [S1] - 10.0\n >> [Close] - 1.0\n"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Resampling of bars into the timeframes of `History`
+//!
+//! Periods are aligned to UTC with weeks starting on Monday.
+
+use super::OHLC;
+use chrono::{DateTime, Datelike, NaiveDate};
+
+const HOUR: i64 = 3_600;
+const DAY: i64 = 86_400;
+
+/// Timeframes available in `History`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Timeframe {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Timeframe {
+    /// All timeframes from the shortest to the longest
+    pub const ALL: [Timeframe; 5] = [
+        Timeframe::Hourly,
+        Timeframe::Daily,
+        Timeframe::Weekly,
+        Timeframe::Monthly,
+        Timeframe::Yearly,
+    ];
+
+    /// Returns the readable name of a timeframe
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Timeframe::Hourly => "hourly",
+            Timeframe::Daily => "daily",
+            Timeframe::Weekly => "weekly",
+            Timeframe::Monthly => "monthly",
+            Timeframe::Yearly => "yearly",
+        }
+    }
+
+    /// Returns the shortest length of a period in seconds
+    pub fn to_seconds(&self) -> i64 {
+        match self {
+            Timeframe::Hourly => HOUR,
+            Timeframe::Daily => DAY,
+            Timeframe::Weekly => 7 * DAY,
+            Timeframe::Monthly => 28 * DAY,
+            Timeframe::Yearly => 365 * DAY,
+        }
+    }
+
+    /// Returns the Unix timestamp of the start of the
+    /// period containing `time`
+    pub fn to_start(&self, time: i64) -> i64 {
+        let days = time.div_euclid(DAY);
+        match self {
+            Timeframe::Hourly => time - time.rem_euclid(HOUR),
+            Timeframe::Daily => days * DAY,
+            // 1970-01-01 was a Thursday
+            Timeframe::Weekly => (days - (days + 3).rem_euclid(7)) * DAY,
+            Timeframe::Monthly | Timeframe::Yearly => {
+                let Some(date) = DateTime::from_timestamp(time, 0) else {
+                    return days * DAY;
+                };
+                let month = if *self == Timeframe::Monthly {
+                    date.month()
+                } else {
+                    1
+                };
+                NaiveDate::from_ymd_opt(date.year(), month, 1)
+                    .and_then(|start| start.and_hms_opt(0, 0, 0))
+                    .map(|start| start.and_utc().timestamp())
+                    .unwrap_or(days * DAY)
+            }
+        }
+    }
+}
+
+/// Returns the bars merged into periods of a timeframe
+///
+/// Bars must be ordered from oldest to newest, each
+/// merged bar takes the start time of its period
+pub fn to_resample(bars: &[OHLC], timeframe: Timeframe) -> Vec<OHLC> {
+    let mut merged: Vec<OHLC> = vec![];
+    for bar in bars {
+        let start = timeframe.to_start(bar.time);
+        match merged.last_mut() {
+            Some(last) if last.time == start => {
+                last.high = last.high.max(bar.high);
+                last.low = last.low.min(bar.low);
+                last.close = bar.close;
+                last.volume += bar.volume;
+            }
+            _ => merged.push(
+                OHLC::new(bar.open, bar.high, bar.low, bar.close)
+                    .at(start)
+                    .with_volume(bar.volume),
+            ),
+        }
+    }
+    merged
+}
+
+/// Returns the shortest positive time between two bars
+pub fn to_interval(bars: &[OHLC]) -> Option<i64> {
+    bars.windows(2)
+        .map(|pair| pair[1].time - pair[0].time)
+        .filter(|gap| *gap > 0)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_start() {
+        // 2024-03-14 15:30:00 UTC, a Thursday
+        let time = 1_710_430_200;
+        assert_eq!(Timeframe::Hourly.to_start(time), 1_710_428_400);
+        assert_eq!(Timeframe::Daily.to_start(time), 1_710_374_400);
+        assert_eq!(Timeframe::Weekly.to_start(time), 1_710_115_200);
+        assert_eq!(Timeframe::Monthly.to_start(time), 1_709_251_200);
+        assert_eq!(Timeframe::Yearly.to_start(time), 1_704_067_200);
+    }
+
+    #[test]
+    fn test_to_resample() {
+        let bars = vec![
+            OHLC::new(10.0, 11.0, 9.0, 10.5).at(1_710_374_400),
+            OHLC::new(10.5, 12.0, 10.0, 11.5)
+                .at(1_710_378_000)
+                .with_volume(50.0),
+            OHLC::new(11.5, 11.8, 8.0, 9.0).at(1_710_460_800),
+        ];
+        let daily = to_resample(&bars, Timeframe::Daily);
+        assert_eq!(daily.len(), 2);
+        assert_eq!(
+            (daily[0].open, daily[0].high, daily[0].low, daily[0].close),
+            (10.0, 12.0, 9.0, 11.5)
+        );
+        assert_eq!(daily[0].volume, 50.0);
+        assert_eq!(daily[1].time, 1_710_460_800);
+
+        let weekly = to_resample(&bars, Timeframe::Weekly);
+        assert_eq!(weekly.len(), 1);
+        assert_eq!((weekly[0].low, weekly[0].close), (8.0, 9.0));
+    }
+
+    #[test]
+    fn test_to_interval() {
+        let bars = vec![
+            OHLC::new(1.0, 1.0, 1.0, 1.0).at(0),
+            OHLC::new(1.0, 1.0, 1.0, 1.0).at(7_200),
+            OHLC::new(1.0, 1.0, 1.0, 1.0).at(10_800),
+        ];
+        assert_eq!(to_interval(&bars), Some(3_600));
+        assert_eq!(to_interval(&bars[..1]), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Swing points and key support/resistance zones
+//!
+//! Swing highs and lows are found with fractals or a
+//! zig-zag percent threshold, then clustered into zones
+//! that price has touched more than once.
+
+use super::OHLC;
+use std::io::Write;
+
+/// A confirmed swing high or low at the bar `index`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Swing {
+    pub index: usize,
+    pub price: f32,
+    pub high: bool,
+}
+
+/// A cluster of swing points
+///
+/// `kind` is `'S'` for support below the latest close
+/// otherwise `'R'` for resistance
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Zone {
+    pub low: f32,
+    pub high: f32,
+    pub touches: usize,
+    pub strength: f32,
+    pub kind: char,
+}
+
+/// Returns the fractal swing points of a series of bars
+///
+/// A swing high has a higher high than the `width` bars on
+/// either side of it and a swing low a lower low
+pub fn to_fractals(bars: &[OHLC], width: usize) -> Vec<Swing> {
+    let mut swings = vec![];
+    if width == 0 || bars.len() < 2 * width + 1 {
+        return swings;
+    }
+    for i in width..bars.len() - width {
+        let neighbours = bars[i - width..i].iter().chain(&bars[i + 1..=i + width]);
+        if neighbours.clone().all(|bar| bar.high < bars[i].high) {
+            swings.push(Swing {
+                index: i,
+                price: bars[i].high,
+                high: true,
+            });
+        }
+        if neighbours.clone().all(|bar| bar.low > bars[i].low) {
+            swings.push(Swing {
+                index: i,
+                price: bars[i].low,
+                high: false,
+            });
+        }
+    }
+    swings
+}
+
+/// Returns the zig-zag swing points of a series of bars
+///
+/// A swing is confirmed once price reverses by `percent`
+/// (`5.0` = 5%) from the latest extreme
+pub fn to_zigzag(bars: &[OHLC], percent: f32) -> Vec<Swing> {
+    let mut swings = vec![];
+    if bars.is_empty() {
+        return swings;
+    }
+    let ratio = percent / 100.0;
+    let swing = |index: usize, high: bool| Swing {
+        index,
+        price: if high {
+            bars[index].high
+        } else {
+            bars[index].low
+        },
+        high,
+    };
+
+    let mut trend = 'N';
+    let (mut high_at, mut low_at) = (0, 0);
+    for (i, bar) in bars.iter().enumerate().skip(1) {
+        match trend {
+            'U' => {
+                if bar.high > bars[high_at].high {
+                    high_at = i;
+                } else if bar.low <= bars[high_at].high * (1.0 - ratio) {
+                    swings.push(swing(high_at, true));
+                    trend = 'D';
+                    low_at = i;
+                }
+            }
+            'D' => {
+                if bar.low < bars[low_at].low {
+                    low_at = i;
+                } else if bar.high >= bars[low_at].low * (1.0 + ratio) {
+                    swings.push(swing(low_at, false));
+                    trend = 'U';
+                    high_at = i;
+                }
+            }
+            _ => {
+                if bar.high > bars[high_at].high {
+                    high_at = i;
+                }
+                if bar.low < bars[low_at].low {
+                    low_at = i;
+                }
+                if low_at < high_at && bars[high_at].high >= bars[low_at].low * (1.0 + ratio) {
+                    swings.push(swing(low_at, false));
+                    trend = 'U';
+                } else if high_at < low_at && bars[low_at].low <= bars[high_at].high * (1.0 - ratio)
+                {
+                    swings.push(swing(high_at, true));
+                    trend = 'D';
+                }
+            }
+        }
+    }
+    swings
+}
+
+/// Returns zones of swing points clustered within `width`
+/// of each other, strongest first
+///
+/// Default `width` is half of the average bar range
+///
+/// Strength counts every touch weighted by how recent it
+/// is, from `0.5` for the first bar up to `1.0` for the last
+pub fn to_zones(bars: &[OHLC], swings: &[Swing], width: Option<f32>) -> Vec<Zone> {
+    let (Some(last), false) = (bars.last(), swings.is_empty()) else {
+        return vec![];
+    };
+    let width: f32 = width
+        .unwrap_or(bars.iter().map(|bar| bar.to_range()).sum::<f32>() / bars.len() as f32 / 2.0);
+
+    let mut sorted = swings.to_vec();
+    sorted.sort_by(|a, b| a.price.total_cmp(&b.price));
+
+    let weight = |swing: &Swing| 0.5 + 0.5 * swing.index as f32 / (bars.len() - 1).max(1) as f32;
+    let mut zones: Vec<Zone> = vec![];
+    for swing in &sorted {
+        match zones.last_mut() {
+            Some(zone) if swing.price - zone.low <= width => {
+                zone.high = swing.price;
+                zone.touches += 1;
+                zone.strength += weight(swing);
+            }
+            _ => zones.push(Zone {
+                low: swing.price,
+                high: swing.price,
+                touches: 1,
+                strength: weight(swing),
+                kind: 'R',
+            }),
+        }
+    }
+
+    for zone in &mut zones {
+        zone.kind = if (zone.low + zone.high) / 2.0 < last.close {
+            'S'
+        } else {
+            'R'
+        };
+    }
+    zones.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+    zones
+}
+
+/// Write the key levels with at least `touches` touches
+/// ordered from highest to lowest price
+///
+/// To prevent losing precision specfiy
+/// the level of `precision` after decimal needed
+pub fn show_zones<T: Write>(zones: &[Zone], touches: usize, mut output: T, precision: usize) {
+    let mut keys: Vec<&Zone> = zones
+        .iter()
+        .filter(|zone| zone.touches >= touches)
+        .collect();
+    keys.sort_by(|a, b| b.high.total_cmp(&a.high));
+    let _ = writeln!(output, "Key Levels: {}", keys.len());
+    for zone in keys {
+        let kind = if zone.kind == 'S' {
+            "Support"
+        } else {
+            "Resistance"
+        };
+        let _ = writeln!(
+            output,
+            " [{}] - {2:.1$} to {3:.1$}  [Touches] - {4}  [Strength] - {5:.2}",
+            kind, precision, zone.low, zone.high, zone.touches, zone.strength
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bars oscillating between 10 and 20 every 4 bars
+    fn waves(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let step = [0.0, 5.0, 10.0, 5.0][i % 4];
+                let base = 10.0 + step;
+                OHLC::new(base, base + 0.5, base - 0.5, base)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_fractals() {
+        let swings = to_fractals(&waves(9), 1);
+        assert_eq!(
+            swings,
+            vec![
+                Swing {
+                    index: 2,
+                    price: 20.5,
+                    high: true
+                },
+                Swing {
+                    index: 4,
+                    price: 9.5,
+                    high: false
+                },
+                Swing {
+                    index: 6,
+                    price: 20.5,
+                    high: true
+                },
+            ]
+        );
+        assert!(to_fractals(&waves(4), 2).is_empty());
+    }
+
+    #[test]
+    fn test_to_zigzag() {
+        let swings = to_zigzag(&waves(12), 20.0);
+        let points: Vec<(usize, bool)> = swings.iter().map(|s| (s.index, s.high)).collect();
+        assert_eq!(
+            points,
+            vec![
+                (0, false),
+                (2, true),
+                (4, false),
+                (6, true),
+                (8, false),
+                (10, true)
+            ]
+        );
+        assert!(to_zigzag(&waves(12), 200.0).is_empty());
+    }
+
+    #[test]
+    fn test_to_zones() {
+        let bars = waves(12);
+        let swings = to_zigzag(&bars, 20.0);
+        let zones = to_zones(&bars, &swings, None);
+        assert_eq!(zones.len(), 2);
+        // Recent highs make resistance the stronger zone
+        assert_eq!(zones[0].high, 20.5);
+        assert_eq!(zones[0].touches, 3);
+        assert_eq!(zones[0].kind, 'R');
+        assert_eq!(zones[1].low, 9.5);
+        assert_eq!(zones[1].touches, 3);
+        assert_eq!(zones[1].kind, 'S');
+        assert!(zones[0].strength > zones[1].strength);
+
+        // Narrow zones split every swing apart
+        let swings = vec![
+            Swing {
+                index: 1,
+                price: 10.0,
+                high: false,
+            },
+            Swing {
+                index: 3,
+                price: 10.4,
+                high: false,
+            },
+        ];
+        assert_eq!(to_zones(&bars, &swings, Some(0.5)).len(), 1);
+        assert_eq!(to_zones(&bars, &swings, Some(0.1)).len(), 2);
+    }
+
+    #[test]
+    fn test_show_zones() {
+        let bars = waves(12);
+        let zones = to_zones(&bars, &to_zigzag(&bars, 20.0), None);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_zones(&zones, 3, &mut output, 2);
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "Key Levels: 2\n [Resistance] - 20.50 to 20.50  [Touches] - 3  [Strength] - 2.32\n [Support] - 9.50 to 9.50  [Touches] - 3  [Strength] - 2.05\n"
+        );
+
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_zones(&zones, 4, &mut output, 2);
+        assert_eq!(output.into_inner(), b"Key Levels: 0\n");
+    }
+}
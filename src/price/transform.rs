@@ -0,0 +1,336 @@
+//! Alternative chart constructions built from a series of bars
+//!
+//! Heikin-Ashi, Renko and range bars come back as `OHLC` so
+//! every existing method works on them unchanged, while
+//! point-and-figure returns its own columns.
+
+use super::{trend, OHLC};
+
+/// Size of a Renko brick
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BoxSize {
+    /// Fixed price size
+    Fixed(f32),
+    /// Latest Average True Range (ATR) of a period
+    Atr(usize),
+}
+
+impl BoxSize {
+    /// Returns the price size of a box for a series of bars
+    pub fn to_size(&self, bars: &[OHLC]) -> Option<f32> {
+        let size = match self {
+            BoxSize::Fixed(size) => *size,
+            BoxSize::Atr(period) => *trend::to_atr(bars, *period).last()?,
+        };
+        if size > 0.0 {
+            Some(size)
+        } else {
+            None
+        }
+    }
+}
+
+/// A point-and-figure column of X (rising) or O (falling) boxes
+///
+/// `high` and `low` are the box boundaries the column spans
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Column {
+    pub rising: bool,
+    pub high: f32,
+    pub low: f32,
+    pub boxes: usize,
+}
+
+/// Returns the Heikin-Ashi candles of a series of bars
+pub fn to_heikin_ashi(bars: &[OHLC]) -> Vec<OHLC> {
+    let mut candles: Vec<OHLC> = vec![];
+    for bar in bars {
+        let close = (bar.open + bar.high + bar.low + bar.close) / 4.0;
+        let open = match candles.last() {
+            Some(prev) => (prev.open + prev.close) / 2.0,
+            None => (bar.open + bar.close) / 2.0,
+        };
+        candles.push(
+            OHLC::new(
+                open,
+                bar.high.max(open).max(close),
+                bar.low.min(open).min(close),
+                close,
+            )
+            .at(bar.time)
+            .with_volume(bar.volume),
+        );
+    }
+    candles
+}
+
+/// Returns the Renko bricks of the closes of a series of bars
+///
+/// A brick is added every time price closes a full box past
+/// the last brick, so a reversal needs two boxes of movement.
+/// Bricks take the time of the bar that completed them.
+/// Boxes too small to move a price of `f32` make no bricks
+pub fn to_renko(bars: &[OHLC], size: BoxSize) -> Vec<OHLC> {
+    let mut bricks: Vec<OHLC> = vec![];
+    let (Some(size), Some(first)) = (size.to_size(bars), bars.first()) else {
+        return bricks;
+    };
+
+    let (mut top, mut bottom) = (first.close, first.close);
+    for bar in &bars[1..] {
+        if top + size <= top || bottom - size >= bottom {
+            break;
+        }
+        let rising = ((bar.close - top) / size).floor();
+        for i in 0..rising.max(0.0) as usize {
+            let open = top + i as f32 * size;
+            bricks.push(OHLC::new(open, open + size, open, open + size).at(bar.time));
+        }
+        if rising >= 1.0 {
+            (bottom, top) = (top + (rising - 1.0) * size, top + rising * size);
+        }
+        let falling = ((bottom - bar.close) / size).floor();
+        for i in 0..falling.max(0.0) as usize {
+            let open = bottom - i as f32 * size;
+            bricks.push(OHLC::new(open, open, open - size, open - size).at(bar.time));
+        }
+        if falling >= 1.0 {
+            (top, bottom) = (bottom - (falling - 1.0) * size, bottom - falling * size);
+        }
+    }
+    bricks
+}
+
+/// Returns the range bars of a series of bars
+///
+/// Every bar spans exactly `range` in price except the last
+/// one which is still in progress. The path inside a source
+/// bar is assumed to visit the wick against its direction first.
+/// A range too small to move a price of `f32` never completes
+pub fn to_range_bars(bars: &[OHLC], range: f32) -> Vec<OHLC> {
+    let mut range_bars: Vec<OHLC> = vec![];
+    if range <= 0.0 {
+        return range_bars;
+    }
+
+    let mut current: Option<OHLC> = None;
+    for bar in bars {
+        let path = if bar.to_direction() == 'U' {
+            [bar.open, bar.low, bar.high, bar.close]
+        } else {
+            [bar.open, bar.high, bar.low, bar.close]
+        };
+        for price in path {
+            loop {
+                let Some(cur) = current.as_mut() else {
+                    current = Some(OHLC::new(price, price, price, price).at(bar.time));
+                    break;
+                };
+                let is_stuck = cur.low + range <= cur.low || cur.high - range >= cur.high;
+                let edge = if is_stuck {
+                    cur.high = cur.high.max(price);
+                    cur.low = cur.low.min(price);
+                    cur.close = price;
+                    break;
+                } else if price > cur.low + range {
+                    cur.low + range
+                } else if price < cur.high - range {
+                    cur.high - range
+                } else {
+                    cur.high = cur.high.max(price);
+                    cur.low = cur.low.min(price);
+                    cur.close = price;
+                    break;
+                };
+                cur.high = cur.high.max(edge);
+                cur.low = cur.low.min(edge);
+                cur.close = edge;
+                range_bars.push(OHLC::new(cur.open, cur.high, cur.low, cur.close).at(cur.time));
+                current = Some(OHLC::new(edge, edge, edge, edge).at(bar.time));
+            }
+        }
+    }
+    range_bars.extend(current);
+    range_bars
+}
+
+/// Returns the point-and-figure columns of the closes of a
+/// series of bars
+///
+/// Default `reversal` is: `3` boxes
+pub fn to_point_figure(bars: &[OHLC], size: BoxSize, reversal: Option<usize>) -> Vec<Column> {
+    let reversal: f32 = reversal.unwrap_or(3) as f32;
+    let mut columns: Vec<Column> = vec![];
+    let (Some(size), Some(first)) = (size.to_size(bars), bars.first()) else {
+        return columns;
+    };
+    let to_boxes = |distance: f32| (distance / size).floor();
+
+    let reference = first.close;
+    for bar in &bars[1..] {
+        let close = bar.close;
+        let Some(column) = columns.last_mut() else {
+            let boxes = to_boxes((close - reference).abs());
+            if boxes >= 1.0 {
+                let rising = close > reference;
+                let edge = if rising {
+                    reference + boxes * size
+                } else {
+                    reference - boxes * size
+                };
+                columns.push(Column {
+                    rising,
+                    high: reference.max(edge),
+                    low: reference.min(edge),
+                    boxes: boxes as usize,
+                });
+            }
+            continue;
+        };
+
+        if column.rising {
+            let extend = to_boxes(close - column.high);
+            let reverse = to_boxes(column.high - close);
+            if extend >= 1.0 {
+                column.high += extend * size;
+                column.boxes += extend as usize;
+            } else if reverse >= reversal {
+                let high = column.high;
+                columns.push(Column {
+                    rising: false,
+                    high,
+                    low: high - reverse * size,
+                    boxes: reverse as usize,
+                });
+            }
+        } else {
+            let extend = to_boxes(column.low - close);
+            let reverse = to_boxes(close - column.low);
+            if extend >= 1.0 {
+                column.low -= extend * size;
+                column.boxes += extend as usize;
+            } else if reverse >= reversal {
+                let low = column.low;
+                columns.push(Column {
+                    rising: true,
+                    high: low + reverse * size,
+                    low,
+                    boxes: reverse as usize,
+                });
+            }
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closes(prices: &[f32]) -> Vec<OHLC> {
+        prices
+            .iter()
+            .map(|price| OHLC::new(*price, *price, *price, *price))
+            .collect()
+    }
+
+    #[test]
+    fn test_to_heikin_ashi() {
+        let bars = vec![
+            OHLC::new(10.0, 12.0, 9.0, 11.0).at(60),
+            OHLC::new(11.0, 13.0, 10.0, 12.0).at(120),
+        ];
+        let candles = to_heikin_ashi(&bars);
+        assert_eq!(candles[0].open, 10.5);
+        assert_eq!(candles[0].close, 10.5);
+        assert_eq!(candles[0].high, 12.0);
+        assert_eq!(candles[0].low, 9.0);
+        assert_eq!(candles[1].open, 10.5);
+        assert_eq!(candles[1].close, 11.5);
+        assert_eq!(candles[1].to_direction(), 'U');
+        assert_eq!(candles[1].time, 120);
+        assert!(candles[1].is_weak(None));
+    }
+
+    #[test]
+    fn test_to_renko() {
+        let bars = closes(&[10.0, 11.2, 12.1, 11.5, 10.9, 9.8]);
+        let bricks = to_renko(&bars, BoxSize::Fixed(1.0));
+        let directions: Vec<char> = bricks.iter().map(|brick| brick.to_direction()).collect();
+        assert_eq!(directions, vec!['U', 'U', 'D']);
+        assert_eq!(bricks[2].open, 11.0);
+        assert_eq!(bricks[2].close, 10.0);
+        assert!(to_renko(&bars, BoxSize::Fixed(0.0)).is_empty());
+
+        // One large move makes every brick it spans
+        let bricks = to_renko(&closes(&[10.0, 15.5, 12.9]), BoxSize::Fixed(1.0));
+        let opens: Vec<f32> = bricks.iter().map(|brick| brick.open).collect();
+        assert_eq!(opens, vec![10.0, 11.0, 12.0, 13.0, 14.0, 14.0]);
+        // Boxes below the step of f32 at the price end at once
+        let bars = closes(&[60_000.0, 60_100.0]);
+        assert!(to_renko(&bars, BoxSize::Fixed(0.001)).is_empty());
+    }
+
+    #[test]
+    fn test_to_renko_atr() {
+        let bars = vec![
+            OHLC::new(10.0, 11.0, 9.0, 10.0),
+            OHLC::new(10.0, 11.0, 9.0, 12.5),
+            OHLC::new(12.5, 13.0, 11.0, 14.5),
+        ];
+        assert_eq!(BoxSize::Atr(2).to_size(&bars), Some(2.0));
+        assert_eq!(to_renko(&bars, BoxSize::Atr(2)).len(), 2);
+        assert!(to_renko(&bars, BoxSize::Atr(5)).is_empty());
+    }
+
+    #[test]
+    fn test_to_range_bars() {
+        let bars = vec![OHLC::new(10.0, 12.5, 9.5, 12.0)];
+        let range_bars = to_range_bars(&bars, 1.0);
+        let points: Vec<(f32, f32)> = range_bars.iter().map(|bar| (bar.open, bar.close)).collect();
+        assert_eq!(points, vec![(10.0, 10.5), (10.5, 11.5), (11.5, 12.0)]);
+        // Completed bars span exactly the range
+        assert_eq!(range_bars[0].to_range(), 1.0);
+        assert_eq!(range_bars[1].to_range(), 1.0);
+        assert!(to_range_bars(&bars, 0.0).is_empty());
+
+        // Ranges below the step of f32 at the price never complete
+        let bars = vec![OHLC::new(60_000.0, 60_100.0, 59_900.0, 60_050.0)];
+        let range_bars = to_range_bars(&bars, 0.001);
+        assert_eq!(range_bars.len(), 1);
+        assert_eq!(range_bars[0].to_range(), 200.0);
+    }
+
+    #[test]
+    fn test_to_point_figure() {
+        let bars = closes(&[10.0, 13.2, 14.1, 13.0, 11.0, 10.5, 12.6, 14.0]);
+        let columns = to_point_figure(&bars, BoxSize::Fixed(1.0), None);
+        assert_eq!(
+            columns,
+            vec![
+                Column {
+                    rising: true,
+                    high: 14.0,
+                    low: 10.0,
+                    boxes: 4
+                },
+                Column {
+                    rising: false,
+                    high: 14.0,
+                    low: 11.0,
+                    boxes: 3
+                },
+                Column {
+                    rising: true,
+                    high: 14.0,
+                    low: 11.0,
+                    boxes: 3
+                },
+            ]
+        );
+        assert_eq!(
+            to_point_figure(&bars, BoxSize::Fixed(1.0), Some(5)).len(),
+            1
+        );
+    }
+}
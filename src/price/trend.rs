@@ -0,0 +1,511 @@
+//! Multi-bar trend strength indicators
+//!
+//! Every indicator consumes a series of bars such as
+//! `History::daily` and returns values aligned to the
+//! end of the series, so the last value always belongs
+//! to the latest bar.
+
+use super::{write_direction, OHLC};
+use std::io::Write;
+
+/// Directional Movement Index (DMI) with its
+/// Average Directional Index (ADX)
+#[derive(Default, Clone, Copy)]
+pub struct DMI {
+    pub plus_di: f32,
+    pub minus_di: f32,
+    pub adx: f32,
+}
+
+/// Aroon up/down lines and their oscillator
+#[derive(Default, Clone, Copy)]
+pub struct Aroon {
+    pub up: f32,
+    pub down: f32,
+    pub oscillator: f32,
+}
+
+/// Supertrend line and the side of price it is on
+///
+/// `direction` is `'U'` when the line sits below price
+/// otherwise `'D'`
+#[derive(Default, Clone, Copy)]
+pub struct Supertrend {
+    pub value: f32,
+    pub direction: char,
+}
+
+/// Returns the Simple Moving Average (SMA) of closes
+///
+/// The first value belongs to the bar at `period - 1`
+pub fn to_sma(bars: &[OHLC], period: usize) -> Vec<f32> {
+    if period == 0 {
+        return vec![];
+    }
+    bars.windows(period)
+        .map(|window| window.iter().map(|bar| bar.close).sum::<f32>() / period as f32)
+        .collect()
+}
+
+/// Returns the true range of every bar
+///
+/// The first bar has no previous close so its
+/// true range is the bar range
+pub fn to_true_range(bars: &[OHLC]) -> Vec<f32> {
+    bars.iter()
+        .enumerate()
+        .map(|(i, bar)| {
+            if i == 0 {
+                bar.to_range()
+            } else {
+                let prev_close = bars[i - 1].close;
+                bar.to_range()
+                    .max((bar.high - prev_close).abs())
+                    .max((bar.low - prev_close).abs())
+            }
+        })
+        .collect()
+}
+
+/// Returns the Average True Range (ATR) using Wilder's smoothing
+///
+/// The first value belongs to the bar at `period - 1`
+pub fn to_atr(bars: &[OHLC], period: usize) -> Vec<f32> {
+    let tr = to_true_range(bars);
+    if period == 0 || tr.len() < period {
+        return vec![];
+    }
+
+    let mut atr = tr[..period].iter().sum::<f32>() / period as f32;
+    let mut values = vec![atr];
+    for range in &tr[period..] {
+        atr = (atr * (period - 1) as f32 + range) / period as f32;
+        values.push(atr);
+    }
+    values
+}
+
+/// Returns the +DI, -DI and ADX using Wilder's smoothing
+///
+/// Needs at least `2 * period` bars, the first value
+/// belongs to the bar at `2 * period - 1`
+pub fn to_dmi(bars: &[OHLC], period: usize) -> Vec<DMI> {
+    if period == 0 || bars.len() < 2 * period {
+        return vec![];
+    }
+
+    let tr = to_true_range(bars);
+    let mut plus_dm = vec![0.0f32; bars.len()];
+    let mut minus_dm = vec![0.0f32; bars.len()];
+    for i in 1..bars.len() {
+        let up = bars[i].high - bars[i - 1].high;
+        let down = bars[i - 1].low - bars[i].low;
+        if up > down && up > 0.0 {
+            plus_dm[i] = up;
+        }
+        if down > up && down > 0.0 {
+            minus_dm[i] = down;
+        }
+    }
+
+    let mut smooth_tr: f32 = tr[1..=period].iter().sum();
+    let mut smooth_plus: f32 = plus_dm[1..=period].iter().sum();
+    let mut smooth_minus: f32 = minus_dm[1..=period].iter().sum();
+
+    let mut lines = vec![];
+    for i in period..bars.len() {
+        if i > period {
+            smooth_tr += tr[i] - smooth_tr / period as f32;
+            smooth_plus += plus_dm[i] - smooth_plus / period as f32;
+            smooth_minus += minus_dm[i] - smooth_minus / period as f32;
+        }
+        let (plus_di, minus_di) = if smooth_tr == 0.0 {
+            (0.0, 0.0)
+        } else {
+            (
+                100.0 * smooth_plus / smooth_tr,
+                100.0 * smooth_minus / smooth_tr,
+            )
+        };
+        let dx = if plus_di + minus_di == 0.0 {
+            0.0
+        } else {
+            100.0 * (plus_di - minus_di).abs() / (plus_di + minus_di)
+        };
+        lines.push((plus_di, minus_di, dx));
+    }
+
+    let mut adx = lines[..period].iter().map(|line| line.2).sum::<f32>() / period as f32;
+    let mut dmi = vec![];
+    for (i, (plus_di, minus_di, dx)) in lines.iter().enumerate().skip(period - 1) {
+        if i >= period {
+            adx = (adx * (period - 1) as f32 + dx) / period as f32;
+        }
+        dmi.push(DMI {
+            plus_di: *plus_di,
+            minus_di: *minus_di,
+            adx,
+        });
+    }
+    dmi
+}
+
+/// Returns the Aroon up, down and oscillator
+///
+/// The first value belongs to the bar at `period`
+pub fn to_aroon(bars: &[OHLC], period: usize) -> Vec<Aroon> {
+    if period == 0 || bars.len() <= period {
+        return vec![];
+    }
+
+    bars.windows(period + 1)
+        .map(|window| {
+            // Latest extreme wins when tied
+            let mut high_at = 0;
+            let mut low_at = 0;
+            for (i, bar) in window.iter().enumerate() {
+                if bar.high >= window[high_at].high {
+                    high_at = i;
+                }
+                if bar.low <= window[low_at].low {
+                    low_at = i;
+                }
+            }
+            let up = 100.0 * high_at as f32 / period as f32;
+            let down = 100.0 * low_at as f32 / period as f32;
+            Aroon {
+                up,
+                down,
+                oscillator: up - down,
+            }
+        })
+        .collect()
+}
+
+/// Returns the Parabolic Stop and Reverse (SAR)
+///
+/// Default `step` is: `0.02`
+///
+/// Default `max` is: `0.2`
+///
+/// The first value belongs to the second bar
+pub fn to_sar(bars: &[OHLC], step: Option<f32>, max: Option<f32>) -> Vec<f32> {
+    let step: f32 = step.unwrap_or(0.02);
+    let max: f32 = max.unwrap_or(0.2);
+    if bars.len() < 2 {
+        return vec![];
+    }
+
+    let mut up = bars[1].close >= bars[0].close;
+    let (mut sar, mut extreme) = if up {
+        (bars[0].low, bars[0].high)
+    } else {
+        (bars[0].high, bars[0].low)
+    };
+    let mut factor = step;
+
+    let mut values = vec![];
+    for i in 1..bars.len() {
+        let bar = &bars[i];
+        let prev = &bars[i - 1];
+        let prev2 = &bars[i.saturating_sub(2)];
+        sar += factor * (extreme - sar);
+        if up {
+            // SAR can never rise above the previous lows
+            sar = sar.min(prev.low).min(prev2.low);
+            if bar.low < sar {
+                up = false;
+                sar = extreme;
+                extreme = bar.low;
+                factor = step;
+            } else if bar.high > extreme {
+                extreme = bar.high;
+                factor = (factor + step).min(max);
+            }
+        } else {
+            // SAR can never fall below the previous highs
+            sar = sar.max(prev.high).max(prev2.high);
+            if bar.high > sar {
+                up = true;
+                sar = extreme;
+                extreme = bar.high;
+                factor = step;
+            } else if bar.low < extreme {
+                extreme = bar.low;
+                factor = (factor + step).min(max);
+            }
+        }
+        values.push(sar);
+    }
+    values
+}
+
+/// Returns the Supertrend line
+///
+/// Default `multiplier` is: `3.0`
+///
+/// The first value belongs to the bar at `period - 1`
+pub fn to_supertrend(bars: &[OHLC], period: usize, multiplier: Option<f32>) -> Vec<Supertrend> {
+    let multiplier: f32 = multiplier.unwrap_or(3.0);
+    let atr = to_atr(bars, period);
+    if atr.is_empty() {
+        return vec![];
+    }
+
+    let mut upper = f32::MAX;
+    let mut lower = f32::MIN;
+    let mut direction = 'D';
+    let mut lines = vec![];
+    for (i, range) in atr.iter().enumerate() {
+        let index = i + period - 1;
+        let bar = &bars[index];
+        let mid = (bar.high + bar.low) / 2.0;
+        let basic_upper = mid + multiplier * range;
+        let basic_lower = mid - multiplier * range;
+
+        // Bands only tighten unless price closed through them
+        let prev_close = if index > 0 {
+            bars[index - 1].close
+        } else {
+            bar.close
+        };
+        upper = if i == 0 || basic_upper < upper || prev_close > upper {
+            basic_upper
+        } else {
+            upper
+        };
+        lower = if i == 0 || basic_lower > lower || prev_close < lower {
+            basic_lower
+        } else {
+            lower
+        };
+
+        direction = if direction == 'D' && bar.close > upper {
+            'U'
+        } else if direction == 'U' && bar.close < lower {
+            'D'
+        } else {
+            direction
+        };
+        lines.push(Supertrend {
+            value: if direction == 'U' { lower } else { upper },
+            direction,
+        });
+    }
+    lines
+}
+
+/// Returns the trend of a series of bars
+///
+/// Trend can be one of the following:
+///
+/// `'D'` = Downtrend
+///
+/// `'U'` = Uptrend
+///
+/// `'N'` = Sideways or not enough bars
+///
+/// A trend needs ADX above the gauge level and at least
+/// three of DMI, Aroon, SAR and Supertrend agreeing
+///
+/// Default level is: `20.0`
+pub fn to_trend(bars: &[OHLC], period: usize, gauge: Option<f32>) -> char {
+    let gauge: f32 = gauge.unwrap_or(20.0);
+    let (Some(dmi), Some(aroon), Some(sar), Some(supertrend), Some(last)) = (
+        to_dmi(bars, period).last().copied(),
+        to_aroon(bars, period).last().copied(),
+        to_sar(bars, None, None).last().copied(),
+        to_supertrend(bars, period, None).last().copied(),
+        bars.last(),
+    ) else {
+        return 'N';
+    };
+    if dmi.adx < gauge {
+        return 'N';
+    }
+
+    let votes = [
+        dmi.plus_di.total_cmp(&dmi.minus_di),
+        aroon.oscillator.total_cmp(&0.0),
+        last.close.total_cmp(&sar),
+        if supertrend.direction == 'U' {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Less
+        },
+    ];
+    let up = votes.iter().filter(|vote| vote.is_gt()).count();
+    let down = votes.iter().filter(|vote| vote.is_lt()).count();
+    if up >= 3 {
+        'U'
+    } else if down >= 3 {
+        'D'
+    } else {
+        'N'
+    }
+}
+
+/// Write the trend summary of a series of bars
+///
+/// To prevent losing precision specfiy
+/// the level of `precision` after decimal needed
+pub fn show_trend<T: Write>(bars: &[OHLC], mut output: T, period: usize, precision: usize) {
+    let _ = write!(output, "Trend: ");
+    write_direction(&mut output, to_trend(bars, period, None));
+    if let Some(dmi) = to_dmi(bars, period).last() {
+        let _ = write!(output, "  [ADX] - {:.2}", dmi.adx);
+        let _ = write!(output, "  [+DI] - {:.2}", dmi.plus_di);
+        let _ = write!(output, "  [-DI] - {:.2}", dmi.minus_di);
+    }
+    if let Some(aroon) = to_aroon(bars, period).last() {
+        let _ = write!(output, "  [Aroon] - {:.2}", aroon.oscillator);
+    }
+    if let Some(sar) = to_sar(bars, None, None).last() {
+        let _ = write!(output, "  [SAR] - {1:.0$}", precision, sar);
+    }
+    if let Some(supertrend) = to_supertrend(bars, period, None).last() {
+        let _ = write!(
+            output,
+            "  [Supertrend] - {1:.0$}",
+            precision, supertrend.value
+        );
+    }
+    let _ = writeln!(output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rising(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let base = 10.0 + i as f32;
+                OHLC::new(base, base + 1.5, base - 0.5, base + 1.0)
+            })
+            .collect()
+    }
+
+    fn falling(count: usize) -> Vec<OHLC> {
+        (0..count)
+            .map(|i| {
+                let base = 100.0 - i as f32;
+                OHLC::new(base, base + 0.5, base - 1.5, base - 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_sma() {
+        let bars = rising(4);
+        assert_eq!(to_sma(&bars, 2), vec![11.5, 12.5, 13.5]);
+        assert!(to_sma(&bars, 5).is_empty());
+        assert!(to_sma(&bars, 0).is_empty());
+    }
+
+    #[test]
+    fn test_to_true_range() {
+        let bars = vec![
+            OHLC::new(5.0, 6.0, 4.0, 5.5),
+            OHLC::new(8.0, 9.0, 7.5, 8.5),
+            OHLC::new(3.0, 4.0, 2.0, 3.5),
+        ];
+        assert_eq!(to_true_range(&bars), vec![2.0, 3.5, 6.5]);
+    }
+
+    #[test]
+    fn test_to_atr() {
+        let bars = vec![
+            OHLC::new(5.0, 6.0, 4.0, 5.5),
+            OHLC::new(5.0, 7.0, 4.0, 5.5),
+            OHLC::new(5.0, 6.0, 5.0, 5.5),
+        ];
+        assert_eq!(to_atr(&bars, 2), vec![2.5, 1.75]);
+        assert!(to_atr(&bars, 4).is_empty());
+    }
+
+    #[test]
+    fn test_to_dmi() {
+        let up = to_dmi(&rising(30), 14);
+        assert_eq!(up.len(), 30 - 27);
+        assert!(up[0].plus_di > up[0].minus_di);
+        assert!(up[0].adx > 90.0);
+
+        let down = to_dmi(&falling(30), 14);
+        assert!(down[0].minus_di > down[0].plus_di);
+        assert!(to_dmi(&rising(27), 14).is_empty());
+    }
+
+    #[test]
+    fn test_to_aroon() {
+        let up = to_aroon(&rising(10), 5);
+        assert_eq!(up.len(), 5);
+        assert_eq!(up[0].up, 100.0);
+        assert_eq!(up[0].down, 0.0);
+        assert_eq!(up[0].oscillator, 100.0);
+
+        let down = to_aroon(&falling(10), 5);
+        assert_eq!(down[4].oscillator, -100.0);
+    }
+
+    #[test]
+    fn test_to_sar() {
+        let bars = rising(10);
+        let sar = to_sar(&bars, None, None);
+        assert_eq!(sar.len(), 9);
+        assert!(sar.iter().zip(&bars[1..]).all(|(sar, bar)| *sar < bar.low));
+
+        let bars = falling(10);
+        let sar = to_sar(&bars, None, None);
+        assert!(sar.iter().zip(&bars[1..]).all(|(sar, bar)| *sar > bar.high));
+    }
+
+    #[test]
+    fn test_to_sar_reversal() {
+        let mut bars = rising(6);
+        bars.push(OHLC::new(15.0, 15.5, 5.0, 6.0));
+        let sar = to_sar(&bars, None, None);
+        assert!(*sar.last().unwrap() > bars.last().unwrap().high);
+    }
+
+    #[test]
+    fn test_to_supertrend() {
+        let up = to_supertrend(&rising(20), 5, None);
+        assert_eq!(up.len(), 16);
+        assert_eq!(up.last().unwrap().direction, 'U');
+
+        let down = to_supertrend(&falling(20), 5, None);
+        assert_eq!(down.last().unwrap().direction, 'D');
+    }
+
+    #[test]
+    fn test_to_trend() {
+        assert_eq!(to_trend(&rising(40), 14, None), 'U');
+        assert_eq!(to_trend(&falling(40), 14, None), 'D');
+        assert_eq!(to_trend(&rising(10), 14, None), 'N');
+
+        let flat: Vec<OHLC> = (0..40)
+            .map(|i| {
+                let shift = if i % 2 == 0 { 0.5 } else { -0.5 };
+                OHLC::new(10.0, 11.0 + shift, 9.0 + shift, 10.0)
+            })
+            .collect();
+        assert_eq!(to_trend(&flat, 14, None), 'N');
+    }
+
+    #[test]
+    fn test_show_trend() {
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_trend(&rising(2), &mut output, 14, 2);
+        assert_eq!(
+            output.into_inner(),
+            b"Trend: \x1b[38;2;255;255;0m\xE2\xAC\x8C Side\x1b[0m  [SAR] - 9.50\n"
+        );
+
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_trend(&rising(40), &mut output, 14, 2);
+        let text = String::from_utf8(output.into_inner()).unwrap();
+        assert!(text.starts_with("Trend: \x1b[38;2;0;255;0m⬆ Up\x1b[0m  [ADX] - "));
+        assert!(text.contains("[Supertrend] - "));
+    }
+}
@@ -0,0 +1,594 @@
+//! Data quality checks of bars
+//!
+//! Bars from a vendor may break the rules of an `OHLC` such
+//! as a low above the high, repeat or go back in time, skip
+//! sessions the exchange traded or jump on a bad print. A
+//! `Validator` finds them as a `Report` and repairs bars by
+//! dropping, forward-filling or clamping them.
+//!
+//! Outliers are closes moving more than `outlier` times the
+//! median absolute deviation of every close to close return.
+
+use super::{History, OHLC};
+use chrono::{DateTime, Datelike, NaiveDate, Weekday};
+use std::io::Write;
+
+/// Deviations a return is away from the median before it
+/// is an outlier when none are given
+pub const DEFAULT_OUTLIER: f32 = 10.0;
+
+/// Smallest deviation of returns so flat bars still find
+/// outliers
+const MIN_DEVIATION: f32 = 1e-3;
+
+/// Days an exchange has a session
+pub trait Sessions {
+    /// Returns `true` if the exchange trades on a date
+    fn is_session(&self, date: NaiveDate) -> bool;
+}
+
+/// Sessions of every weekday such as a stock exchange
+pub struct Weekdays;
+
+impl Sessions for Weekdays {
+    fn is_session(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+}
+
+/// Sessions of every day such as a crypto exchange
+pub struct Always;
+
+impl Sessions for Always {
+    fn is_session(&self, _date: NaiveDate) -> bool {
+        true
+    }
+}
+
+/// Returns the sessions of bars: every day when any bar
+/// trades on a weekend otherwise weekdays
+pub fn to_sessions(bars: &[OHLC]) -> Box<dyn Sessions> {
+    match bars
+        .iter()
+        .any(|bar| !Weekdays.is_session(to_date(bar.time)))
+    {
+        true => Box::new(Always),
+        false => Box::new(Weekdays),
+    }
+}
+
+/// Problem of a bar
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Issue {
+    /// A price is zero, negative or not a number
+    NonPositive,
+    /// Low above high or open or close outside of them
+    OutOfRange,
+    /// Same time as the bar before it
+    Duplicate,
+    /// Older than a bar before it
+    OutOfOrder,
+    /// Sessions without a bar before this one
+    Missing(usize),
+    /// Close too far from the close before it
+    Outlier,
+}
+
+impl Issue {
+    /// Returns the issue as readable text
+    pub fn to_name(self) -> String {
+        match self {
+            Issue::NonPositive => String::from("price not positive"),
+            Issue::OutOfRange => String::from("price outside the high-low range"),
+            Issue::Duplicate => String::from("duplicate time"),
+            Issue::OutOfOrder => String::from("out of order"),
+            Issue::Missing(1) => String::from("missing 1 session before"),
+            Issue::Missing(sessions) => format!("missing {} sessions before", sessions),
+            Issue::Outlier => String::from("outlier"),
+        }
+    }
+}
+
+/// An issue of the bar at `index`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Finding {
+    pub index: usize,
+    /// Unix timestamp (seconds) of the bar
+    pub time: i64,
+    pub issue: Issue,
+}
+
+/// Every issue found in bars
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Report {
+    /// Number of bars checked
+    pub bars: usize,
+    /// Issues ordered by index
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Returns `true` if no issue was found
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns the number of bars breaking the rules of an
+    /// `OHLC` or of time order
+    ///
+    /// Missing sessions and outliers may be real so they
+    /// are left out
+    pub fn to_broken(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| !matches!(finding.issue, Issue::Missing(_) | Issue::Outlier))
+            .count()
+    }
+
+    /// Print every issue with the date of its bar
+    pub fn show_report<T: Write>(&self, mut output: T, ticker: &str) {
+        let _ = writeln!(
+            output,
+            "{}: {} issues in {} bars",
+            ticker,
+            self.findings.len(),
+            self.bars
+        );
+        for finding in &self.findings {
+            let format = match finding.time % 86_400 {
+                0 => "%Y-%m-%d",
+                _ => "%Y-%m-%d %H:%M",
+            };
+            let date = DateTime::from_timestamp(finding.time, 0)
+                .map(|date| date.format(format).to_string())
+                .unwrap_or(String::from("-"));
+            let _ = writeln!(output, "  {} {}", date, finding.issue.to_name());
+        }
+    }
+}
+
+/// Way bars with issues are repaired
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Repair {
+    /// Drop bars with issues
+    Drop,
+    /// Replace bars with issues and missing daily sessions
+    /// by the close before them
+    Fill,
+    /// Move prices back into the range and outliers back
+    /// to the largest move allowed
+    Clamp,
+}
+
+/// Returns the repair of a value such as `drop`, `fill` or
+/// `clamp`
+///
+/// An empty value only reports issues
+pub fn to_repair(value: &str) -> Result<Option<Repair>, String> {
+    match value.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "drop" => Ok(Some(Repair::Drop)),
+        "fill" => Ok(Some(Repair::Fill)),
+        "clamp" => Ok(Some(Repair::Clamp)),
+        value => Err(format!(
+            "invalid repair '{}'; possibilities: 'drop' 'fill' 'clamp'",
+            value
+        )),
+    }
+}
+
+/// Checker of the quality of bars
+pub struct Validator {
+    /// Deviations a return is away from the median before it
+    /// is an outlier
+    pub outlier: f32,
+    /// Days with a session, found from the bars when `None`
+    pub sessions: Option<Box<dyn Sessions>>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {
+            outlier: DEFAULT_OUTLIER,
+            sessions: None,
+        }
+    }
+
+    /// Check missing sessions against `sessions`
+    pub fn with_sessions<S: Sessions + 'static>(mut self, sessions: S) -> Self {
+        self.sessions = Some(Box::new(sessions));
+        self
+    }
+
+    /// Get every issue of bars ordered from oldest to newest
+    ///
+    /// Missing sessions are only checked for bars of a day
+    /// or shorter
+    pub fn validate(&self, bars: &[OHLC]) -> Report {
+        let mut findings = vec![];
+        let mut newest = i64::MIN;
+        // Bars in time order without broken ones
+        let mut valid: Vec<(usize, &OHLC)> = vec![];
+        for (index, bar) in bars.iter().enumerate() {
+            let mut to_finding = |issue: Issue| {
+                findings.push(Finding {
+                    index,
+                    time: bar.time,
+                    issue,
+                })
+            };
+            let is_ordered = match bar.time.cmp(&newest) {
+                std::cmp::Ordering::Equal => {
+                    to_finding(Issue::Duplicate);
+                    false
+                }
+                std::cmp::Ordering::Less => {
+                    to_finding(Issue::OutOfOrder);
+                    false
+                }
+                std::cmp::Ordering::Greater => true,
+            };
+            newest = newest.max(bar.time);
+            match to_price_issue(bar) {
+                Some(issue) => to_finding(issue),
+                None if is_ordered => valid.push((index, bar)),
+                None => {}
+            }
+        }
+
+        // Broken bars still show the exchange had a session
+        let mut times: Vec<(usize, i64)> = bars.iter().map(|bar| bar.time).enumerate().collect();
+        times.sort_by_key(|(_, time)| *time);
+        times.dedup_by_key(|(_, time)| *time);
+        let inferred;
+        let sessions = match self.sessions.as_deref() {
+            Some(sessions) => sessions,
+            None => {
+                inferred = to_sessions(bars);
+                inferred.as_ref()
+            }
+        };
+        let interval = times.windows(2).map(|pair| pair[1].1 - pair[0].1).min();
+        if interval.is_some_and(|interval| interval <= 86_400) {
+            for pair in times.windows(2) {
+                let missing = to_missing(pair[0].1, pair[1].1, sessions).len();
+                if missing > 0 {
+                    findings.push(Finding {
+                        index: pair[1].0,
+                        time: pair[1].1,
+                        issue: Issue::Missing(missing),
+                    });
+                }
+            }
+        }
+        let ordered: Vec<OHLC> = valid.iter().map(|(_, bar)| (*bar).clone()).collect();
+        for (index, _) in self.to_outliers(&ordered) {
+            let (index, bar) = valid[index];
+            findings.push(Finding {
+                index,
+                time: bar.time,
+                issue: Issue::Outlier,
+            });
+        }
+        findings.sort_by_key(|finding| finding.index);
+        Report {
+            bars: bars.len(),
+            findings,
+        }
+    }
+
+    /// Returns bars ordered from oldest to newest with every
+    /// issue repaired
+    ///
+    /// Bars are put in time order keeping the first of bars
+    /// at the same time. Bars with a price that is not
+    /// positive are dropped unless filled
+    pub fn repair(&self, bars: &[OHLC], repair: Repair) -> Vec<OHLC> {
+        let mut ordered = bars.to_vec();
+        ordered.sort_by_key(|bar| bar.time);
+        ordered.dedup_by_key(|bar| bar.time);
+
+        let outliers = self.to_outliers(
+            &ordered
+                .iter()
+                .filter(|bar| to_price_issue(bar).is_none())
+                .cloned()
+                .collect::<Vec<OHLC>>(),
+        );
+        let mut outliers = outliers.into_iter().peekable();
+        let inferred;
+        let sessions = match self.sessions.as_deref() {
+            Some(sessions) => sessions,
+            None => {
+                inferred = to_sessions(&ordered);
+                inferred.as_ref()
+            }
+        };
+        let is_daily = super::resample::to_interval(&ordered) == Some(86_400);
+
+        let mut repaired: Vec<OHLC> = vec![];
+        let mut valid = 0;
+        for bar in ordered {
+            let issue = to_price_issue(&bar);
+            let outlier = match issue {
+                Some(_) => None,
+                None => {
+                    valid += 1;
+                    outliers.next_if(|(index, _)| *index == valid - 1)
+                }
+            };
+            let previous = repaired.last().cloned();
+            if let (Repair::Fill, true, Some(previous)) = (repair, is_daily, &previous) {
+                for date in to_missing(previous.time, bar.time, sessions) {
+                    let time = to_midnight(date) + previous.time.rem_euclid(86_400);
+                    repaired.push(to_flat(previous.close, time));
+                }
+            }
+            match (repair, issue, outlier, previous) {
+                (_, None, None, _) => repaired.push(bar),
+                (Repair::Drop, _, _, _) => {}
+                (Repair::Fill, _, _, Some(previous)) => {
+                    repaired.push(to_flat(previous.close, bar.time))
+                }
+                (Repair::Clamp, Some(Issue::OutOfRange), _, _) => {
+                    let high = bar.open.max(bar.high).max(bar.low).max(bar.close);
+                    let low = bar.open.min(bar.high).min(bar.low).min(bar.close);
+                    repaired.push(OHLC { high, low, ..bar });
+                }
+                (Repair::Clamp, None, Some((_, bound)), _) => {
+                    let clamp = |price: f32| match bound > bar.close {
+                        true => price.max(bound),
+                        false => price.min(bound),
+                    };
+                    repaired.push(OHLC {
+                        open: clamp(bar.open),
+                        high: clamp(bar.high),
+                        low: clamp(bar.low),
+                        close: bound,
+                        ..bar
+                    });
+                }
+                _ => {}
+            }
+        }
+        repaired
+    }
+
+    /// Returns the index and the closest allowed close of
+    /// every outlier of bars in time order
+    ///
+    /// A close jumping away and back is a single bad print
+    /// while a close staying away starts a new level
+    fn to_outliers(&self, bars: &[OHLC]) -> Vec<(usize, f32)> {
+        let returns: Vec<f32> = bars
+            .windows(2)
+            .map(|pair| (pair[1].close / pair[0].close).ln())
+            .collect();
+        if returns.is_empty() {
+            return vec![];
+        }
+        let median = to_median(returns.clone());
+        let deviation = to_median(
+            returns
+                .iter()
+                .map(|change| (change - median).abs())
+                .collect(),
+        );
+        let limit = self.outlier * (1.4826 * deviation).max(MIN_DEVIATION);
+        let is_outlier = |from: f32, to: f32| ((to / from).ln() - median).abs() > limit;
+
+        let mut outliers = vec![];
+        // Close the next one is compared to
+        let mut last = 0;
+        for index in 1..bars.len() {
+            let close = bars[last].close;
+            if !is_outlier(close, bars[index].close) {
+                last = index;
+                continue;
+            }
+            let change = (bars[index].close / close).ln() - median;
+            let bound = close * (median + limit.copysign(change)).exp();
+            outliers.push((index, bound));
+            let is_back = bars
+                .get(index + 1)
+                .is_some_and(|next| !is_outlier(close, next.close));
+            if !is_back {
+                last = index;
+            }
+        }
+        outliers
+    }
+}
+
+impl History {
+    /// Returns the history with the bars of every timeframe
+    /// repaired, see `Validator::repair`
+    pub fn to_repaired(&self, validator: &Validator, repair: Repair) -> History {
+        let to_bars = |bars: &Vec<OHLC>| validator.repair(bars, repair);
+        History::new(
+            to_bars(&self.hourly),
+            to_bars(&self.daily),
+            to_bars(&self.weekly),
+            to_bars(&self.monthly),
+            to_bars(&self.yearly),
+            to_bars(&self.timeframe),
+        )
+    }
+}
+
+/// Returns the issue of the prices of a bar
+fn to_price_issue(bar: &OHLC) -> Option<Issue> {
+    let prices = [bar.open, bar.high, bar.low, bar.close];
+    if prices
+        .iter()
+        .any(|price| !price.is_finite() || *price <= 0.0)
+    {
+        return Some(Issue::NonPositive);
+    }
+    let is_inside = |price: f32| bar.low <= price && price <= bar.high;
+    match bar.low <= bar.high && is_inside(bar.open) && is_inside(bar.close) {
+        true => None,
+        false => Some(Issue::OutOfRange),
+    }
+}
+
+/// Returns the dates of sessions strictly between two times
+fn to_missing(from: i64, to: i64, sessions: &dyn Sessions) -> Vec<NaiveDate> {
+    let (from, to) = (to_date(from), to_date(to));
+    from.iter_days()
+        .skip(1)
+        .take_while(|date| *date < to)
+        .filter(|date| sessions.is_session(*date))
+        .collect()
+}
+
+/// Returns the UTC date of a Unix timestamp
+fn to_date(time: i64) -> NaiveDate {
+    DateTime::from_timestamp(time, 0)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Returns the Unix timestamp of the start of a UTC date
+fn to_midnight(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc()
+        .timestamp()
+}
+
+/// Returns a bar without any trading at a price
+fn to_flat(price: f32, time: i64) -> OHLC {
+    OHLC::new(price, price, price, price).at(time)
+}
+
+/// Returns the median of numbers
+fn to_median(mut numbers: Vec<f32>) -> f32 {
+    numbers.sort_by(f32::total_cmp);
+    let middle = numbers.len() / 2;
+    match numbers.len() % 2 {
+        0 => (numbers[middle - 1] + numbers[middle]) / 2.0,
+        _ => numbers[middle],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-11, a Monday
+    const MONDAY: i64 = 1_710_115_200;
+
+    fn bar(day: i64, close: f32) -> OHLC {
+        OHLC::new(close, close + 1.0, close - 1.0, close).at(MONDAY + day * 86_400)
+    }
+
+    fn bars() -> Vec<OHLC> {
+        vec![
+            bar(0, 100.0),
+            bar(1, 101.0),
+            // Low above high
+            OHLC::new(100.0, 99.0, 101.0, 100.0).at(MONDAY + 2 * 86_400),
+            bar(3, 102.0),
+            // Thursday again
+            bar(3, 103.0),
+            // Friday is missing and the weekend is closed
+            bar(7, 101.0),
+            // A bad print
+            bar(8, 1_000.0),
+            bar(9, 102.0),
+            bar(1, 101.0),
+            OHLC::new(0.0, 0.0, 0.0, 0.0).at(MONDAY + 10 * 86_400),
+        ]
+    }
+
+    #[test]
+    fn test_validate() {
+        let report = Validator::new().validate(&bars());
+        let issues: Vec<(usize, Issue)> = report
+            .findings
+            .iter()
+            .map(|finding| (finding.index, finding.issue))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (2, Issue::OutOfRange),
+                (4, Issue::Duplicate),
+                (5, Issue::Missing(1)),
+                (6, Issue::Outlier),
+                (8, Issue::OutOfOrder),
+                (9, Issue::NonPositive),
+            ]
+        );
+        assert_eq!(report.to_broken(), 4);
+
+        // Crypto trades every day
+        let report = Validator::new().with_sessions(Always).validate(&bars());
+        assert!(report
+            .findings
+            .iter()
+            .any(|finding| finding.issue == Issue::Missing(3)));
+        assert!(Validator::new().validate(&bars()[..2]).is_clean());
+    }
+
+    #[test]
+    fn test_show_report() {
+        let mut output: Vec<u8> = vec![];
+        Validator::new()
+            .validate(&bars()[..3])
+            .show_report(&mut output, "AAPL");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "AAPL: 1 issues in 3 bars\n  2024-03-13 price outside the high-low range\n"
+        );
+    }
+
+    #[test]
+    fn test_repair() {
+        let validator = Validator::new();
+        let closes = |bars: Vec<OHLC>| -> Vec<f32> { bars.iter().map(|bar| bar.close).collect() };
+
+        let repaired = validator.repair(&bars(), Repair::Drop);
+        assert_eq!(
+            closes(repaired.clone()),
+            [100.0, 101.0, 102.0, 101.0, 102.0]
+        );
+        assert_eq!(validator.validate(&repaired).to_broken(), 0);
+
+        // Friday is filled with Thursday's close
+        let repaired = validator.repair(&bars(), Repair::Fill);
+        assert_eq!(
+            closes(repaired.clone()),
+            [100.0, 101.0, 101.0, 102.0, 102.0, 101.0, 101.0, 102.0, 102.0]
+        );
+        assert_eq!(repaired[4].time, MONDAY + 4 * 86_400);
+        assert_eq!(validator.validate(&repaired).findings, vec![]);
+
+        // The bad print is brought back to the largest move
+        let repaired = validator.repair(&bars(), Repair::Clamp);
+        assert_eq!(
+            repaired[2],
+            OHLC::new(100.0, 101.0, 99.0, 100.0).at(MONDAY + 2 * 86_400)
+        );
+        let clamped = repaired
+            .iter()
+            .find(|bar| bar.time == MONDAY + 8 * 86_400)
+            .unwrap();
+        assert!(clamped.close > 101.0 && clamped.close < 1_000.0);
+        assert!(clamped.high >= clamped.close && clamped.low <= clamped.close);
+    }
+
+    #[test]
+    fn test_to_repair() {
+        assert_eq!(to_repair(""), Ok(None));
+        assert_eq!(to_repair("Fill"), Ok(Some(Repair::Fill)));
+        assert!(to_repair("guess").is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Candlestick charts drawn in the terminal
+//!
+//! Candles are drawn with box-drawing characters, one
+//! column per bar, next to a price axis and above a date
+//! axis. Only the latest bars that fit the width are shown.
+
+use super::color::{faint, reset};
+use super::{term, theme};
+use crate::price::{trend, OHLC};
+use std::io::Write;
+
+/// Rows between two labels of the price axis
+const LABEL_EVERY: usize = 4;
+
+/// Settings of a candlestick chart
+pub struct Chart {
+    /// Number of rows of the price area
+    pub height: usize,
+    /// Number of columns including the price axis
+    pub width: usize,
+    /// Periods of the simple moving averages drawn over the candles
+    pub overlays: Vec<usize>,
+    /// Level of precision after decimal of the price axis
+    pub precision: usize,
+}
+
+/// A single character of the chart and its color
+#[derive(Clone, Copy)]
+struct Cell {
+    glyph: char,
+    color: &'static str,
+}
+
+impl Cell {
+    const EMPTY: Cell = Cell {
+        glyph: ' ',
+        color: "",
+    };
+}
+
+impl Chart {
+    /// Create a chart sized to the width of the terminal
+    pub fn new(height: usize, precision: usize) -> Self {
+        Self {
+            height,
+            width: term::get_width(),
+            overlays: vec![],
+            precision,
+        }
+    }
+
+    /// Print the chart of a series of bars
+    pub fn show_chart<T: Write>(&self, bars: &[OHLC], mut output: T) {
+        if bars.is_empty() || self.height < 2 {
+            let _ = writeln!(output, "Chart: no bars");
+            return;
+        }
+
+        // Fit as many of the latest bars as the width allows
+        let label_width = bars
+            .iter()
+            .map(|bar| format!("{1:.0$}", self.precision, bar.high).len())
+            .max()
+            .unwrap_or_default();
+        let room = self.width.saturating_sub(label_width + 2).max(1);
+        let spacing = if room >= bars.len() * 2 { 2 } else { 1 };
+        let count = (room / spacing).clamp(1, bars.len());
+        let start = bars.len() - count;
+        let visible = &bars[start..];
+
+        // Moving averages aligned to the visible bars
+        let overlays: Vec<Vec<Option<f32>>> = self
+            .overlays
+            .iter()
+            .map(|period| {
+                let sma = trend::to_sma(bars, *period);
+                (start..bars.len())
+                    .map(|index| {
+                        let offset = index.checked_sub(period.saturating_sub(1))?;
+                        sma.get(offset).copied()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let prices = visible
+            .iter()
+            .flat_map(|bar| [bar.high, bar.low])
+            .chain(overlays.iter().flatten().flatten().copied());
+        let (mut top, mut bottom) = (f32::MIN, f32::MAX);
+        for price in prices {
+            top = top.max(price);
+            bottom = bottom.min(price);
+        }
+        let step = if top > bottom {
+            (top - bottom) / (self.height - 1) as f32
+        } else {
+            1.0
+        };
+        let row_of = |price: f32| (((top - price) / step).round() as usize).min(self.height - 1);
+
+        let symbols = theme::get_symbols();
+        let [tick_label, tick, corner, line] = symbols.axis;
+        let columns = (count - 1) * spacing + 1;
+        let mut grid = vec![vec![Cell::EMPTY; columns]; self.height];
+        for (i, bar) in visible.iter().enumerate() {
+            let color = theme::to_color(bar.to_direction());
+            let [wick, body, flat] = symbols.candle;
+            let body_top = row_of(bar.open.max(bar.close));
+            let body_bot = row_of(bar.open.min(bar.close));
+            for (row, line) in grid
+                .iter_mut()
+                .enumerate()
+                .take(row_of(bar.low) + 1)
+                .skip(row_of(bar.high))
+            {
+                let glyph = if row < body_top || row > body_bot {
+                    wick
+                } else if body_top == body_bot {
+                    flat
+                } else {
+                    body
+                };
+                line[i * spacing] = Cell { glyph, color };
+            }
+        }
+        for (overlay, glyph) in overlays.iter().zip(symbols.overlays.iter().cycle()) {
+            for (i, value) in overlay.iter().enumerate() {
+                if let Some(value) = value {
+                    let cell = &mut grid[row_of(*value)][i * spacing];
+                    if cell.glyph == ' ' {
+                        *cell = Cell {
+                            glyph: *glyph,
+                            color: faint(),
+                        };
+                    }
+                }
+            }
+        }
+
+        // Price axis and candles
+        for (row, line) in grid.iter().enumerate() {
+            if row % LABEL_EVERY == 0 || row == self.height - 1 {
+                let price = top - row as f32 * step;
+                let _ = write!(
+                    output,
+                    "{:>1$.2$} {3}",
+                    price, label_width, self.precision, tick_label
+                );
+            } else {
+                let _ = write!(output, "{:>1$} {2}", "", label_width, tick);
+            }
+            for cell in line {
+                if cell.color.is_empty() {
+                    let _ = write!(output, "{}", cell.glyph);
+                } else {
+                    let _ = write!(output, "{}{}{}", cell.color, cell.glyph, reset());
+                }
+            }
+            let _ = writeln!(output);
+        }
+
+        // Date axis
+        let _ = writeln!(
+            output,
+            "{:>2$} {3}{}",
+            "",
+            line.to_string().repeat(columns),
+            label_width,
+            corner
+        );
+        let dates = self.to_dates(visible, spacing, room);
+        if !dates.trim().is_empty() {
+            let _ = writeln!(output, "{:>2$}  {}", "", dates.trim_end(), label_width);
+        }
+
+        // Legend of overlays
+        if !self.overlays.is_empty() {
+            let legend: Vec<String> = self
+                .overlays
+                .iter()
+                .zip(symbols.overlays.iter().cycle())
+                .map(|(period, glyph)| format!("{}{}{} SMA({})", faint(), glyph, reset(), period))
+                .collect();
+            let _ = writeln!(output, "{:>2$}  {}", "", legend.join("  "), label_width);
+        }
+    }
+
+    /// Returns the date labels under the visible bars that
+    /// fit within `room` columns
+    ///
+    /// Labels switch between time, day and month depending
+    /// on the span of the bars
+    fn to_dates(&self, visible: &[OHLC], spacing: usize, room: usize) -> String {
+        let (Some(first), Some(last)) = (visible.first(), visible.last()) else {
+            return String::new();
+        };
+        let span = last.time - first.time;
+        let format = if span < 86_400 {
+            "%H:%M"
+        } else if span < 365 * 86_400 {
+            "%m-%d"
+        } else {
+            "%Y-%m"
+        };
+
+        let mut line = vec![' '; room];
+        let mut next = 0;
+        for (i, bar) in visible.iter().enumerate() {
+            let column = i * spacing;
+            if bar.time == 0 || column < next {
+                continue;
+            }
+            let Some(date) = chrono::DateTime::from_timestamp(bar.time, 0) else {
+                continue;
+            };
+            let label: Vec<char> = date.format(format).to_string().chars().collect();
+            if column + label.len() > line.len() {
+                break;
+            }
+            line[column..column + label.len()].copy_from_slice(&label);
+            next = column + label.len() + 2;
+        }
+        line.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(width: usize, overlays: Vec<usize>) -> Chart {
+        Chart {
+            height: 5,
+            width,
+            overlays,
+            precision: 1,
+        }
+    }
+
+    fn render(chart: &Chart, bars: &[OHLC]) -> String {
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        chart.show_chart(bars, &mut output);
+        String::from_utf8(output.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_show_chart() {
+        let bars = vec![
+            OHLC::new(10.0, 14.0, 10.0, 13.0).at(1_700_000_000),
+            OHLC::new(13.0, 13.0, 11.0, 11.0).at(1_700_086_400),
+        ];
+        assert_eq!(
+            render(&chart(40, vec![]), &bars),
+            format!(
+                "14.0 ┤{g}│{r}  \n     │{g}┃{r} {d}┃{r}\n     │{g}┃{r} {d}┃{r}\n     │{g}┃{r} {d}┃{r}\n10.0 ┤{g}┃{r}  \n     └───\n      11-14\n",
+                g = theme::to_color('U'),
+                d = theme::to_color('D'),
+                r = reset()
+            )
+        );
+    }
+
+    #[test]
+    fn test_show_chart_width() {
+        let bars: Vec<OHLC> = (0..50)
+            .map(|i| OHLC::new(10.0, 11.0, 9.0, 10.0 + i as f32 / 10.0))
+            .collect();
+        let text = render(&chart(20, vec![]), &bars);
+        let axis = text.lines().nth(5).unwrap();
+        // 20 columns minus the label and axis leaves 14 bars
+        assert_eq!(axis, format!("     └{}", "─".repeat(14)));
+        // Bars without time have no date labels
+        assert_eq!(text.lines().count(), 6);
+    }
+
+    #[test]
+    fn test_show_chart_overlays() {
+        let bars: Vec<OHLC> = (0..6)
+            .map(|i| {
+                let base = 10.0 + i as f32;
+                OHLC::new(base, base + 0.2, base - 0.2, base + 0.1)
+            })
+            .collect();
+        let text = render(&chart(40, vec![3]), &bars);
+        assert!(text.contains(&format!("{}•{}", faint(), reset())));
+        assert!(text.ends_with(&format!("{}•{} SMA(3)\n", faint(), reset())));
+    }
+
+    #[test]
+    fn test_show_chart_empty() {
+        assert_eq!(render(&chart(40, vec![]), &[]), "Chart: no bars\n");
+    }
+}
@@ -1,5 +1,199 @@
-pub const RESET: &str = "\x1b[0m";
-pub const RED: &str = "\x1b[38;2;255;0;0m";
-pub const GREEN: &str = "\x1b[38;2;0;255;0m";
-pub const YELLOW: &str = "\x1b[38;2;255;255;0m";
-pub const FAINT: &str = "\x1b[2m";
+//! Colors of the output and what the terminal supports
+//!
+//! Escape codes are picked from the current `Level` so the
+//! same output works in truecolor terminals, older terminals
+//! and pipes. The level is set once from `--color`,
+//! `NO_COLOR`, `CLICOLOR_FORCE` and the terminal type.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Colors a terminal is able to show
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    /// Plain text without escape codes
+    None = 0,
+    /// The 16 basic ANSI colors
+    Ansi16 = 1,
+    /// The 256 color palette
+    Ansi256 = 2,
+    /// 24-bit colors
+    TrueColor = 3,
+}
+
+/// Colors used by the output
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Orange,
+}
+
+/// Level of the output, truecolor until set otherwise
+static LEVEL: AtomicU8 = AtomicU8::new(Level::TrueColor as u8);
+
+/// Set the level of colors of the output
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Get the level of colors of the output
+pub fn get_level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::None,
+        1 => Level::Ansi16,
+        2 => Level::Ansi256,
+        _ => Level::TrueColor,
+    }
+}
+
+/// Returns the level of colors for a `--color` value
+///
+/// `auto` turns colors off when `NO_COLOR` is not empty or
+/// the output is not a terminal unless `CLICOLOR_FORCE` is
+/// set other than `0`. `always` only keeps the palette of
+/// the terminal
+///
+/// `vars` looks up environment variables
+pub fn to_level<F: Fn(&str) -> Option<String>>(
+    choice: &str,
+    is_terminal: bool,
+    vars: F,
+) -> Result<Level, String> {
+    let is_forced = vars("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+    // Any value of `NO_COLOR` counts, even `0`
+    let is_no_color = vars("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let palette = match (vars("COLORTERM"), vars("TERM")) {
+        (Some(colorterm), _) if colorterm == "truecolor" || colorterm == "24bit" => {
+            Level::TrueColor
+        }
+        (_, Some(term)) if term.contains("256color") => Level::Ansi256,
+        _ => Level::Ansi16,
+    };
+    match choice {
+        "always" => Ok(palette),
+        "never" => Ok(Level::None),
+        "auto" | "" => {
+            if is_forced {
+                Ok(palette)
+            } else if is_no_color || !is_terminal || vars("TERM").is_some_and(|term| term == "dumb")
+            {
+                Ok(Level::None)
+            } else {
+                Ok(palette)
+            }
+        }
+        _ => Err(format!(
+            "invalid color '{}'; possibilities: 'auto' 'always' 'never'",
+            choice
+        )),
+    }
+}
+
+/// Returns the escape code of a color at a level
+pub fn to_code(color: Color, level: Level) -> &'static str {
+    match (level, color) {
+        (Level::None, _) => "",
+        (Level::Ansi16, Color::Red) => "\x1b[91m",
+        (Level::Ansi16, Color::Green) => "\x1b[92m",
+        (Level::Ansi16, Color::Yellow) => "\x1b[93m",
+        (Level::Ansi16, Color::Blue) => "\x1b[94m",
+        (Level::Ansi16, Color::Orange) => "\x1b[33m",
+        (Level::Ansi256, Color::Red) => "\x1b[38;5;196m",
+        (Level::Ansi256, Color::Green) => "\x1b[38;5;46m",
+        (Level::Ansi256, Color::Yellow) => "\x1b[38;5;226m",
+        (Level::Ansi256, Color::Blue) => "\x1b[38;5;33m",
+        (Level::Ansi256, Color::Orange) => "\x1b[38;5;208m",
+        (Level::TrueColor, Color::Red) => "\x1b[38;2;255;0;0m",
+        (Level::TrueColor, Color::Green) => "\x1b[38;2;0;255;0m",
+        (Level::TrueColor, Color::Yellow) => "\x1b[38;2;255;255;0m",
+        (Level::TrueColor, Color::Blue) => "\x1b[38;2;0;114;178m",
+        (Level::TrueColor, Color::Orange) => "\x1b[38;2;230;159;0m",
+    }
+}
+
+/// Returns the escape code of a color at the current level
+pub fn get_code(color: Color) -> &'static str {
+    to_code(color, get_level())
+}
+
+pub fn red() -> &'static str {
+    get_code(Color::Red)
+}
+
+pub fn green() -> &'static str {
+    get_code(Color::Green)
+}
+
+pub fn yellow() -> &'static str {
+    get_code(Color::Yellow)
+}
+
+pub fn faint() -> &'static str {
+    match get_level() {
+        Level::None => "",
+        _ => "\x1b[2m",
+    }
+}
+
+pub fn reset() -> &'static str {
+    match get_level() {
+        Level::None => "",
+        _ => "\x1b[0m",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_to_level() {
+        let truecolor = [("COLORTERM", "truecolor")];
+        assert_eq!(
+            to_level("auto", true, env(&truecolor)),
+            Ok(Level::TrueColor)
+        );
+        assert_eq!(to_level("auto", false, env(&truecolor)), Ok(Level::None));
+        assert_eq!(
+            to_level("always", false, env(&truecolor)),
+            Ok(Level::TrueColor)
+        );
+        assert_eq!(to_level("never", true, env(&truecolor)), Ok(Level::None));
+
+        let term = [("TERM", "xterm-256color"), ("NO_COLOR", "1")];
+        assert_eq!(to_level("auto", true, env(&term)), Ok(Level::None));
+        assert_eq!(to_level("always", true, env(&term)), Ok(Level::Ansi256));
+        let zero = [("TERM", "xterm-256color"), ("NO_COLOR", "0")];
+        assert_eq!(to_level("auto", true, env(&zero)), Ok(Level::None));
+        let empty = [("TERM", "xterm-256color"), ("NO_COLOR", "")];
+        assert_eq!(to_level("auto", true, env(&empty)), Ok(Level::Ansi256));
+
+        let forced = [("TERM", "xterm"), ("CLICOLOR_FORCE", "1")];
+        assert_eq!(to_level("", false, env(&forced)), Ok(Level::Ansi16));
+        let unforced = [("TERM", "dumb"), ("CLICOLOR_FORCE", "0")];
+        assert_eq!(to_level("auto", true, env(&unforced)), Ok(Level::None));
+
+        assert!(to_level("sometimes", true, env(&[])).is_err());
+    }
+
+    #[test]
+    fn test_to_code() {
+        assert_eq!(to_code(Color::Red, Level::None), "");
+        assert_eq!(to_code(Color::Red, Level::Ansi16), "\x1b[91m");
+        assert_eq!(to_code(Color::Green, Level::Ansi256), "\x1b[38;5;46m");
+        assert_eq!(
+            to_code(Color::Yellow, Level::TrueColor),
+            "\x1b[38;2;255;255;0m"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Compact one line summaries of tickers
+//!
+//! Made for status bars (tmux, polybar) and listing
+//! several tickers at once.
+
+use super::color::reset;
+use super::theme;
+use crate::price::{to_style, History, OHLC};
+use std::io::Write;
+
+/// Returns a sparkline of the closes of a series of bars
+///
+/// Flat closes are drawn at mid height
+pub fn to_sparkline(bars: &[OHLC]) -> String {
+    let blocks = theme::get_symbols().blocks;
+    let low = bars.iter().map(|bar| bar.close).fold(f32::MAX, f32::min);
+    let high = bars.iter().map(|bar| bar.close).fold(f32::MIN, f32::max);
+    bars.iter()
+        .map(|bar| {
+            if high > low {
+                let level = (bar.close - low) / (high - low) * (blocks.len() - 1) as f32;
+                blocks[level.round() as usize]
+            } else {
+                blocks[blocks.len() / 2 - 1]
+            }
+        })
+        .collect()
+}
+
+/// Write a one line summary of a ticker
+///
+/// Shows the last close, the change of the day, a sparkline
+/// of the latest `count` closes, the range of the day and
+/// the session of the market such as `open` when known
+///
+/// To prevent losing precision specfiy
+/// the level of `precision` after decimal needed
+pub fn show_compact<T: Write>(
+    ticker: &str,
+    history: &History,
+    market: Option<&str>,
+    mut output: T,
+    count: usize,
+    precision: usize,
+) {
+    let bars = &history.timeframe;
+    let Some(day) = history.daily.last().or(bars.last()) else {
+        let _ = writeln!(output, "{} n/a", ticker);
+        return;
+    };
+    let close = bars.last().map(|bar| bar.close).unwrap_or(day.close);
+    let (color, arrow, _) = to_style(day.to_direction());
+    let _ = writeln!(
+        output,
+        "{ticker} {close:.precision$} {color}{arrow} {point:+.precision$} ({percent:+.2}%){reset} {sparkline} {low:.precision$}-{high:.precision$}{market}",
+        point = day.to_point(),
+        percent = day.to_percent() * 100.0,
+        sparkline = to_sparkline(&bars[bars.len().saturating_sub(count)..]),
+        low = day.low,
+        high = day.high,
+        market = market.map(|market| format!(" {}", market)).unwrap_or_default(),
+        reset = reset()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_sparkline() {
+        let bars: Vec<OHLC> = [1.0, 2.0, 8.0, 4.5]
+            .iter()
+            .map(|close| OHLC::new(1.0, 8.0, 1.0, *close))
+            .collect();
+        assert_eq!(to_sparkline(&bars), "▁▂█▅");
+        assert_eq!(to_sparkline(&bars[..1]), "▄");
+        assert_eq!(to_sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_show_compact() {
+        let bars = vec![
+            OHLC::new(100.0, 102.0, 99.0, 101.0).at(1_710_374_400),
+            OHLC::new(101.0, 103.0, 100.5, 102.0).at(1_710_378_000),
+        ];
+        let history = History::from_bars(bars);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_compact("AAPL", &history, None, &mut output, 20, 2);
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "AAPL 102.00 \x1b[38;2;0;255;0m⬆ +2.00 (+2.00%)\x1b[0m ▁█ 99.00-103.00\n"
+        );
+        let mut output: Vec<u8> = vec![];
+        show_compact("AAPL", &history, Some("closed"), &mut output, 20, 2);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("99.00-103.00 closed\n"));
+
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        show_compact(
+            "MSFT",
+            &History::from_bars(vec![]),
+            None,
+            &mut output,
+            20,
+            2,
+        );
+        assert_eq!(output.into_inner(), b"MSFT n/a\n");
+    }
+}
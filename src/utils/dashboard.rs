@@ -0,0 +1,434 @@
+//! Full-screen interactive dashboard
+//!
+//! A watchlist of tickers sits next to the chart and
+//! indicators of the selected ticker, with the log messages
+//! underneath. Bars come from a `Provider` so it runs fully
+//! against data files. Messages of providers are captured
+//! into the log pane while the dashboard is open.
+//!
+//! Keys: `↑`/`↓` or `k`/`j` select a ticker, `←`/`→` or
+//! `h`/`l` switch the timeframe, `r` reloads and `q` quits
+
+use super::chart::Chart;
+use super::color::{faint, reset};
+use super::stream::{self, Level};
+use super::{term, theme};
+use crate::data::Provider;
+use crate::price::resample::Timeframe;
+use crate::price::{ichimoku, swing, to_style, trend, History, OHLC};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::Write;
+
+/// Columns of the watchlist pane
+const WATCHLIST_WIDTH: usize = 28;
+
+/// Rows of the indicator pane
+const INDICATOR_ROWS: usize = 6;
+
+/// Rows of the log pane
+const LOG_ROWS: usize = 5;
+
+/// Log messages kept for the log pane
+const LOG_LIMIT: usize = 100;
+
+/// Smallest terminal the panes fit in
+const MIN_SIZE: (usize, usize) = (60, 20);
+
+/// State of the dashboard
+pub struct Dashboard<P: Provider> {
+    provider: P,
+    tickers: Vec<String>,
+    histories: Vec<Option<History>>,
+    selected: usize,
+    /// Index in `Timeframe::ALL` or `None` for the bars as loaded
+    timeframe: Option<usize>,
+    logs: Vec<String>,
+    /// Most detailed level of messages in the log pane
+    level: Level,
+    /// Messages kept off stderr for the log pane
+    capture: stream::Capture,
+    precision: usize,
+}
+
+impl<P: Provider> Dashboard<P> {
+    /// Create a dashboard and load the history of every ticker
+    ///
+    /// Log messages more detailed than `level` are hidden
+    pub fn new(provider: P, tickers: Vec<String>, level: Level, precision: usize) -> Self {
+        let mut dashboard = Self {
+            provider,
+            histories: vec![],
+            tickers,
+            selected: 0,
+            timeframe: Some(1),
+            logs: vec![],
+            level,
+            capture: stream::capture(),
+            precision,
+        };
+        dashboard.reload();
+        dashboard
+    }
+
+    /// Add a message of a `level` to the log pane
+    pub fn log(&mut self, level: Level, msg: &str) {
+        stream::emit(level, msg);
+        self.drain_logs();
+    }
+
+    /// Move the captured messages into the log pane
+    fn drain_logs(&mut self) {
+        for (level, msg) in self.capture.drain() {
+            if level <= self.level {
+                self.logs.extend(msg.lines().map(|line| line.to_string()));
+            }
+        }
+        let overflow = self.logs.len().saturating_sub(LOG_LIMIT);
+        self.logs.drain(..overflow);
+    }
+
+    /// Load the history of every ticker again
+    pub fn reload(&mut self) {
+        self.histories.clear();
+        for i in 0..self.tickers.len() {
+            let ticker = self.tickers[i].clone();
+            let history = self.provider.get_history(&ticker);
+            self.drain_logs();
+            match history {
+                Ok(history) => {
+                    self.log(
+                        Level::Info,
+                        &format!("Loaded {}: {} bars", ticker, history.timeframe.len()),
+                    );
+                    self.histories.push(Some(history));
+                }
+                Err(err) => {
+                    self.log(Level::Error, &err.for_ticker(&ticker));
+                    self.histories.push(None);
+                }
+            }
+        }
+    }
+
+    /// Get the name of the selected timeframe
+    pub fn get_timeframe_name(&self) -> &'static str {
+        match self.timeframe {
+            Some(index) => Timeframe::ALL[index].to_name(),
+            None => "bars",
+        }
+    }
+
+    /// Get the bars of the selected ticker and timeframe
+    pub fn get_bars(&self) -> &[OHLC] {
+        let Some(Some(history)) = self.histories.get(self.selected) else {
+            return &[];
+        };
+        match self.timeframe {
+            Some(index) => history.get_timeframe(Timeframe::ALL[index]),
+            None => &history.timeframe,
+        }
+    }
+
+    /// Update the state from a key press
+    ///
+    /// Returns `false` when the dashboard should close
+    pub fn handle(&mut self, key: KeyCode) -> bool {
+        let count = self.tickers.len().max(1);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1) % count,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.timeframe = match self.timeframe {
+                    None => Some(Timeframe::ALL.len() - 1),
+                    Some(0) => None,
+                    Some(index) => Some(index - 1),
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.timeframe = match self.timeframe {
+                    None => Some(0),
+                    Some(index) if index + 1 == Timeframe::ALL.len() => None,
+                    Some(index) => Some(index + 1),
+                }
+            }
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Returns the lines of the whole screen
+    pub fn to_frame(&self, width: usize, height: usize) -> Vec<String> {
+        if width < MIN_SIZE.0 || height < MIN_SIZE.1 {
+            let mut frame = vec![term::to_fit(
+                &format!("Terminal too small: need {}x{}", MIN_SIZE.0, MIN_SIZE.1),
+                width,
+            )];
+            frame.resize(height, term::to_fit("", width));
+            return frame;
+        }
+        let rule = theme::get_symbols().rule;
+        let right_width = width - WATCHLIST_WIDTH - 1;
+        let body_rows = height - LOG_ROWS - 4;
+        let chart_rows = body_rows - INDICATOR_ROWS - 1;
+
+        let mut frame = vec![term::to_fit(&self.to_title(), width)];
+        frame.push(rule.to_string().repeat(width));
+
+        let watchlist = self.to_watchlist();
+        let bars = self.get_bars();
+        let chart = Chart {
+            height: chart_rows - 2,
+            width: right_width,
+            overlays: vec![],
+            precision: self.precision,
+        };
+        let mut right = to_lines(|output| chart.show_chart(bars, output));
+        right.resize(chart_rows, String::new());
+        right.push(rule.to_string().repeat(right_width));
+        right.extend(to_lines(|output| self.show_indicators(bars, output)));
+        for row in 0..body_rows {
+            frame.push(format!(
+                "{}{}{}",
+                term::to_fit(watchlist.get(row).map_or("", |line| line), WATCHLIST_WIDTH),
+                theme::get_symbols().axis[1],
+                term::to_fit(right.get(row).map_or("", |line| line), right_width)
+            ));
+        }
+
+        frame.push(rule.to_string().repeat(width));
+        let start = self.logs.len().saturating_sub(LOG_ROWS);
+        for row in 0..LOG_ROWS {
+            let line = self.logs.get(start + row).map_or("", |line| line);
+            frame.push(term::to_fit(line, width));
+        }
+        frame.push(term::to_fit(
+            &format!(
+                "{}↑/↓ ticker  ←/→ timeframe  r reload  q quit{}",
+                faint(),
+                reset()
+            ),
+            width,
+        ));
+        frame
+    }
+
+    /// Returns the title with the selected ticker and timeframe
+    fn to_title(&self) -> String {
+        let ticker = self.tickers.get(self.selected).map_or("", |ticker| ticker);
+        let names = std::iter::once("bars").chain(Timeframe::ALL.iter().map(|tf| tf.to_name()));
+        let tabs: Vec<String> = names
+            .map(|name| {
+                if name == self.get_timeframe_name() {
+                    format!("[{}]", name)
+                } else {
+                    format!(" {} ", name)
+                }
+            })
+            .collect();
+        format!(" {}  {}", ticker, tabs.join(""))
+    }
+
+    /// Returns a line per ticker with its close and daily change
+    fn to_watchlist(&self) -> Vec<String> {
+        self.tickers
+            .iter()
+            .zip(&self.histories)
+            .enumerate()
+            .map(|(i, (ticker, history))| {
+                let marker = if i == self.selected { '>' } else { ' ' };
+                let last = history
+                    .as_ref()
+                    .and_then(|history| history.daily.last().or(history.timeframe.last()));
+                let Some(day) = last else {
+                    return format!("{} {:<8} n/a", marker, ticker);
+                };
+                let (color, arrow, _) = to_style(day.to_direction());
+                format!(
+                    "{marker} {ticker:<8} {close:>8.precision$} {color}{arrow} {percent:+.2}%{reset}",
+                    close = day.close,
+                    precision = self.precision,
+                    percent = day.to_percent() * 100.0,
+                    reset = reset(),
+                )
+            })
+            .collect()
+    }
+
+    /// Print the latest bar and indicators of a series of bars
+    fn show_indicators<T: Write>(&self, bars: &[OHLC], mut output: T) {
+        let Some(last) = bars.last() else {
+            return;
+        };
+        last.show_ohlc(&mut output, self.precision);
+        trend::show_trend(bars, &mut output, 14, self.precision);
+        ichimoku::to_ichimoku(bars, None, None, None).show_ichimoku(
+            bars,
+            &mut output,
+            self.precision,
+        );
+        let swings = swing::to_fractals(bars, 2);
+        swing::show_zones(
+            &swing::to_zones(bars, &swings, None),
+            2,
+            &mut output,
+            self.precision,
+        );
+    }
+
+    /// Open the dashboard until it is closed with `q`
+    pub fn run(&mut self) -> Result<(), String> {
+        let mut stdout = std::io::stdout();
+        terminal::enable_raw_mode().map_err(|err| err.to_string())?;
+        let _ = execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide);
+        let result = self.run_loop(&mut stdout);
+        let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        result
+    }
+
+    /// Draw and handle key presses until closed
+    fn run_loop<T: Write>(&mut self, output: &mut T) -> Result<(), String> {
+        loop {
+            for (row, line) in self
+                .to_frame(term::get_width(), term::get_height())
+                .iter()
+                .enumerate()
+            {
+                let _ = queue!(output, cursor::MoveTo(0, row as u16));
+                let _ = write!(output, "{}", line);
+            }
+            let _ = output.flush();
+
+            match event::read().map_err(|err| err.to_string())? {
+                Event::Key(key) if key.kind == KeyEventKind::Press && !self.handle(key.code) => {
+                    return Ok(());
+                }
+                Event::Resize(..) => {
+                    let _ = queue!(output, terminal::Clear(terminal::ClearType::All));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns the lines written by a `show_*` call
+fn to_lines<F: FnOnce(&mut Vec<u8>)>(show: F) -> Vec<String> {
+    let mut buffer: Vec<u8> = vec![];
+    show(&mut buffer);
+    String::from_utf8_lossy(&buffer)
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Error, Result};
+
+    /// Provider of two days of hourly bars for `AAPL` only
+    /// warning about `TSLA`
+    struct MockProvider;
+
+    impl Provider for MockProvider {
+        fn get_bars(&self, ticker: &str) -> Result<Vec<OHLC>> {
+            if ticker == "TSLA" {
+                stream::warn("TSLA: cache is stale");
+            }
+            if ticker != "AAPL" {
+                return Err(Error::NotFound(ticker.to_string()));
+            }
+            Ok((0..48)
+                .map(|i| {
+                    let base = 100.0 + (i % 5) as f32;
+                    OHLC::new(base, base + 1.0, base - 1.0, base + 0.5)
+                        .at(1_710_374_400 + i * 3_600)
+                })
+                .collect())
+        }
+    }
+
+    fn dashboard() -> Dashboard<MockProvider> {
+        Dashboard::new(
+            MockProvider,
+            vec![String::from("AAPL"), String::from("MSFT")],
+            Level::Info,
+            2,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let dashboard = dashboard();
+        assert_eq!(
+            dashboard.logs,
+            vec!["Loaded AAPL: 48 bars", "MSFT: no data found"]
+        );
+        assert_eq!(dashboard.get_timeframe_name(), "daily");
+        assert_eq!(dashboard.get_bars().len(), 2);
+
+        let quiet = Dashboard::new(
+            MockProvider,
+            vec![String::from("AAPL"), String::from("MSFT")],
+            Level::Error,
+            2,
+        );
+        assert_eq!(quiet.logs, vec!["MSFT: no data found"]);
+    }
+
+    #[test]
+    fn test_capture() {
+        let dashboard = Dashboard::new(MockProvider, vec![String::from("TSLA")], Level::Info, 2);
+        assert_eq!(
+            dashboard.logs,
+            vec!["TSLA: cache is stale", "TSLA: no data found"]
+        );
+        drop(dashboard);
+        assert!(stream::capture().drain().is_empty());
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut dashboard = dashboard();
+        assert!(dashboard.handle(KeyCode::Left));
+        assert_eq!(dashboard.get_timeframe_name(), "hourly");
+        dashboard.handle(KeyCode::Char('h'));
+        assert_eq!(dashboard.get_timeframe_name(), "bars");
+        assert_eq!(dashboard.get_bars().len(), 48);
+        dashboard.handle(KeyCode::Char('h'));
+        assert_eq!(dashboard.get_timeframe_name(), "yearly");
+        dashboard.handle(KeyCode::Right);
+        assert_eq!(dashboard.get_timeframe_name(), "bars");
+
+        dashboard.handle(KeyCode::Up);
+        assert_eq!(dashboard.selected, 1);
+        assert!(dashboard.get_bars().is_empty());
+        dashboard.handle(KeyCode::Char('j'));
+        assert_eq!(dashboard.selected, 0);
+
+        dashboard.handle(KeyCode::Char('r'));
+        assert_eq!(dashboard.logs.len(), 4);
+        assert!(!dashboard.handle(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_to_frame() {
+        let dashboard = dashboard();
+        let frame = dashboard.to_frame(80, 24);
+        assert_eq!(frame.len(), 24);
+        assert!(frame[0].starts_with(" AAPL   bars  hourly [daily] weekly "));
+        assert!(frame[2].starts_with("> AAPL"));
+        assert!(frame[3].starts_with("  MSFT     n/a"));
+        assert_eq!(frame[17], "─".repeat(80));
+        assert!(frame[18].starts_with("Loaded AAPL: 48 bars"));
+        assert!(frame[19].starts_with("MSFT: no data found"));
+
+        let small = dashboard.to_frame(40, 10);
+        assert_eq!(small.len(), 10);
+        assert!(small[0].starts_with("Terminal too small"));
+    }
+}
@@ -1,2 +1,9 @@
+pub mod chart;
 pub mod color;
+pub mod compact;
+pub mod dashboard;
 pub mod stream;
+pub mod table;
+pub mod term;
+pub mod theme;
+pub mod watch;
@@ -1,13 +1,22 @@
-use super::color::{FAINT, GREEN, RED, RESET};
+use super::color::{faint, red, reset};
+use super::theme::to_color;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
 
 fn show_art() {
     println!(
         "{} **** ******  ******  *****        ***  **** ******      ***  ***{}",
-        GREEN, RESET
+        to_color('U'),
+        reset()
     );
     println!(
         "{} * **** ****  **  **  * ** *       * *  * **** ****    ***\\\\  //***{}",
-        GREEN, RESET
+        to_color('U'),
+        reset()
     );
     println!(" * *          **  **  * * * *      * *  * *           ***  \\\\//  ***");
     println!(" **** ******  **  **  * *   * *    * *  **** ******  ***    \\\\    ***");
@@ -16,24 +25,194 @@ fn show_art() {
     println!(" * *          **  **  * *      * * * *  * *           ***//    \\\\***",);
     println!(
         "{} * *          **  **  * *       * ** *  * *            ***      ***{}",
-        GREEN, RESET
+        to_color('U'),
+        reset()
     );
     println!(
         "{} ***          ******  * *        *** *  ***             ***    ***{}",
-        GREEN, RESET
+        to_color('U'),
+        reset()
     );
 }
 
-pub fn log<T: std::io::Write>(mut output: T, msg: &str, verbose: &bool) {
-    if *verbose {
+/// Levels of log messages from the most to the least important
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+    Trace = 4,
+}
+
+impl Level {
+    /// Returns the name of a level written in log files
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+/// Most detailed level shown, warnings until set otherwise
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+/// File capturing every message with a timestamp
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+thread_local! {
+    /// Number of captures alive on the thread and the messages
+    /// they took from stderr
+    static CAPTURED: RefCell<(usize, Vec<(Level, String)>)> = const { RefCell::new((0, vec![])) };
+}
+
+/// Guard taking the messages of its thread away from stderr
+/// until dropped
+///
+/// Made for full-screen views where stderr would draw over
+/// the screen. Messages of every level are kept so the view
+/// can filter them
+pub struct Capture {
+    _private: (),
+}
+
+/// Start capturing the messages of this thread
+pub fn capture() -> Capture {
+    CAPTURED.with(|captured| captured.borrow_mut().0 += 1);
+    Capture { _private: () }
+}
+
+impl Capture {
+    /// Take the messages captured so far, oldest first
+    pub fn drain(&self) -> Vec<(Level, String)> {
+        CAPTURED.with(|captured| std::mem::take(&mut captured.borrow_mut().1))
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        CAPTURED.with(|captured| {
+            let mut captured = captured.borrow_mut();
+            captured.0 -= 1;
+            if captured.0 == 0 {
+                captured.1.clear();
+            }
+        });
+    }
+}
+
+/// Set the most detailed level of messages shown
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Get the most detailed level of messages shown
+pub fn get_level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Error,
+        1 => Level::Warn,
+        2 => Level::Info,
+        3 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Returns the level shown for the number of `-v` given
+///
+/// `quiet` only shows errors
+pub fn to_level(verbosity: usize, quiet: bool) -> Level {
+    match (quiet, verbosity) {
+        (true, _) => Level::Error,
+        (false, 0) => Level::Warn,
+        (false, 1) => Level::Info,
+        (false, 2) => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Capture every following message in a file
+///
+/// Messages are appended to the file when it exists
+pub fn set_log_file(path: &Path) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        *log_file = Some(file);
+    }
+    Ok(())
+}
+
+/// Write a message of a `level` when it is not more
+/// detailed than `max`
+///
+/// Every message also goes to the log file if one is set
+pub fn log<T: Write>(mut output: T, level: Level, msg: &str, max: &Level) {
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        if let Some(file) = log_file.as_mut() {
+            let time = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+            let _ = writeln!(file, "{} {:<5} {}", time, level.to_name(), msg);
+        }
+    }
+    if level <= *max {
         let _ = writeln!(output, "{}", msg);
     }
 }
 
+/// Write a message of a `level` to stderr unless captured
+///
+/// Captured messages still go to the log file
+pub fn emit(level: Level, msg: &str) {
+    let is_captured = CAPTURED.with(|captured| {
+        let mut captured = captured.borrow_mut();
+        if captured.0 > 0 {
+            captured.1.push((level, msg.to_string()));
+        }
+        captured.0 > 0
+    });
+    match is_captured {
+        true => log(std::io::sink(), level, msg, &get_level()),
+        false => log(std::io::stderr().lock(), level, msg, &get_level()),
+    }
+}
+
+/// Write an error to stderr
+pub fn error(msg: &str) {
+    emit(Level::Error, msg);
+}
+
+/// Write a warning to stderr
+pub fn warn(msg: &str) {
+    emit(Level::Warn, msg);
+}
+
+/// Write information to stderr
+pub fn info(msg: &str) {
+    emit(Level::Info, msg);
+}
+
+/// Write a debug message to stderr
+pub fn debug(msg: &str) {
+    emit(Level::Debug, msg);
+}
+
+/// Write a trace message to stderr
+pub fn trace(msg: &str) {
+    emit(Level::Trace, msg);
+}
+
 pub fn show_usage(cmd_name: &str) {
     println!(
-        "Usage: {}{} [OPTIONS]... [COMMAND] TICKER{}",
-        FAINT, cmd_name, RESET
+        "Usage: {}{} [OPTIONS]... [COMMAND] TICKER...{}",
+        faint(),
+        cmd_name,
+        reset()
     );
     println!("Try '{} --help' for more information.", cmd_name);
 }
@@ -49,31 +228,139 @@ pub fn show_help(cmd_name: &str) {
     println!();
     // Usage list
     println!(
-        "Usage: {}{} [OPTIONS]... [COMMAND] TICKER{}",
-        FAINT, cmd_name, RESET
+        "Usage: {}{} [OPTIONS]... [COMMAND] TICKER...{}",
+        faint(),
+        cmd_name,
+        reset()
     );
-    println!("Example: {} -n 'appl'", cmd_name);
+    println!("Example: {} -n 'appl' 'msft'", cmd_name);
     println!();
     // Options list
     println!("Options:");
-    println!("\t{}-V, --version{}\t\tShow version info", FAINT, RESET);
-    println!("\t{}-v, --verbose{}\t\tVerbose", FAINT, RESET);
-    println!("\t{}-q, --quiet{}\t\tHide log messages", FAINT, RESET);
+    println!("\t{}-V, --version{}\t\tShow version info", faint(), reset());
+    println!(
+        "\t{}-v, --verbose{}\t\tShow info, debug (-vv) and trace (-vvv) messages",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-c, --color[=WHEN]{}\tColor the output: auto, always or never\n\t\t\t\t(NO_COLOR and CLICOLOR_FORCE are respected)",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-q, --quiet{}\t\tHide log messages except errors",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-l, --log-file=PATH{}\tAppend a timestamped trace of every message to PATH",
+        faint(),
+        reset()
+    );
     println!(
         "\t{}-o, --offline{}\t\tRun without network requests",
-        FAINT, RESET
+        faint(),
+        reset()
+    );
+    println!("\t{}-n, --nocache{}\t\tDo not create a cache file: {}WARNING: will delete cache file if exists{}", faint(), reset(), red(), reset());
+    println!(
+        "\t{}-w, --watch[=SECONDS]{}\tRefresh every SECONDS (default 10)",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-S, --stream[=BARS]{}\tBuild bars from live trades of a crypto pair: time\n\t\t\t\tsuch as 30s, 5m or daily (default 1m), ticks such\n\t\t\t\tas 100t, volume such as 50v or dollars such as 1e6$",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-a, --adjust[=WHAT]{}\tAdjust past prices for splits (default) or for\n\t\t\t\tsplits and dividends with 'all', 'raw' shows\n\t\t\t\tprices as sent (chart API bars are always\n\t\t\t\tsplit-adjusted)",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-k, --check[=REPAIR]{}\tReport bad bars, gaps and outliers, and repair\n\t\t\t\tthem with drop, fill or clamp",
+        faint(),
+        reset()
     );
-    println!("\t{}-n, --nocache{}\t\tDo not create a cache file: {}WARNING: will delete cache file if exists{}", FAINT, RESET, RED, RESET);
-    println!("\t{}-h, --help{}\t\tHelp menu", FAINT, RESET);
+    println!(
+        "\t{}-C, --columns=NAME,...{}\tHistory columns: date, open, high, low, close,\n\t\t\t\tvolume, change, direction and strength",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-e, --export=FILE{}\tWrite histories to FILE (.csv, .json with the\n\t\t\t\tserde feature, or .parquet and .arrow with the\n\t\t\t\tarrow feature), '{{ticker}}' in FILE is\n\t\t\t\treplaced by each ticker",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-s, --sort=[-]NAME{}\tSort history by a column, descending with '-'",
+        faint(),
+        reset()
+    );
+    println!(
+        "\t{}-t, --theme=NAME,...{}\tColors and symbols: default, colorblind, monochrome,\n\t\t\t\tunicode or ascii (also set by FINFO_THEME)",
+        faint(),
+        reset()
+    );
+    println!("\t{}-h, --help{}\t\tHelp menu", faint(), reset());
     println!();
     // Commands list
     println!("Commands:");
+    println!(
+        "\t{}dashboard{}\t\tFull-screen watchlist, chart and indicators",
+        faint(),
+        reset()
+    );
     println!(
         "\t{}history{}\t\t\tThe historical information of a ticker",
-        FAINT, RESET
+        faint(),
+        reset()
     );
+    println!();
+    // Data sources
+    println!("Data:");
+    println!("\tBars are read from TICKER.csv files in $FINFO_DATA or ~/.finfo/data");
+    println!("\twith the columns: time,open,high,low,close[,volume]");
+    if cfg!(feature = "arrow") {
+        println!("\tor TICKER.parquet and TICKER.arrow files with the same columns");
+    }
+    if cfg!(feature = "http") {
+        println!("\tOther tickers are fetched from the chart API at $FINFO_URL");
+        println!("\tand cached for 15 minutes in $FINFO_CACHE or ~/.finfo/cache");
+        println!("\tor from the providers routed to in $FINFO_ROUTES or ~/.finfo/routes");
+        println!("\twith lines such as: *-USD = binance, coinbase, yahoo");
+        println!("\tand min_bars = N to fall back on fewer bars than N");
+        println!("\tWithout the file crypto pairs go to binance, coinbase then yahoo");
+    }
+    println!("\tMarket hours are bundled for NYSE, Nasdaq, LSE and crypto and");
+    println!("\tadded to or replaced by calendars in $FINFO_CALENDARS or ~/.finfo/calendars");
+    println!("\tand picked by the exchange code of quotes, so other exchanges show none");
 }
 
 pub fn show_license(cmd_name: &str) {
     println!("{} 0.1.0\nCopyright (C) 2021 Free Software Foundation, Inc.\nLicense GPLv3+: GNU GPL version 3 or later <https://gnu.org/licenses/gpl.html>.\nThis is free software: you are free to change and redistribute it.\nThere is NO WARRANTY, to the extent permitted by law.\n\nWritten by Samson Ayeni", cmd_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_level() {
+        assert_eq!(to_level(0, false), Level::Warn);
+        assert_eq!(to_level(2, false), Level::Debug);
+        assert_eq!(to_level(5, false), Level::Trace);
+        assert_eq!(to_level(3, true), Level::Error);
+    }
+
+    #[test]
+    fn test_log() {
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        log(&mut output, Level::Info, "shown", &Level::Debug);
+        log(&mut output, Level::Trace, "hidden", &Level::Debug);
+        log(&mut output, Level::Error, "error", &Level::Error);
+        assert_eq!(output.into_inner(), b"shown\nerror\n");
+    }
+}
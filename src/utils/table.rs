@@ -0,0 +1,410 @@
+//! Bars printed as a table of aligned columns
+//!
+//! Every column is as wide as its widest cell so prices of
+//! any size line up. Columns that do not fit the width are
+//! left out from the right.
+
+use super::color::reset;
+use super::{term, theme};
+use crate::price::{to_style, OHLC};
+use std::cmp::Ordering;
+use std::io::Write;
+
+/// Spaces between two columns
+const GAP: usize = 2;
+
+/// Columns of a table
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Column {
+    Date,
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    Change,
+    Direction,
+    Strength,
+}
+
+impl Column {
+    /// All columns in their default order
+    pub const ALL: [Column; 9] = [
+        Column::Date,
+        Column::Open,
+        Column::High,
+        Column::Low,
+        Column::Close,
+        Column::Volume,
+        Column::Change,
+        Column::Direction,
+        Column::Strength,
+    ];
+
+    /// Returns the name of a column used by options
+    pub fn to_name(&self) -> &'static str {
+        match self {
+            Column::Date => "date",
+            Column::Open => "open",
+            Column::High => "high",
+            Column::Low => "low",
+            Column::Close => "close",
+            Column::Volume => "volume",
+            Column::Change => "change",
+            Column::Direction => "direction",
+            Column::Strength => "strength",
+        }
+    }
+
+    /// Returns the header of a column
+    pub fn to_header(&self) -> &'static str {
+        match self {
+            Column::Date => "Date",
+            Column::Open => "Open",
+            Column::High => "High",
+            Column::Low => "Low",
+            Column::Close => "Close",
+            Column::Volume => "Volume",
+            Column::Change => "Change",
+            Column::Direction => "Direction",
+            Column::Strength => "Strong",
+        }
+    }
+
+    /// Returns the order of two bars in a column
+    ///
+    /// Dates compare the exact timestamps and directions sort
+    /// down, side then up
+    pub fn to_ordering(&self, a: &OHLC, b: &OHLC) -> Ordering {
+        match self {
+            Column::Date => a.time.cmp(&b.time),
+            _ => self.to_value(a).total_cmp(&self.to_value(b)),
+        }
+    }
+
+    /// Returns the number a bar is sorted by in a column
+    pub fn to_value(&self, bar: &OHLC) -> f32 {
+        match self {
+            Column::Date => bar.time as f32,
+            Column::Open => bar.open,
+            Column::High => bar.high,
+            Column::Low => bar.low,
+            Column::Close => bar.close,
+            Column::Volume => bar.volume,
+            Column::Change => bar.to_percent(),
+            Column::Direction => match bar.to_direction() {
+                'U' => 1.0,
+                'D' => -1.0,
+                _ => 0.0,
+            },
+            Column::Strength => bar.is_strong(None) as u8 as f32,
+        }
+    }
+
+    /// Numbers are aligned to the right
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Column::Date | Column::Direction | Column::Strength)
+    }
+}
+
+/// Settings of a table of bars
+pub struct Table {
+    pub columns: Vec<Column>,
+    /// Column to sort by and whether it is descending
+    pub sort: Option<(Column, bool)>,
+    /// Number of columns of text the table fits in
+    pub width: usize,
+    /// Level of precision after decimal of prices
+    pub precision: usize,
+}
+
+impl Table {
+    /// Create a table of every column sized to the width of
+    /// the terminal
+    pub fn new(precision: usize) -> Self {
+        Self {
+            columns: Column::ALL.to_vec(),
+            sort: None,
+            width: term::get_width(),
+            precision,
+        }
+    }
+
+    /// Print bars in rows under a header
+    pub fn show_table<T: Write>(&self, bars: &[OHLC], mut output: T) {
+        let mut rows: Vec<&OHLC> = bars.iter().collect();
+        if let Some((column, descending)) = self.sort {
+            rows.sort_by(|a, b| column.to_ordering(a, b));
+            if descending {
+                rows.reverse();
+            }
+        }
+        let date_format = to_date_format(bars);
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|bar| {
+                self.columns
+                    .iter()
+                    .map(|column| self.to_cell(bar, *column, date_format))
+                    .collect()
+            })
+            .collect();
+
+        // Keep the columns that fit from the left
+        let mut widths: Vec<usize> = vec![];
+        let mut used = 0;
+        for (i, column) in self.columns.iter().enumerate() {
+            let width = cells
+                .iter()
+                .map(|row| term::to_width(&row[i]))
+                .chain([column.to_header().len()])
+                .max()
+                .unwrap_or_default();
+            let gap = if widths.is_empty() { 0 } else { GAP };
+            if !widths.is_empty() && used + gap + width > self.width {
+                break;
+            }
+            used += gap + width;
+            widths.push(width);
+        }
+
+        let headers: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| column.to_header().to_string())
+            .collect();
+        self.show_row(&headers, &widths, None, &mut output);
+        let _ = writeln!(
+            output,
+            "{}",
+            theme::get_symbols().rule.to_string().repeat(used)
+        );
+        for (row, bar) in cells.iter().zip(&rows) {
+            self.show_row(row, &widths, Some(bar), &mut output);
+        }
+    }
+
+    /// Print one row of cells padded to the column widths
+    ///
+    /// Changes and directions are colored by the `bar` of the row
+    fn show_row<T: Write>(
+        &self,
+        row: &[String],
+        widths: &[usize],
+        bar: Option<&OHLC>,
+        mut output: T,
+    ) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+            let column = self.columns[i];
+            if i > 0 {
+                line.push_str(&" ".repeat(GAP));
+            }
+            let padding = " ".repeat(width - term::to_width(cell));
+            let color = match (column, bar) {
+                (Column::Direction | Column::Change, Some(bar)) => to_style(bar.to_direction()).0,
+                _ => "",
+            };
+            let cell = if color.is_empty() {
+                cell.to_string()
+            } else {
+                format!("{}{}{}", color, cell, reset())
+            };
+            if column.is_numeric() {
+                line.push_str(&padding);
+                line.push_str(&cell);
+            } else {
+                line.push_str(&cell);
+                // The last column needs no padding
+                if i + 1 < widths.len() {
+                    line.push_str(&padding);
+                }
+            }
+        }
+        let _ = writeln!(output, "{}", line);
+    }
+
+    /// Returns the text of a bar in a column
+    fn to_cell(&self, bar: &OHLC, column: Column, date_format: &str) -> String {
+        let precision = self.precision;
+        match column {
+            Column::Date => match chrono::DateTime::from_timestamp(bar.time, 0) {
+                Some(date) if bar.time != 0 => date.format(date_format).to_string(),
+                _ => String::from("-"),
+            },
+            Column::Open => format!("{:.1$}", bar.open, precision),
+            Column::High => format!("{:.1$}", bar.high, precision),
+            Column::Low => format!("{:.1$}", bar.low, precision),
+            Column::Close => format!("{:.1$}", bar.close, precision),
+            Column::Volume => to_volume(bar.volume),
+            Column::Change => format!("{:+.2}%", bar.to_percent() * 100.0),
+            Column::Direction => {
+                let (_, arrow, label) = to_style(bar.to_direction());
+                format!("{} {}", arrow, label)
+            }
+            Column::Strength => {
+                let symbols = theme::get_symbols();
+                if bar.is_strong(None) {
+                    symbols.strong.to_string()
+                } else {
+                    symbols.weak.to_string()
+                }
+            }
+        }
+    }
+}
+
+/// Returns the columns of a comma separated list of names
+pub fn to_columns(value: &str) -> Result<Vec<Column>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(to_column)
+        .collect()
+}
+
+/// Returns the column and order of a sort such as `close`
+/// or `-volume` for descending
+pub fn to_sort(value: &str) -> Result<(Column, bool), String> {
+    match value.trim().strip_prefix('-') {
+        Some(name) => Ok((to_column(name)?, true)),
+        None => Ok((to_column(value.trim())?, false)),
+    }
+}
+
+/// Returns the column of a name
+fn to_column(name: &str) -> Result<Column, String> {
+    Column::ALL
+        .iter()
+        .find(|column| column.to_name() == name)
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<String> = Column::ALL
+                .iter()
+                .map(|column| format!("'{}'", column.to_name()))
+                .collect();
+            format!(
+                "invalid column '{}'; possibilities: {}",
+                name,
+                names.join(" ")
+            )
+        })
+}
+
+/// Returns a volume shortened with a `K`, `M` or `B` suffix
+pub fn to_volume(volume: f32) -> String {
+    match volume.abs() {
+        v if v >= 1e9 => format!("{:.2}B", volume / 1e9),
+        v if v >= 1e6 => format!("{:.2}M", volume / 1e6),
+        v if v >= 1e3 => format!("{:.2}K", volume / 1e3),
+        _ => format!("{:.0}", volume),
+    }
+}
+
+/// Returns the date format of bars, with the time of day
+/// unless every bar opens at midnight
+fn to_date_format(bars: &[OHLC]) -> &'static str {
+    if bars.iter().all(|bar| bar.time % 86_400 == 0) {
+        "%Y-%m-%d"
+    } else {
+        "%Y-%m-%d %H:%M"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(columns: Vec<Column>, width: usize) -> Table {
+        Table {
+            columns,
+            sort: None,
+            width,
+            precision: 2,
+        }
+    }
+
+    fn render(table: &Table, bars: &[OHLC]) -> String {
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        table.show_table(bars, &mut output);
+        String::from_utf8(output.into_inner()).unwrap()
+    }
+
+    fn bars() -> Vec<OHLC> {
+        vec![
+            OHLC::new(9.5, 10.0, 9.0, 9.75)
+                .at(1_710_374_400)
+                .with_volume(1_500.0),
+            OHLC::new(101.0, 120.0, 100.0, 102.0)
+                .at(1_710_460_800)
+                .with_volume(2_000_000.0),
+        ]
+    }
+
+    #[test]
+    fn test_show_table() {
+        let columns = vec![Column::Date, Column::Close, Column::Volume];
+        assert_eq!(
+            render(&table(columns, 80), &bars()),
+            "Date         Close  Volume\n\
+             ──────────────────────────\n\
+             2024-03-14    9.75   1.50K\n\
+             2024-03-15  102.00   2.00M\n"
+        );
+    }
+
+    #[test]
+    fn test_show_table_sort() {
+        let mut table = table(vec![Column::Close, Column::Change], 80);
+        table.sort = Some((Column::Change, true));
+        let text = render(&table, &bars());
+        let rows: Vec<&str> = text.lines().skip(2).collect();
+        assert!(rows[0].starts_with("  9.75"));
+        assert!(rows[1].starts_with("102.00"));
+
+        // Timestamps a minute apart are equal once made f32
+        let bars = [
+            OHLC::new(1.0, 1.0, 1.0, 1.0).at(1_710_374_460),
+            OHLC::new(2.0, 2.0, 2.0, 2.0).at(1_710_374_400),
+        ];
+        assert_eq!(bars[0].time as f32, bars[1].time as f32);
+        table.columns = vec![Column::Close];
+        table.sort = Some((Column::Date, false));
+        let text = render(&table, &bars);
+        let rows: Vec<&str> = text.lines().skip(2).collect();
+        assert_eq!(rows, [" 2.00", " 1.00"]);
+    }
+
+    #[test]
+    fn test_show_table_width() {
+        let text = render(&table(Column::ALL.to_vec(), 20), &bars());
+        // Only date and open fit in 20 columns
+        assert_eq!(text.lines().next(), Some("Date          Open"));
+        assert!(text.lines().all(|line| term::to_width(line) <= 20));
+
+        // Wide symbols are padded by the columns they take
+        let table = table(vec![Column::Strength, Column::Close], 80);
+        let text = render(&table, &bars());
+        let widths: Vec<usize> = text.lines().map(term::to_width).collect();
+        assert_eq!(widths, [14, 14, 14, 14]);
+    }
+
+    #[test]
+    fn test_to_columns() {
+        assert_eq!(
+            to_columns("date, close"),
+            Ok(vec![Column::Date, Column::Close])
+        );
+        assert!(to_columns("date,price").is_err());
+        assert_eq!(to_sort("-volume"), Ok((Column::Volume, true)));
+        assert_eq!(to_sort("open"), Ok((Column::Open, false)));
+    }
+
+    #[test]
+    fn test_to_volume() {
+        assert_eq!(to_volume(950.0), "950");
+        assert_eq!(to_volume(12_345.0), "12.35K");
+        assert_eq!(to_volume(3_200_000_000.0), "3.20B");
+    }
+}
@@ -0,0 +1,106 @@
+use super::color::reset;
+
+/// Width used when the terminal size is unknown
+const DEFAULT_WIDTH: usize = 80;
+
+/// Height used when the terminal size is unknown
+const DEFAULT_HEIGHT: usize = 24;
+
+/// Symbols of the themes taking two columns in most terminals
+const WIDE: [char; 5] = ['⬆', '⬇', '⬌', '✅', '❌'];
+
+/// Get the width of the terminal in columns
+///
+/// Falls back to the `COLUMNS` environment variable
+/// then `80` when output is not a terminal
+pub fn get_width() -> usize {
+    match crossterm::terminal::size() {
+        Ok((columns, _)) if columns > 0 => columns as usize,
+        _ => std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH),
+    }
+}
+
+/// Get the height of the terminal in rows
+///
+/// Falls back to the `LINES` environment variable
+/// then `24` when output is not a terminal
+pub fn get_height() -> usize {
+    match crossterm::terminal::size() {
+        Ok((_, rows)) if rows > 0 => rows as usize,
+        _ => std::env::var("LINES")
+            .ok()
+            .and_then(|rows| rows.parse().ok())
+            .unwrap_or(DEFAULT_HEIGHT),
+    }
+}
+
+/// Returns the columns a text without escape sequences
+/// takes in a terminal
+pub fn to_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if WIDE.contains(&c) { 2 } else { 1 })
+        .sum()
+}
+
+/// Returns a line cut or padded with spaces to exactly
+/// `width` visible columns
+///
+/// Escape sequences are kept and do not count toward the
+/// width, a reset is added when the line had any
+pub fn to_fit(line: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut visible = 0;
+    let mut escaped = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Copy the whole sequence up to its final letter
+            escaped = true;
+            fitted.push(c);
+            for c in chars.by_ref() {
+                fitted.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        if c == '\n' || visible == width {
+            break;
+        }
+        fitted.push(c);
+        visible += 1;
+    }
+    if escaped {
+        fitted.push_str(reset());
+    }
+    fitted.push_str(&" ".repeat(width - visible));
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_width() {
+        assert_eq!(to_width("Open"), 4);
+        assert_eq!(to_width("⬆ Up"), 5);
+        assert_eq!(to_width("✅"), 2);
+        assert_eq!(to_width("[x]"), 3);
+    }
+
+    #[test]
+    fn test_to_fit() {
+        assert_eq!(to_fit("abc", 5), "abc  ");
+        assert_eq!(to_fit("abcdef", 3), "abc");
+        assert_eq!(
+            to_fit("\x1b[1m⬆ Up\x1b[0m tail", 4),
+            format!("\x1b[1m⬆ Up\x1b[0m{}", reset())
+        );
+        assert_eq!(to_fit("", 2), "  ");
+    }
+}
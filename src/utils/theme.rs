@@ -0,0 +1,190 @@
+//! Themes of the output
+//!
+//! A theme is a palette of direction colors and a set of
+//! symbols. Both are picked once from `--theme` or the
+//! `FINFO_THEME` environment variable, such as
+//! `colorblind` or `monochrome,ascii`.
+
+use super::color::{get_code, Color};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Colors of the directions, `None` leaves the text plain
+pub struct Palette {
+    pub name: &'static str,
+    pub up: Option<Color>,
+    pub down: Option<Color>,
+    pub side: Option<Color>,
+}
+
+/// Symbols of the directions, strength and charts
+pub struct Symbols {
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+    pub side: &'static str,
+    pub strong: &'static str,
+    pub weak: &'static str,
+    /// Wick, body and flat body of a candle
+    pub candle: [char; 3],
+    /// Labelled tick, plain tick, corner and line of an axis
+    pub axis: [char; 4],
+    /// Blocks of a sparkline from the lowest to the highest
+    pub blocks: [char; 8],
+    /// Glyphs of chart overlays in the order they are drawn
+    pub overlays: [char; 3],
+    /// Separator of panes
+    pub rule: char,
+}
+
+/// Available palettes, the first is the default
+pub const PALETTES: [Palette; 3] = [
+    Palette {
+        name: "default",
+        up: Some(Color::Green),
+        down: Some(Color::Red),
+        side: Some(Color::Yellow),
+    },
+    // Blue and orange stay apart for every kind of color blindness
+    Palette {
+        name: "colorblind",
+        up: Some(Color::Blue),
+        down: Some(Color::Orange),
+        side: None,
+    },
+    Palette {
+        name: "monochrome",
+        up: None,
+        down: None,
+        side: None,
+    },
+];
+
+/// Available symbol sets, the first is the default
+pub const SYMBOLS: [Symbols; 2] = [
+    Symbols {
+        name: "unicode",
+        up: "⬆",
+        down: "⬇",
+        side: "⬌",
+        strong: "✅",
+        weak: "❌",
+        candle: ['│', '┃', '━'],
+        axis: ['┤', '│', '└', '─'],
+        blocks: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+        overlays: ['•', '◦', '∙'],
+        rule: '─',
+    },
+    Symbols {
+        name: "ascii",
+        up: "^",
+        down: "v",
+        side: "~",
+        strong: "[x]",
+        weak: "[ ]",
+        candle: ['|', '#', '='],
+        axis: ['+', '|', '+', '-'],
+        blocks: ['_', '.', ',', '-', '~', '=', '*', '#'],
+        overlays: ['*', 'o', '.'],
+        rule: '-',
+    },
+];
+
+static PALETTE: AtomicUsize = AtomicUsize::new(0);
+static SYMBOL: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the palette of the output
+pub fn get_palette() -> &'static Palette {
+    &PALETTES[PALETTE.load(Ordering::Relaxed)]
+}
+
+/// Get the symbols of the output
+pub fn get_symbols() -> &'static Symbols {
+    &SYMBOLS[SYMBOL.load(Ordering::Relaxed)]
+}
+
+/// Returns the indexes in `PALETTES` and `SYMBOLS` of a
+/// comma separated list of names
+///
+/// Names left out keep the defaults
+pub fn to_theme(value: &str) -> Result<(usize, usize), String> {
+    let (mut palette, mut symbols) = (0, 0);
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if let Some(index) = PALETTES.iter().position(|p| p.name == name) {
+            palette = index;
+        } else if let Some(index) = SYMBOLS.iter().position(|s| s.name == name) {
+            symbols = index;
+        } else {
+            let names: Vec<String> = PALETTES
+                .iter()
+                .map(|p| p.name)
+                .chain(SYMBOLS.iter().map(|s| s.name))
+                .map(|name| format!("'{}'", name))
+                .collect();
+            return Err(format!(
+                "invalid theme '{}'; possibilities: {}",
+                name,
+                names.join(" ")
+            ));
+        }
+    }
+    Ok((palette, symbols))
+}
+
+/// Set the theme of the output from a list of names
+pub fn set_theme(value: &str) -> Result<(), String> {
+    let (palette, symbols) = to_theme(value)?;
+    PALETTE.store(palette, Ordering::Relaxed);
+    SYMBOL.store(symbols, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Returns the escape code of a direction `'U'`, `'D'` or `'N'`
+pub fn to_color(direction: char) -> &'static str {
+    let palette = get_palette();
+    let color = match direction {
+        'U' => palette.up,
+        'D' => palette.down,
+        _ => palette.side,
+    };
+    color.map_or("", get_code)
+}
+
+/// Returns the symbol of a direction `'U'`, `'D'` or `'N'`
+pub fn to_arrow(direction: char) -> &'static str {
+    let symbols = get_symbols();
+    match direction {
+        'U' => symbols.up,
+        'D' => symbols.down,
+        _ => symbols.side,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_theme() {
+        assert_eq!(to_theme(""), Ok((0, 0)));
+        assert_eq!(to_theme("colorblind"), Ok((1, 0)));
+        assert_eq!(to_theme("monochrome, ascii"), Ok((2, 1)));
+        assert_eq!(
+            to_theme("neon"),
+            Err(String::from(
+                "invalid theme 'neon'; possibilities: 'default' 'colorblind' 'monochrome' 'unicode' 'ascii'"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_to_color() {
+        assert_eq!(to_color('U'), "\x1b[38;2;0;255;0m");
+        assert_eq!(to_arrow('D'), "⬇");
+        assert_eq!(PALETTES[1].side, None);
+        assert_eq!(SYMBOLS[1].blocks.len(), SYMBOLS[0].blocks.len());
+    }
+}
@@ -0,0 +1,189 @@
+//! Periodic refresh of a view
+//!
+//! Every refresh renders the view again and highlights the
+//! numbers that changed since the previous one. A terminal is
+//! redrawn in place while other outputs get the new lines
+//! appended. Views taller than the terminal are redrawn from
+//! the top of a cleared screen.
+
+use super::color::reset;
+use super::term;
+use super::theme::to_color;
+use crossterm::{cursor, queue, terminal};
+use std::io::Write;
+use std::time::Duration;
+
+/// Seconds between refreshes when none are given
+pub const DEFAULT_INTERVAL: u64 = 10;
+
+/// State of a watched view
+pub struct Watch {
+    /// Time between two refreshes
+    pub interval: Duration,
+    /// Redraw in place instead of appending
+    pub in_place: bool,
+    /// Rows of the terminal, `None` asks the terminal
+    pub height: Option<usize>,
+    previous: String,
+}
+
+impl Watch {
+    pub fn new(interval: Duration, in_place: bool) -> Self {
+        Self {
+            interval,
+            in_place,
+            height: None,
+            previous: String::new(),
+        }
+    }
+
+    /// Print a rendered view over the previous one
+    pub fn show_frame<T: Write>(&mut self, frame: String, mut output: T) {
+        if self.in_place && !self.previous.is_empty() {
+            let rows = self.previous.lines().count();
+            // The cursor cannot move up past the top of the screen
+            if rows >= self.height.unwrap_or_else(term::get_height) {
+                let _ = queue!(
+                    output,
+                    terminal::Clear(terminal::ClearType::All),
+                    cursor::MoveTo(0, 0)
+                );
+            } else {
+                let _ = queue!(
+                    output,
+                    cursor::MoveToColumn(0),
+                    cursor::MoveUp(rows as u16),
+                    terminal::Clear(terminal::ClearType::FromCursorDown)
+                );
+            }
+        }
+        let _ = write!(output, "{}", to_highlight(&self.previous, &frame));
+        let _ = output.flush();
+        self.previous = frame;
+    }
+
+    /// Render and print a view every interval, forever
+    pub fn run<F: FnMut(&mut Vec<u8>), T: Write>(&mut self, mut render: F, mut output: T) {
+        loop {
+            let mut frame: Vec<u8> = vec![];
+            render(&mut frame);
+            self.show_frame(String::from_utf8_lossy(&frame).to_string(), &mut output);
+            std::thread::sleep(self.interval);
+        }
+    }
+}
+
+/// Returns the interval of a `--watch` value in seconds
+///
+/// An empty value is the default interval
+pub fn to_interval(value: &str) -> Result<Duration, String> {
+    if value.is_empty() {
+        return Ok(Duration::from_secs(DEFAULT_INTERVAL));
+    }
+    match value.parse::<f32>() {
+        Ok(seconds) if seconds > 0.0 => Ok(Duration::from_secs_f32(seconds)),
+        _ => Err(format!("invalid interval '{}'", value)),
+    }
+}
+
+/// Returns the current view with the numbers that went up
+/// colored green and the ones that went down colored red
+///
+/// Numbers are compared with the word at the same place in
+/// the `previous` view
+pub fn to_highlight(previous: &str, current: &str) -> String {
+    let mut previous_lines = previous.lines();
+    let mut highlighted = String::new();
+    for line in current.split_inclusive('\n') {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        let mut previous_words = previous_lines.next().unwrap_or_default().split(' ');
+        let words: Vec<String> = text
+            .split(' ')
+            .map(|word| {
+                let before = previous_words.next().and_then(to_number);
+                match (before, to_number(word)) {
+                    (Some(before), Some(after)) if after > before => {
+                        format!("{}{}{}", to_color('U'), word, reset())
+                    }
+                    (Some(before), Some(after)) if after < before => {
+                        format!("{}{}{}", to_color('D'), word, reset())
+                    }
+                    _ => word.to_string(),
+                }
+            })
+            .collect();
+        highlighted.push_str(&words.join(" "));
+        highlighted.push_str(newline);
+    }
+    highlighted
+}
+
+/// Returns the number in a word such as `+1.23` or `(0.66%)`
+///
+/// Words with escape sequences are skipped
+fn to_number(word: &str) -> Option<f32> {
+    if word.contains('\x1b') {
+        return None;
+    }
+    word.trim_matches(|c: char| matches!(c, '(' | ')' | '%' | '$' | ','))
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_highlight() {
+        assert_eq!(
+            to_highlight("AAPL 10.00 (+1.0%)\n", "AAPL 10.50 (+1.0%)\n"),
+            format!("AAPL {}10.50{} (+1.0%)\n", to_color('U'), reset())
+        );
+        assert_eq!(
+            to_highlight("A 2 x\nB 3", "A 1 x\nB 3\nC 4"),
+            format!("A {}1{} x\nB 3\nC 4", to_color('D'), reset())
+        );
+        assert_eq!(to_highlight("", "A 1\n"), "A 1\n");
+    }
+
+    #[test]
+    fn test_to_interval() {
+        assert_eq!(to_interval(""), Ok(Duration::from_secs(10)));
+        assert_eq!(to_interval("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(to_interval("0"), Err(String::from("invalid interval '0'")));
+        assert!(to_interval("soon").is_err());
+    }
+
+    #[test]
+    fn test_show_frame() {
+        let mut watch = Watch::new(Duration::from_secs(1), false);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        watch.show_frame(String::from("A 1\n"), &mut output);
+        watch.show_frame(String::from("A 2\n"), &mut output);
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            format!("A 1\nA {}2{}\n", to_color('U'), reset())
+        );
+
+        let mut watch = Watch::new(Duration::from_secs(1), true);
+        watch.height = Some(24);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        watch.show_frame(String::from("A 1\nB 1\n"), &mut output);
+        watch.show_frame(String::from("A 1\nB 1\n"), &mut output);
+        let text = String::from_utf8(output.into_inner()).unwrap();
+        // Cursor goes back up the two lines before redrawing
+        assert!(text.starts_with("A 1\nB 1\n\x1b[1G\x1b[2A\x1b[J"));
+        assert!(text.ends_with("\x1b[JA 1\nB 1\n"));
+
+        // Frames taller than the terminal start from a cleared screen
+        watch.height = Some(2);
+        let mut output: std::io::Cursor<Vec<u8>> = std::io::Cursor::new(Vec::<u8>::new());
+        watch.show_frame(String::from("A 1\nB 1\n"), &mut output);
+        let text = String::from_utf8(output.into_inner()).unwrap();
+        assert_eq!(text, "\x1b[2J\x1b[1;1HA 1\nB 1\n");
+    }
+}